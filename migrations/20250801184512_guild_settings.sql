@@ -0,0 +1,69 @@
+CREATE TABLE guild_settings (
+    guild_id INT PRIMARY KEY REFERENCES guilds(id) ON DELETE CASCADE,
+    -- general staff channel, used for raid alerts, dm activity and automod alerts.
+    staff_channel BIGINT,
+    -- channel discord automod posts flagged content into.
+    automod_channel BIGINT,
+    vc_status_alert_channel BIGINT,
+    invite_log_channel BIGINT,
+    mass_delete_channel BIGINT,
+    verification_log_channel BIGINT,
+    private_vc_channel BIGINT,
+    super_poop_role BIGINT,
+    super_poop_channel BIGINT,
+    moderator_roles BIGINT[] NOT NULL DEFAULT '{}',
+    raid_alerts BOOLEAN NOT NULL DEFAULT FALSE,
+    dm_activity_alerts BOOLEAN NOT NULL DEFAULT FALSE,
+    vc_status_alerts BOOLEAN NOT NULL DEFAULT FALSE,
+    invite_logging BOOLEAN NOT NULL DEFAULT FALSE,
+    mass_delete_alerts BOOLEAN NOT NULL DEFAULT FALSE,
+    private_vcs BOOLEAN NOT NULL DEFAULT FALSE,
+    verification BOOLEAN NOT NULL DEFAULT FALSE,
+    auto_super_poop BOOLEAN NOT NULL DEFAULT FALSE
+);
+
+-- carry over what was previously hardcoded for gg/osu.
+INSERT INTO guilds (guild_id) VALUES (98226572468690944) ON CONFLICT (guild_id) DO NOTHING;
+
+INSERT INTO guild_settings (
+    guild_id,
+    staff_channel,
+    automod_channel,
+    vc_status_alert_channel,
+    invite_log_channel,
+    mass_delete_channel,
+    verification_log_channel,
+    private_vc_channel,
+    super_poop_role,
+    super_poop_channel,
+    moderator_roles,
+    raid_alerts,
+    dm_activity_alerts,
+    vc_status_alerts,
+    invite_logging,
+    mass_delete_alerts,
+    private_vcs,
+    verification,
+    auto_super_poop
+)
+SELECT
+    id,
+    158484765136125952,
+    697738506944118814,
+    1163544192866336808,
+    277163440999628800,
+    1284217769423798282,
+    776522946872344586,
+    1399817426723668039,
+    1384235804678684712,
+    1390062742274310317,
+    ARRAY[98459030455853056, 723115326195367936, 781213498998915123, 1062803266636873781, 150811709009821696]::BIGINT[],
+    TRUE,
+    TRUE,
+    TRUE,
+    TRUE,
+    TRUE,
+    TRUE,
+    TRUE,
+    TRUE
+FROM guilds WHERE guild_id = 98226572468690944;
@@ -0,0 +1,6 @@
+-- discord allows entries without an actor, these should still be stored.
+ALTER TABLE audit_log ALTER COLUMN user_id DROP NOT NULL;
+
+CREATE INDEX audit_log_guild_created_at_idx ON audit_log (guild_id, created_at DESC);
+CREATE INDEX audit_log_user_id_idx ON audit_log (user_id);
+CREATE INDEX audit_log_target_id_idx ON audit_log (target_id);
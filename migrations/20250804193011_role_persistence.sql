@@ -0,0 +1,5 @@
+ALTER TABLE guild_settings
+    ADD COLUMN persistable_roles BIGINT[] NOT NULL DEFAULT '{}',
+    ADD COLUMN role_persistence BOOLEAN NOT NULL DEFAULT FALSE;
+
+CREATE INDEX role_snapshots_user_guild_idx ON role_snapshots (user_id, guild_id, snapshot_taken DESC);
@@ -0,0 +1 @@
+ALTER TABLE guild_settings ADD COLUMN ocr_scanning BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,50 @@
+-- raw usage older than the retention policy is rolled up into these, one row per day.
+CREATE TABLE emote_usage_daily (
+    day DATE NOT NULL,
+    guild_id INT REFERENCES guilds(id),
+    emote_id INT NOT NULL REFERENCES emotes(id),
+    user_id INT NOT NULL REFERENCES users(id),
+    usage_type EmoteUsageType NOT NULL,
+    uses BIGINT NOT NULL,
+    UNIQUE NULLS NOT DISTINCT (day, guild_id, emote_id, user_id, usage_type)
+);
+
+CREATE INDEX emote_usage_daily_guild_emote_idx ON emote_usage_daily (guild_id, emote_id);
+
+CREATE TABLE sticker_usage_daily (
+    day DATE NOT NULL,
+    guild_id INT REFERENCES guilds(id),
+    sticker_id BIGINT NOT NULL REFERENCES stickers(sticker_id),
+    user_id INT NOT NULL REFERENCES users(id),
+    uses BIGINT NOT NULL,
+    UNIQUE NULLS NOT DISTINCT (day, guild_id, sticker_id, user_id)
+);
+
+CREATE TABLE executed_commands_daily (
+    day DATE NOT NULL,
+    guild_id INT REFERENCES guilds(id),
+    user_id INT NOT NULL REFERENCES users(id),
+    command TEXT NOT NULL,
+    uses BIGINT NOT NULL,
+    failures BIGINT NOT NULL,
+    UNIQUE NULLS NOT DISTINCT (day, guild_id, user_id, command)
+);
+
+CREATE INDEX executed_commands_daily_day_idx ON executed_commands_daily (day);
+
+CREATE TABLE retention_runs (
+    id BIGSERIAL PRIMARY KEY,
+    table_name TEXT NOT NULL,
+    cutoff TIMESTAMPTZ NOT NULL,
+    rows_pruned BIGINT NOT NULL,
+    started_at TIMESTAMPTZ NOT NULL,
+    finished_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX retention_runs_finished_at_idx ON retention_runs (finished_at DESC);
+
+-- pruning scans by age and has to check for references before deleting messages.
+CREATE INDEX emote_usage_used_at_idx ON emote_usage (used_at);
+CREATE INDEX sticker_usage_used_at_idx ON sticker_usage (used_at);
+CREATE INDEX sticker_usage_message_id_idx ON sticker_usage (message_id);
+CREATE INDEX executed_commands_executed_at_idx ON executed_commands (executed_at);
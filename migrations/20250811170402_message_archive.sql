@@ -0,0 +1,16 @@
+-- channels opted into storing message content, see `message_revisions`.
+ALTER TABLE guild_settings ADD COLUMN archived_channels BIGINT[] NOT NULL DEFAULT '{}';
+
+ALTER TABLE messages ADD COLUMN deleted_at TIMESTAMPTZ;
+
+-- every version of a message in an archived channel, the first is the content it was sent with.
+-- archived content goes with the message when retention prunes it.
+CREATE TABLE message_revisions (
+    id BIGSERIAL PRIMARY KEY,
+    message_id BIGINT NOT NULL REFERENCES messages(id) ON DELETE CASCADE,
+    content TEXT NOT NULL,
+    attachments JSONB NOT NULL,
+    revised_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX message_revisions_message_id_idx ON message_revisions (message_id, revised_at);
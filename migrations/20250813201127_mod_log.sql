@@ -0,0 +1,19 @@
+CREATE TYPE ModLogEvent AS ENUM (
+    'message_edit',
+    'message_delete',
+    'member_join',
+    'member_leave',
+    'member_update',
+    'role_update',
+    'channel_update'
+);
+
+-- where each kind of event is logged to in a guild, either a channel or a webhook.
+CREATE TABLE mod_log_routes (
+    guild_id INT NOT NULL REFERENCES guilds(id) ON DELETE CASCADE,
+    event ModLogEvent NOT NULL,
+    channel_id BIGINT,
+    webhook_url TEXT,
+    PRIMARY KEY (guild_id, event),
+    CHECK ((channel_id IS NULL) <> (webhook_url IS NULL))
+);
@@ -0,0 +1,5 @@
+-- heat needed before a delete is attributed, the heat cap and seconds without deletes before heat
+-- decays, null uses the defaults.
+ALTER TABLE guild_settings ADD COLUMN mass_delete_check_heat SMALLINT;
+ALTER TABLE guild_settings ADD COLUMN mass_delete_max_heat SMALLINT;
+ALTER TABLE guild_settings ADD COLUMN mass_delete_decay_secs SMALLINT;
@@ -0,0 +1,33 @@
+-- raid detection thresholds and automatic responses, guilds without a row use the defaults.
+CREATE TABLE raid_config (
+    guild_id INT PRIMARY KEY REFERENCES guilds(id) ON DELETE CASCADE,
+    age_window_secs INT NOT NULL,
+    cluster_size SMALLINT NOT NULL,
+    join_window_secs INT NOT NULL,
+    join_rate SMALLINT NOT NULL,
+    raise_verification BOOLEAN NOT NULL,
+    pause_invites BOOLEAN NOT NULL,
+    timeout_cluster BOOLEAN NOT NULL,
+    timeout_mins INT NOT NULL
+);
+
+-- recent joins, kept so detection still sees the joins from before a restart.
+CREATE TABLE raid_joins (
+    id BIGSERIAL PRIMARY KEY,
+    guild_id INT NOT NULL REFERENCES guilds(id) ON DELETE CASCADE,
+    user_id INT NOT NULL REFERENCES users(id),
+    username TEXT NOT NULL,
+    default_avatar BOOLEAN NOT NULL,
+    joined_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX raid_joins_guild_id_idx ON raid_joins (guild_id, joined_at);
+
+-- what the automatic responses changed, so an all clear can undo them after a restart.
+CREATE TABLE raid_lockdowns (
+    guild_id INT PRIMARY KEY REFERENCES guilds(id) ON DELETE CASCADE,
+    started_at TIMESTAMPTZ NOT NULL,
+    previous_verification_level SMALLINT,
+    invites_paused BOOLEAN NOT NULL,
+    timed_out_users BIGINT[] NOT NULL
+);
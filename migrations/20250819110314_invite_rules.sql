@@ -0,0 +1,13 @@
+CREATE TYPE InviteAction AS ENUM ('report', 'delete', 'warn', 'timeout');
+
+-- which guilds can be invited to, guilds without a row don't have invites enforced.
+CREATE TABLE invite_rules (
+    guild_id INT PRIMARY KEY REFERENCES guilds(id) ON DELETE CASCADE,
+    action InviteAction NOT NULL,
+    -- when empty, every guild that isn't denied is allowed.
+    allowed_guilds BIGINT[] NOT NULL DEFAULT '{}',
+    denied_guilds BIGINT[] NOT NULL DEFAULT '{}',
+    exempt_roles BIGINT[] NOT NULL DEFAULT '{}',
+    exempt_channels BIGINT[] NOT NULL DEFAULT '{}',
+    timeout_mins INT NOT NULL
+);
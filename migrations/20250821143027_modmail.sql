@@ -0,0 +1,18 @@
+-- forum channel that modmail threads are opened in.
+ALTER TABLE guild_settings ADD COLUMN modmail_channel BIGINT;
+ALTER TABLE guild_settings ADD COLUMN modmail BOOLEAN NOT NULL DEFAULT FALSE;
+
+CREATE TABLE modmail_threads (
+    id SERIAL PRIMARY KEY,
+    guild_id INT NOT NULL REFERENCES guilds(id) ON DELETE CASCADE,
+    user_id INT NOT NULL REFERENCES users(id),
+    thread_id BIGINT NOT NULL,
+    opened_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    closed_at TIMESTAMPTZ,
+    closed_by INT REFERENCES users(id),
+    -- written when the thread is closed.
+    transcript TEXT
+);
+
+-- a user can only have one open thread at a time.
+CREATE UNIQUE INDEX modmail_threads_open_idx ON modmail_threads (user_id) WHERE closed_at IS NULL;
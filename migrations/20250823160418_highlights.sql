@@ -0,0 +1,18 @@
+-- keywords and regexes members are dmed about when they're said in a guild.
+CREATE TABLE highlights (
+    id SERIAL PRIMARY KEY,
+    guild_id INT NOT NULL REFERENCES guilds(id) ON DELETE CASCADE,
+    user_id INT NOT NULL REFERENCES users(id),
+    pattern TEXT NOT NULL,
+    is_regex BOOLEAN NOT NULL,
+    UNIQUE (guild_id, user_id, pattern)
+);
+
+-- channels and users a member doesn't want to be highlighted by.
+CREATE TABLE highlight_settings (
+    guild_id INT NOT NULL REFERENCES guilds(id) ON DELETE CASCADE,
+    user_id INT NOT NULL REFERENCES users(id),
+    ignored_channels BIGINT[] NOT NULL DEFAULT '{}',
+    ignored_users BIGINT[] NOT NULL DEFAULT '{}',
+    PRIMARY KEY (guild_id, user_id)
+);
@@ -0,0 +1,13 @@
+-- every voice channel status change seen, user_id is null when the audit log didn't show who.
+CREATE TABLE vc_status_history (
+    id BIGSERIAL PRIMARY KEY,
+    guild_id INT NOT NULL REFERENCES guilds(id) ON DELETE CASCADE,
+    channel_id BIGINT NOT NULL,
+    user_id INT REFERENCES users(id),
+    old_status TEXT,
+    new_status TEXT,
+    blacklisted BOOLEAN NOT NULL,
+    changed_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX vc_status_history_guild_changed_at_idx ON vc_status_history (guild_id, changed_at DESC);
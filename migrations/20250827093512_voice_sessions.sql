@@ -0,0 +1,17 @@
+-- one row per continuous stay in a voice channel, ended_at is null while it is ongoing.
+CREATE TABLE voice_sessions (
+    id BIGSERIAL PRIMARY KEY,
+    guild_id INT NOT NULL REFERENCES guilds(id) ON DELETE CASCADE,
+    user_id INT NOT NULL REFERENCES users(id),
+    channel_id BIGINT NOT NULL,
+    started_at TIMESTAMPTZ NOT NULL,
+    ended_at TIMESTAMPTZ,
+    -- bumped periodically so sessions left open by a restart can be closed at a sensible time.
+    last_seen_at TIMESTAMPTZ NOT NULL,
+    muted_secs BIGINT NOT NULL DEFAULT 0,
+    deafened_secs BIGINT NOT NULL DEFAULT 0
+);
+
+CREATE UNIQUE INDEX voice_sessions_open_idx ON voice_sessions (guild_id, user_id) WHERE ended_at IS NULL;
+CREATE INDEX voice_sessions_guild_started_at_idx ON voice_sessions (guild_id, started_at DESC);
+CREATE INDEX voice_sessions_guild_user_idx ON voice_sessions (guild_id, user_id);
@@ -0,0 +1,20 @@
+CREATE TYPE NameChangeKind AS ENUM (
+    'username',
+    'display_name',
+    'nickname',
+    'avatar',
+    'guild_avatar'
+);
+
+-- name and avatar changes of members, guild_id is only set for per-guild values like nicknames.
+CREATE TABLE name_history (
+    id BIGSERIAL PRIMARY KEY,
+    user_id INT NOT NULL REFERENCES users(id),
+    guild_id INT REFERENCES guilds(id) ON DELETE CASCADE,
+    kind NameChangeKind NOT NULL,
+    old_value TEXT,
+    new_value TEXT,
+    changed_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX name_history_user_changed_at_idx ON name_history (user_id, changed_at DESC);
@@ -0,0 +1,28 @@
+ALTER TABLE guild_settings ADD COLUMN presence_history BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- games members played, short sessions are dropped and quick restarts are merged into one row.
+CREATE TABLE presence_activities (
+    id BIGSERIAL PRIMARY KEY,
+    guild_id INT NOT NULL REFERENCES guilds(id) ON DELETE CASCADE,
+    user_id INT NOT NULL REFERENCES users(id),
+    name TEXT NOT NULL,
+    started_at TIMESTAMPTZ NOT NULL,
+    ended_at TIMESTAMPTZ,
+    -- bumped periodically so activities left open by a restart can be closed at a sensible time.
+    last_seen_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX presence_activities_guild_started_at_idx ON presence_activities (guild_id, started_at DESC);
+CREATE INDEX presence_activities_guild_user_idx ON presence_activities (guild_id, user_id, started_at DESC);
+
+-- status transitions, flapping between statuses is collapsed into the latest one.
+CREATE TABLE presence_statuses (
+    id BIGSERIAL PRIMARY KEY,
+    guild_id INT NOT NULL REFERENCES guilds(id) ON DELETE CASCADE,
+    user_id INT NOT NULL REFERENCES users(id),
+    status TEXT NOT NULL,
+    changed_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX presence_statuses_guild_user_idx ON presence_statuses (guild_id, user_id, changed_at DESC);
+CREATE INDEX presence_statuses_changed_at_idx ON presence_statuses (changed_at);
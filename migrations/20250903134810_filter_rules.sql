@@ -0,0 +1,34 @@
+CREATE TYPE FilterAction AS ENUM ('report', 'delete', 'warn', 'timeout');
+
+-- what happens to filtered messages, guilds without a row only have them printed.
+-- a null action means that trigger is ignored.
+CREATE TABLE filter_rules (
+    guild_id INT PRIMARY KEY REFERENCES guilds(id) ON DELETE CASCADE,
+    moderate_action FilterAction,
+    severe_action FilterAction,
+    badlist_action FilterAction,
+    timeout_mins INT NOT NULL,
+    report_channel BIGINT,
+    exempt_roles BIGINT[] NOT NULL DEFAULT '{}',
+    exempt_channels BIGINT[] NOT NULL DEFAULT '{}',
+    -- how long a strike counts towards the escalation ladder.
+    strike_window_hours INT NOT NULL
+);
+
+-- harsher actions once a member has this many strikes within the window.
+CREATE TABLE filter_escalation (
+    guild_id INT NOT NULL REFERENCES filter_rules(guild_id) ON DELETE CASCADE,
+    strikes SMALLINT NOT NULL,
+    action FilterAction NOT NULL,
+    timeout_mins INT NOT NULL,
+    PRIMARY KEY (guild_id, strikes)
+);
+
+CREATE TABLE filter_strikes (
+    id BIGSERIAL PRIMARY KEY,
+    guild_id INT NOT NULL REFERENCES guilds(id) ON DELETE CASCADE,
+    user_id INT NOT NULL REFERENCES users(id),
+    struck_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX filter_strikes_guild_user_idx ON filter_strikes (guild_id, user_id, struck_at DESC);
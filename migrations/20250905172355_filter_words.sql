@@ -0,0 +1,106 @@
+CREATE TYPE FilterWordKind AS ENUM ('bad', 'fix', 'safe', 'profane', 'offensive', 'sexual', 'mean');
+CREATE TYPE FilterWordSeverity AS ENUM ('mild', 'moderate', 'severe');
+
+-- words the filter checks for, rows without a guild apply to every guild.
+-- bad and fix are substring lists, the rest override how rustrict sees a whole word.
+CREATE TABLE filter_words (
+    id SERIAL PRIMARY KEY,
+    guild_id INT REFERENCES guilds(id) ON DELETE CASCADE,
+    word TEXT NOT NULL,
+    kind FilterWordKind NOT NULL,
+    severity FilterWordSeverity,
+    CHECK ((severity IS NULL) = (kind IN ('bad', 'fix', 'safe')))
+);
+
+-- a word has a single kind in each scope, a guild can still give a global word a different one.
+CREATE UNIQUE INDEX filter_words_guild_word_idx ON filter_words ((COALESCE(guild_id, 0)), word);
+
+-- the lists that used to live in config/lists and the overrides that were hardcoded.
+INSERT INTO filter_words (word, kind) VALUES
+    ('anal', 'bad'),
+    ('anus', 'bad'),
+    ('arse', 'bad'),
+    ('beastial', 'bad'),
+    ('blowjob', 'bad'),
+    ('boner', 'bad'),
+    ('boob', 'bad'),
+    ('condum', 'bad'),
+    ('cum', 'bad'),
+    ('dildo', 'bad'),
+    ('dong', 'bad'),
+    ('ejaculat', 'bad'),
+    ('fag', 'bad'),
+    ('fap', 'bad'),
+    ('furr', 'bad'),
+    ('groom', 'bad'),
+    ('hentai', 'bad'),
+    ('horn', 'bad'),
+    ('hump', 'bad'),
+    ('jerk', 'bad'),
+    ('jizz', 'bad'),
+    ('kys', 'bad'),
+    ('loli', 'bad'),
+    ('masterbat', 'bad'),
+    ('n||', 'bad'),
+    ('ni||', 'bad'),
+    ('nibba', 'bad'),
+    ('nig-', 'bad'),
+    ('nig||', 'bad'),
+    ('nigg', 'bad'),
+    ('nut', 'bad'),
+    ('orgasm', 'bad'),
+    ('pedo', 'bad'),
+    ('penis', 'bad'),
+    ('piss', 'bad'),
+    ('porn', 'bad'),
+    ('puss', 'bad'),
+    ('racis', 'bad'),
+    ('rape', 'bad'),
+    ('rapi', 'bad'),
+    ('retard', 'bad'),
+    ('schlong', 'bad'),
+    ('semen', 'bad'),
+    ('slut', 'bad'),
+    ('smegma', 'bad'),
+    ('tit', 'bad'),
+    ('vagina', 'bad'),
+    ('viagra', 'bad'),
+    ('wank', 'bad'),
+    ('whore', 'bad'),
+    ('@everyone', 'bad'),
+    ('@here', 'bad'),
+    ('?partner=', 'bad'),
+    ('/airdrop', 'bad'),
+    ('||ger', 'bad'),
+    ('||gger', 'bad'),
+    ('||||||||||||', 'bad'),
+    ('analyze', 'fix'),
+    ('analyzer', 'fix'),
+    ('ballpoint', 'fix'),
+    ('minute', 'fix'),
+    ('minutes', 'fix'),
+    ('pass', 'fix'),
+    ('hololive', 'fix'),
+    ('title', 'fix'),
+    ('therapist', 'fix'),
+    ('identity', 'fix'),
+    ('peanut', 'fix'),
+    ('peanuts', 'fix'),
+    ('analog', 'fix'),
+    ('quantity', 'fix'),
+    ('rapid', 'fix'),
+    ('document', 'fix'),
+    ('nutrition', 'fix'),
+    ('canal', 'fix'),
+    ('analyst', 'fix'),
+    ('analyse', 'fix'),
+    ('analogs', 'fix'),
+    ('parse', 'fix'),
+    ('parser', 'fix'),
+    ('scum', 'fix'),
+    ('scummy', 'fix'),
+    ('competitive', 'fix'),
+    ('minute(s)', 'fix'),
+    ('fcing', 'safe'),
+    ('pp', 'safe'),
+    ('ppcat', 'safe');
@@ -0,0 +1,2 @@
+-- these alerts are about discord automod flagging a status, not the status tracking itself.
+ALTER TABLE guild_settings RENAME COLUMN vc_status_alerts TO automod_alerts;
@@ -0,0 +1,16 @@
+-- invite and filter rules climb the same ladder, so they share one type.
+CREATE TYPE RuleAction AS ENUM ('report', 'delete', 'warn', 'timeout');
+
+ALTER TABLE invite_rules
+    ALTER COLUMN action TYPE RuleAction USING action::text::RuleAction;
+
+ALTER TABLE filter_rules
+    ALTER COLUMN moderate_action TYPE RuleAction USING moderate_action::text::RuleAction,
+    ALTER COLUMN severe_action TYPE RuleAction USING severe_action::text::RuleAction,
+    ALTER COLUMN badlist_action TYPE RuleAction USING badlist_action::text::RuleAction;
+
+ALTER TABLE filter_escalation
+    ALTER COLUMN action TYPE RuleAction USING action::text::RuleAction;
+
+DROP TYPE InviteAction;
+DROP TYPE FilterAction;
@@ -0,0 +1,8 @@
+-- archiving can start at an edit, so the first stored revision isn't always what was sent.
+ALTER TABLE message_revisions ADD COLUMN is_original BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- revisions stored on create were stamped with the message's snowflake time.
+UPDATE message_revisions r SET is_original = TRUE
+FROM messages m
+WHERE r.message_id = m.id
+    AND r.revised_at = to_timestamp(((m.message_id >> 22) + 1420070400000) / 1000.0);
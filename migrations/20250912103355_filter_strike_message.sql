@@ -0,0 +1,4 @@
+-- the text and images of a message are filtered separately, one message only ever earns one strike.
+ALTER TABLE filter_strikes ADD COLUMN message_id BIGINT;
+
+CREATE UNIQUE INDEX filter_strikes_message_idx ON filter_strikes (message_id);
@@ -0,0 +1,7 @@
+-- only statuses flagged by this rule are alerted on, any rule when unset.
+ALTER TABLE guild_settings ADD COLUMN automod_rule TEXT;
+
+-- gg/osu only alerted on its blocked words rule before it was configurable.
+UPDATE guild_settings s SET automod_rule = 'Bad Words ❌ [BLOCKED]'
+FROM guilds g
+WHERE s.guild_id = g.id AND g.guild_id = 98226572468690944;
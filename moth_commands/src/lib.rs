@@ -14,7 +14,10 @@
     clippy::unused_async, // fix.
 )]
 
-use moth_core::data::structs::{ApplicationContext, Command, Context, Data, Error, PrefixContext};
+use moth_core::data::{
+    database::CommandType,
+    structs::{ApplicationContext, Command, Context, Data, Error, PrefixContext},
+};
 
 pub mod accela;
 pub mod lob;
@@ -66,6 +69,39 @@ pub async fn command_check(ctx: Context<'_>) -> Result<bool, Error> {
     Ok(true)
 }
 
+pub async fn post_command(ctx: Context<'_>) {
+    log_invocation(ctx, None).await;
+}
+
+/// Records a command invocation, `error` being `None` means the command ran successfully.
+pub async fn log_invocation(ctx: Context<'_>, error: Option<&str>) {
+    let command_type = match ctx {
+        lumi::Context::Application(_) => CommandType::Application,
+        lumi::Context::Prefix(_) => CommandType::Prefix,
+    };
+
+    let result = ctx
+        .data()
+        .database
+        .insert_executed_command(
+            ctx.author().id,
+            ctx.channel_id(),
+            ctx.guild_id(),
+            &ctx.command().qualified_name,
+            command_type,
+            *ctx.created_at(),
+            error,
+        )
+        .await;
+
+    if let Err(e) = result {
+        tracing::warn!(
+            "Failed to record invocation of `{}`: {e}",
+            ctx.command().qualified_name
+        );
+    }
+}
+
 async fn notify_user_ban(ctx: Context<'_>) -> Result<(), Error> {
     use lumi::serenity_prelude as serenity;
 
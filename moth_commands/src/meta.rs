@@ -308,9 +308,20 @@ pub async fn find_overwrite(
     Ok(())
 }
 
-#[lumi::command(prefix_command, hide_in_help, owners_only)]
-async fn testing(ctx: Context<'_>, user_id: UserId) -> Result<(), Error> {
-    moth_events::handlers::guilds::join_time(ctx.serenity_context(), &ctx.data(), user_id).await;
+#[lumi::command(prefix_command, hide_in_help, owners_only, guild_only)]
+async fn testing(ctx: Context<'_>, member: serenity::Member) -> Result<(), Error> {
+    let signals = moth_events::handlers::guilds::raid::dry_run(&ctx.data(), &member).await?;
+
+    if signals.is_empty() {
+        ctx.say("Joining now wouldn't trip any raid signals.")
+            .await?;
+    } else {
+        ctx.say(format!(
+            "Joining now would trip:\n- {}",
+            signals.join("\n- ")
+        ))
+        .await?;
+    }
 
     Ok(())
 }
@@ -0,0 +1,235 @@
+use crate::{Context, Error, utils::paginate_embeds};
+use ::serenity::all::audit_log::Action;
+use chrono::{DateTime, NaiveDate, NaiveTime, Utc};
+use lumi::serenity_prelude::{self as serenity, AuditLogEntryId, User};
+use moth_core::data::database::audit_log::AuditLogSearch;
+use std::fmt::Write;
+
+/// Searches show at most this many entries.
+const MAX_RESULTS: i64 = 250;
+
+#[derive(Debug, Clone, Copy, lumi::ChoiceParameter)]
+pub enum AuditCategory {
+    Guild,
+    Channel,
+    Member,
+    Role,
+    Invite,
+    Webhook,
+    Emoji,
+    Message,
+    Integration,
+    #[name = "stage-instance"]
+    StageInstance,
+    Sticker,
+    #[name = "scheduled-event"]
+    ScheduledEvent,
+    Thread,
+    #[name = "automod"]
+    AutoMod,
+    #[name = "vc-status"]
+    VoiceChannelStatus,
+}
+
+impl AuditCategory {
+    /// The inclusive range of action kinds discord uses for this category.
+    fn kinds(self) -> (u8, u8) {
+        match self {
+            AuditCategory::Guild => (1, 1),
+            AuditCategory::Channel => (10, 15),
+            AuditCategory::Member => (20, 28),
+            AuditCategory::Role => (30, 32),
+            AuditCategory::Invite => (40, 42),
+            AuditCategory::Webhook => (50, 52),
+            AuditCategory::Emoji => (60, 62),
+            AuditCategory::Message => (72, 75),
+            AuditCategory::Integration => (80, 82),
+            AuditCategory::StageInstance => (83, 85),
+            AuditCategory::Sticker => (90, 92),
+            AuditCategory::ScheduledEvent => (100, 102),
+            AuditCategory::Thread => (110, 112),
+            AuditCategory::AutoMod => (140, 145),
+            AuditCategory::VoiceChannelStatus => (192, 193),
+        }
+    }
+}
+
+/// Search or backfill the stored audit log.
+#[lumi::command(
+    prefix_command,
+    slash_command,
+    category = "Moderation - Audit Log",
+    required_permissions = "VIEW_AUDIT_LOG",
+    guild_only,
+    hide_in_help,
+    subcommands("search", "backfill")
+)]
+pub async fn audit(_: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+/// Search the stored audit log of this guild.
+#[lumi::command(
+    prefix_command,
+    slash_command,
+    required_permissions = "VIEW_AUDIT_LOG",
+    guild_only,
+    hide_in_help
+)]
+pub async fn search(
+    ctx: Context<'_>,
+    #[description = "The user who performed the action."] actor: Option<User>,
+    #[description = "The id of the target of the action."] target: Option<String>,
+    #[description = "The kind of action."] action: Option<AuditCategory>,
+    #[description = "Only show entries on or after this date (YYYY-MM-DD)."] after: Option<String>,
+    #[description = "Only show entries on or before this date (YYYY-MM-DD)."] before: Option<
+        String,
+    >,
+) -> Result<(), Error> {
+    let target = match target {
+        Some(target) => {
+            let digits = target
+                .chars()
+                .filter(char::is_ascii_digit)
+                .collect::<String>();
+            match digits.parse::<u64>() {
+                Ok(id) => Some(id),
+                Err(_) => {
+                    ctx.say("Could not parse the target id.").await?;
+                    return Ok(());
+                }
+            }
+        }
+        None => None,
+    };
+
+    let search = AuditLogSearch {
+        actor: actor.map(|u| u.id),
+        target,
+        action_kinds: action.map(AuditCategory::kinds),
+        after: after.as_deref().map(|d| parse_date(d, false)).transpose()?,
+        before: before.as_deref().map(|d| parse_date(d, true)).transpose()?,
+    };
+
+    let mut records = ctx
+        .data()
+        .database
+        .search_audit_log(ctx.guild_id().unwrap(), &search, MAX_RESULTS + 1)
+        .await?;
+
+    if records.is_empty() {
+        ctx.say("No audit log entries matched.").await?;
+        return Ok(());
+    }
+
+    let title = if records.len() > MAX_RESULTS as usize {
+        records.truncate(MAX_RESULTS as usize);
+        format!("Audit log (newest {MAX_RESULTS} matches, narrow the search for older entries)")
+    } else {
+        format!("Audit log ({} matches)", records.len())
+    };
+
+    let mut pages = Vec::new();
+    for chunk in records.chunks(10) {
+        let mut page = String::new();
+        for record in chunk {
+            let action = Action::from_value(record.action_kind);
+            write!(
+                page,
+                "<t:{}:f> **{action:?}**",
+                record.created_at.timestamp()
+            )
+            .unwrap();
+
+            if let Some(user_id) = record.user_id {
+                write!(page, " by <@{user_id}>").unwrap();
+            }
+
+            if let Some(target_id) = record.target_id {
+                write!(page, " on `{target_id}`").unwrap();
+            }
+
+            if let Some(reason) = &record.reason {
+                write!(page, "\n> {reason}").unwrap();
+            }
+
+            page.push('\n');
+        }
+        pages.push(
+            serenity::CreateEmbed::new()
+                .title(title.as_str())
+                .description(page),
+        );
+    }
+
+    paginate_embeds(ctx, pages).await?;
+
+    Ok(())
+}
+
+/// Page through the audit log of this guild and store any missing entries, discord only keeps
+/// entries for 45 days so gaps older than that cannot be filled.
+#[lumi::command(
+    prefix_command,
+    slash_command,
+    required_permissions = "VIEW_AUDIT_LOG",
+    required_bot_permissions = "VIEW_AUDIT_LOG",
+    guild_only,
+    hide_in_help
+)]
+pub async fn backfill(ctx: Context<'_>) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().unwrap();
+    let database = &ctx.data().database;
+
+    ctx.defer().await?;
+
+    // entries stored since a restart sit in front of the gap left while offline, so the whole log
+    // is walked and entries that are already stored are skipped by the insert.
+    let mut before: Option<AuditLogEntryId> = None;
+    let mut inserted = 0_usize;
+    let mut seen = 0_usize;
+
+    loop {
+        let logs = guild_id
+            .audit_logs(ctx.http(), None, None, before, None)
+            .await?;
+
+        let Some(last) = logs.entries.last() else {
+            break;
+        };
+        before = Some(last.id);
+
+        for entry in &logs.entries {
+            seen += 1;
+            if database.insert_audit_log_entry(guild_id, entry).await? {
+                inserted += 1;
+            }
+        }
+    }
+
+    ctx.say(format!(
+        "Backfilled {inserted} audit log entries after checking {seen}."
+    ))
+    .await?;
+
+    Ok(())
+}
+
+fn parse_date(input: &str, end_of_day: bool) -> Result<DateTime<Utc>, Error> {
+    let Ok(date) = NaiveDate::parse_from_str(input, "%Y-%m-%d") else {
+        return Err(format!("Could not parse `{input}` as a date, use YYYY-MM-DD.").into());
+    };
+
+    let time = if end_of_day {
+        NaiveTime::from_hms_opt(23, 59, 59).unwrap()
+    } else {
+        NaiveTime::MIN
+    };
+
+    Ok(date.and_time(time).and_utc())
+}
+
+#[must_use]
+pub fn commands() -> [crate::Command; 1] {
+    [audit()]
+}
@@ -0,0 +1,110 @@
+use crate::{utils::paginate_embeds, Context, Error};
+use lumi::serenity_prelude::{self as serenity, MessageId};
+use std::fmt::Write;
+
+/// Leaves room for the attachment list under the content.
+const MAX_CONTENT_LENGTH: usize = 3500;
+
+/// Show every archived revision of a message, including deleted messages.
+#[lumi::command(
+    prefix_command,
+    slash_command,
+    category = "Moderation - Archive",
+    required_permissions = "MANAGE_MESSAGES",
+    guild_only
+)]
+pub async fn history(
+    ctx: Context<'_>,
+    #[description = "A link to the message or its id."] message: String,
+) -> Result<(), Error> {
+    let Some(message_id) = parse_message_id(&message) else {
+        ctx.say("Could not parse a message link or id.").await?;
+        return Ok(());
+    };
+
+    let history = ctx.data().database.get_message_history(message_id).await?;
+
+    // messages from other guilds are treated as missing so they can't be looked up from here.
+    let Some(history) = history.filter(|h| h.guild_id == ctx.guild_id()) else {
+        ctx.say("There is no archived history for that message.")
+            .await?;
+        return Ok(());
+    };
+
+    let total = history.revisions.len();
+    // archiving can start at an edit, then the first revision is an edit too.
+    let first_edit = usize::from(!history.revisions[0].is_original);
+    let mut pages = Vec::with_capacity(total);
+    for (i, revision) in history.revisions.iter().enumerate() {
+        let title = if revision.is_original {
+            "Original".to_owned()
+        } else {
+            format!("Edit {}", i + first_edit)
+        };
+
+        let mut description = format!(
+            "**Author:** <@{}>\n**Channel:** <#{}>\n**At:** <t:{}:f>\n",
+            history.user_id,
+            history.channel_id,
+            revision.revised_at.timestamp()
+        );
+
+        if i + 1 == total
+            && let Some(deleted_at) = history.deleted_at
+        {
+            writeln!(description, "**Deleted:** <t:{}:f>", deleted_at.timestamp()).unwrap();
+        }
+
+        description.push('\n');
+        if revision.content.is_empty() {
+            description.push_str("*No content*");
+        } else if revision.content.chars().count() > MAX_CONTENT_LENGTH {
+            let content = revision
+                .content
+                .chars()
+                .take(MAX_CONTENT_LENGTH)
+                .collect::<String>();
+            write!(description, "{content}...").unwrap();
+        } else {
+            description.push_str(&revision.content);
+        }
+
+        if !revision.attachments.is_empty() {
+            description.push_str("\n\n**Attachments**\n");
+            for attachment in &revision.attachments {
+                writeln!(
+                    description,
+                    "[{}]({}) ({} KB)",
+                    attachment.filename,
+                    attachment.url,
+                    attachment.size / 1024
+                )
+                .unwrap();
+            }
+        }
+
+        pages.push(
+            serenity::CreateEmbed::new()
+                .title(title)
+                .description(description),
+        );
+    }
+
+    paginate_embeds(ctx, pages).await?;
+
+    Ok(())
+}
+
+/// Parses the id out of a message link, or a plain message id.
+fn parse_message_id(input: &str) -> Option<MessageId> {
+    let id = input.trim().trim_end_matches('/').rsplit('/').next()?;
+    id.parse::<u64>()
+        .ok()
+        .filter(|&id| id != 0)
+        .map(MessageId::new)
+}
+
+#[must_use]
+pub fn commands() -> [crate::Command; 1] {
+    [history()]
+}
@@ -1,10 +1,17 @@
+pub mod audit;
+pub mod history;
+pub mod modmail;
+pub mod names;
+pub mod raid;
+pub mod vc_status;
+
 use std::{collections::HashSet, time::Duration};
 
 use crate::{Error, PrefixContext};
 
 use lumi::serenity_prelude as serenity;
 use moth_core::emojis::{Question, X};
-use moth_events::handlers::messages::invites::INVITE;
+use moth_events::handlers::messages::invites::invite_codes;
 use serenity::all::MessageId;
 use small_fixed_array::FixedString;
 
@@ -161,7 +168,7 @@ async fn purge_prep(
             }
             Modifier::Invites => {
                 for msg in &messages {
-                    let matches = INVITE.is_match(&msg.content);
+                    let matches = !invite_codes(&msg.content).is_empty();
                     if matches != group.negated {
                         deleted.insert(msg.id);
                     }
@@ -432,6 +439,14 @@ fn has_permissions(ctx: &PrefixContext) -> (bool, bool) {
 }
 
 #[must_use]
-pub fn commands() -> [crate::Command; 2] {
+pub fn commands() -> Vec<crate::Command> {
     [purge(), purge_in()]
+        .into_iter()
+        .chain(audit::commands())
+        .chain(history::commands())
+        .chain(modmail::commands())
+        .chain(names::commands())
+        .chain(raid::commands())
+        .chain(vc_status::commands())
+        .collect()
 }
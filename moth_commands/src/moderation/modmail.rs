@@ -0,0 +1,91 @@
+use crate::{Context, Error};
+use lumi::serenity_prelude::{CreateAttachment, User};
+use moth_events::handlers::messages::modmail::close_thread;
+
+/// Manage modmail threads.
+#[lumi::command(
+    prefix_command,
+    slash_command,
+    category = "Moderation - Modmail",
+    required_permissions = "MANAGE_MESSAGES",
+    guild_only,
+    subcommands("close", "transcripts")
+)]
+pub async fn modmail(_: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+/// Close the modmail thread this is used in.
+#[lumi::command(
+    prefix_command,
+    slash_command,
+    required_permissions = "MANAGE_MESSAGES",
+    guild_only
+)]
+pub async fn close(ctx: Context<'_>) -> Result<(), Error> {
+    ctx.defer().await?;
+
+    let closed = close_thread(
+        ctx.serenity_context(),
+        &ctx.data(),
+        ctx.channel_id(),
+        ctx.author().id,
+    )
+    .await?;
+
+    if closed.is_none() {
+        ctx.say("This isn't an open modmail thread.").await?;
+    }
+
+    Ok(())
+}
+
+/// Get the transcripts of a user's closed modmail threads.
+#[lumi::command(
+    prefix_command,
+    slash_command,
+    required_permissions = "MANAGE_MESSAGES",
+    guild_only
+)]
+pub async fn transcripts(
+    ctx: Context<'_>,
+    #[description = "The user the threads were with."] user: User,
+) -> Result<(), Error> {
+    let threads = ctx
+        .data()
+        .database
+        .get_modmail_transcripts(ctx.guild_id().unwrap(), user.id)
+        .await?;
+
+    if threads.is_empty() {
+        ctx.say(format!("{} has no closed modmail threads.", user.tag()))
+            .await?;
+        return Ok(());
+    }
+
+    // discord only allows 10 attachments on a message.
+    let mut reply = lumi::CreateReply::new().content(format!(
+        "{} has {} closed modmail threads, showing the latest {}.",
+        user.tag(),
+        threads.len(),
+        threads.len().min(10)
+    ));
+    for thread in threads.into_iter().take(10) {
+        reply = reply.attachment(CreateAttachment::bytes(
+            thread
+                .transcript
+                .unwrap_or_else(|| "The thread was deleted before it was closed.".to_owned())
+                .into_bytes(),
+            format!("modmail-{}-{}.txt", user.id, thread.closed_at.timestamp()),
+        ));
+    }
+
+    ctx.send(reply).await?;
+
+    Ok(())
+}
+
+#[must_use]
+pub fn commands() -> [crate::Command; 1] {
+    [modmail()]
+}
@@ -0,0 +1,80 @@
+use crate::{Context, Error};
+use lumi::serenity_prelude::{GuildId, User, UserId};
+use moth_core::data::database::names::{NameChange, NameChangeKind};
+use std::fmt::Write;
+
+/// Show every stored name, nickname and avatar change of a user.
+#[lumi::command(
+    prefix_command,
+    slash_command,
+    category = "Moderation - Names",
+    required_permissions = "MODERATE_MEMBERS",
+    guild_only
+)]
+pub async fn names(
+    ctx: Context<'_>,
+    #[description = "The user to show the history of."] user: User,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().unwrap();
+    let history = ctx
+        .data()
+        .database
+        .get_name_history(user.id, guild_id)
+        .await?;
+
+    if history.is_empty() {
+        ctx.say(format!("There are no stored changes for {}.", user.tag()))
+            .await?;
+        return Ok(());
+    }
+
+    let mut pages = Vec::new();
+    for chunk in history.chunks(10) {
+        let mut page = format!("**Name history of {}**\n", user.tag());
+        for change in chunk {
+            write!(
+                page,
+                "<t:{}:f> **{}**\n> {} → {}\n",
+                change.changed_at.timestamp(),
+                change.kind.name(),
+                value(change, change.old_value.as_deref(), user.id, guild_id),
+                value(change, change.new_value.as_deref(), user.id, guild_id),
+            )
+            .unwrap();
+        }
+        pages.push(page);
+    }
+
+    let pages = pages.iter().map(String::as_str).collect::<Vec<_>>();
+    lumi::builtins::paginate(ctx, &pages).await?;
+
+    Ok(())
+}
+
+/// Names are shown in code blocks, avatars link to the image of their hash.
+fn value(change: &NameChange, value: Option<&str>, user_id: UserId, guild_id: GuildId) -> String {
+    let Some(value) = value.filter(|v| !v.is_empty()) else {
+        return "None".to_owned();
+    };
+
+    let ext = if value.starts_with("a_") {
+        "gif"
+    } else {
+        "png"
+    };
+    match change.kind {
+        NameChangeKind::Avatar => format!(
+            "[{value}](https://cdn.discordapp.com/avatars/{user_id}/{value}.{ext}?size=1024)"
+        ),
+        NameChangeKind::GuildAvatar => format!(
+            "[{value}](https://cdn.discordapp.com/guilds/{guild_id}/users/{user_id}/avatars/\
+             {value}.{ext}?size=1024)"
+        ),
+        _ => format!("`{}`", value.replace('`', "'")),
+    }
+}
+
+#[must_use]
+pub fn commands() -> [crate::Command; 1] {
+    [names()]
+}
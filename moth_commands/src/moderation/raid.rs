@@ -0,0 +1,177 @@
+use crate::{owner::admin, Context, Error};
+use moth_events::handlers::guilds::raid::{describe_lockdown, lift_lockdown};
+use std::{fmt::Write, time::Duration};
+
+#[derive(Debug, Clone, Copy, lumi::ChoiceParameter)]
+pub enum RaidResponse {
+    #[name = "raise-verification"]
+    RaiseVerification,
+    #[name = "pause-invites"]
+    PauseInvites,
+    #[name = "timeout"]
+    TimeoutCluster,
+}
+
+/// Manage raid detection in this guild.
+#[lumi::command(
+    prefix_command,
+    slash_command,
+    category = "Moderation - Raids",
+    required_permissions = "MANAGE_GUILD",
+    guild_only,
+    subcommands("status", "all_clear", "config", "response")
+)]
+pub async fn raid(_: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+/// Show the raid detection config and any active lockdown.
+#[lumi::command(
+    prefix_command,
+    slash_command,
+    required_permissions = "MANAGE_GUILD",
+    guild_only
+)]
+pub async fn status(ctx: Context<'_>) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().unwrap();
+    let database = &ctx.data().database;
+
+    let config = database.get_raid_config(guild_id);
+    let enabled = database.get_guild_settings(guild_id).raid_alerts();
+    let toggle = |b: bool| if b { "✅" } else { "❌" };
+
+    let mut string = format!(
+        "**Raid detection** {}\nAccount age window: {}h\nCluster size: {}\nJoin window: \
+         {}s\nJoin rate: {}\n\n**Automatic responses**\nRaise verification: {}\nPause invites: \
+         {}\nTimeout cluster: {} ({}m)",
+        toggle(enabled),
+        config.age_window.as_secs() / 3600,
+        config.cluster_size,
+        config.join_window.as_secs(),
+        config.join_rate,
+        toggle(config.raise_verification),
+        toggle(config.pause_invites),
+        toggle(config.timeout_cluster),
+        config.timeout_for.as_secs() / 60,
+    );
+
+    if let Some(lockdown) = database.get_raid_lockdown(guild_id).await? {
+        write!(
+            string,
+            "\n\n**Locked down since <t:{}:R>**\n{}",
+            lockdown.started_at.timestamp(),
+            describe_lockdown(&lockdown)
+        )
+        .unwrap();
+    }
+
+    ctx.say(string).await?;
+
+    Ok(())
+}
+
+/// Undo every automatic response to a raid.
+#[lumi::command(
+    prefix_command,
+    slash_command,
+    rename = "all-clear",
+    required_permissions = "MANAGE_GUILD",
+    guild_only
+)]
+pub async fn all_clear(ctx: Context<'_>) -> Result<(), Error> {
+    ctx.defer().await?;
+
+    let lifted =
+        lift_lockdown(ctx.serenity_context(), &ctx.data(), ctx.guild_id().unwrap()).await?;
+
+    match lifted {
+        Some(lockdown) => {
+            ctx.say(format!(
+                "All clear, undid:\n{}",
+                describe_lockdown(&lockdown)
+            ))
+            .await?;
+        }
+        None => {
+            ctx.say("This guild isn't locked down.").await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Change the raid detection thresholds, anything not provided is left as is.
+#[lumi::command(prefix_command, check = "admin", guild_only, hide_in_help)]
+pub async fn config(
+    ctx: Context<'_>,
+    #[description = "Hours between account creations to count as a cluster."]
+    age_window_hours: Option<u16>,
+    #[description = "How many similar joins trip a signal."] cluster_size: Option<u16>,
+    #[description = "Seconds of joins counted for the join rate."] join_window_secs: Option<u16>,
+    #[description = "Joins within the join window that trip the join rate."] join_rate: Option<u16>,
+) -> Result<(), Error> {
+    if [age_window_hours, cluster_size, join_window_secs, join_rate].contains(&Some(0)) {
+        ctx.say("Thresholds must be at least 1.").await?;
+        return Ok(());
+    }
+
+    let guild_id = ctx.guild_id().unwrap();
+    let database = &ctx.data().database;
+
+    let mut config = database.get_raid_config(guild_id);
+    if let Some(hours) = age_window_hours {
+        config.age_window = Duration::from_secs(u64::from(hours) * 60 * 60);
+    }
+    if let Some(size) = cluster_size {
+        config.cluster_size = size;
+    }
+    if let Some(secs) = join_window_secs {
+        config.join_window = Duration::from_secs(secs.into());
+    }
+    if let Some(rate) = join_rate {
+        config.join_rate = rate;
+    }
+
+    database.set_raid_config(guild_id, config).await?;
+
+    ctx.say("Updated the raid detection thresholds.").await?;
+
+    Ok(())
+}
+
+/// Enable or disable an automatic response to raids.
+#[lumi::command(prefix_command, check = "admin", guild_only, hide_in_help)]
+pub async fn response(
+    ctx: Context<'_>,
+    #[description = "The response to toggle."] response: RaidResponse,
+    #[description = "If the response should be enabled."] enabled: bool,
+    #[description = "How long the cluster is timed out for."] timeout_mins: Option<u16>,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().unwrap();
+    let database = &ctx.data().database;
+
+    let mut config = database.get_raid_config(guild_id);
+    match response {
+        RaidResponse::RaiseVerification => config.raise_verification = enabled,
+        RaidResponse::PauseInvites => config.pause_invites = enabled,
+        RaidResponse::TimeoutCluster => {
+            config.timeout_cluster = enabled;
+            if let Some(mins) = timeout_mins.filter(|&m| m != 0) {
+                // discord caps timeouts at 28 days.
+                config.timeout_for = Duration::from_secs(u64::from(mins.min(28 * 24 * 60)) * 60);
+            }
+        }
+    }
+
+    database.set_raid_config(guild_id, config).await?;
+
+    let state = if enabled { "Enabled" } else { "Disabled" };
+    ctx.say(format!("{state} {response:?}.")).await?;
+
+    Ok(())
+}
+
+#[must_use]
+pub fn commands() -> [crate::Command; 1] {
+    [raid()]
+}
@@ -0,0 +1,128 @@
+use crate::{owner::admin, Context, Error};
+use lumi::serenity_prelude::{GenericChannelId, User};
+use moth_core::data::database::vc_status::VcStatusSearch;
+use std::fmt::Write;
+
+/// Search voice channel status history or change how statuses are handled.
+#[lumi::command(
+    prefix_command,
+    slash_command,
+    rename = "vcstatus",
+    category = "Moderation - VC Status",
+    required_permissions = "VIEW_AUDIT_LOG",
+    guild_only,
+    subcommands("history", "clear_blacklisted")
+)]
+pub async fn vc_status(_: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+/// Search the voice channel status changes in this guild.
+#[lumi::command(
+    prefix_command,
+    slash_command,
+    required_permissions = "VIEW_AUDIT_LOG",
+    guild_only
+)]
+pub async fn history(
+    ctx: Context<'_>,
+    #[description = "The user who changed the status."] user: Option<User>,
+    #[description = "The voice channel."] channel: Option<GenericChannelId>,
+    #[description = "Only show blacklisted statuses."] blacklisted: Option<bool>,
+    #[description = "Text in the old or new status."]
+    #[rest]
+    text: Option<String>,
+) -> Result<(), Error> {
+    let search = VcStatusSearch {
+        user: user.map(|u| u.id),
+        channel: channel.map(GenericChannelId::expect_channel),
+        text: text.filter(|t| !t.trim().is_empty()),
+        blacklisted_only: blacklisted.unwrap_or(false),
+    };
+
+    let changes = ctx
+        .data()
+        .database
+        .search_vc_status_history(ctx.guild_id().unwrap(), &search, 250)
+        .await?;
+
+    if changes.is_empty() {
+        ctx.say("No status changes matched.").await?;
+        return Ok(());
+    }
+
+    let status = |s: &Option<String>| match s.as_deref() {
+        Some(s) if !s.is_empty() => format!("`{}`", s.replace('`', "'")),
+        _ => "None".to_owned(),
+    };
+
+    let mut pages = Vec::new();
+    for chunk in changes.chunks(10) {
+        let mut page = String::new();
+        for change in chunk {
+            write!(
+                page,
+                "<t:{}:f> <#{}>",
+                change.changed_at.timestamp(),
+                change.channel_id
+            )
+            .unwrap();
+
+            match change.user_id {
+                Some(user_id) => write!(page, " by <@{user_id}>").unwrap(),
+                None => page.push_str(" by an unknown user"),
+            }
+
+            if change.blacklisted {
+                page.push_str(" **(blacklisted)**");
+            }
+
+            write!(
+                page,
+                "\n> {} → {}\n",
+                status(&change.old_status),
+                status(&change.new_status)
+            )
+            .unwrap();
+        }
+        pages.push(page);
+    }
+
+    let pages = pages.iter().map(String::as_str).collect::<Vec<_>>();
+    lumi::builtins::paginate(ctx, &pages).await?;
+
+    Ok(())
+}
+
+/// Set if statuses matching the blacklist are cleared automatically.
+#[lumi::command(
+    prefix_command,
+    rename = "clear-blacklisted",
+    check = "admin",
+    guild_only,
+    hide_in_help
+)]
+pub async fn clear_blacklisted(
+    ctx: Context<'_>,
+    #[description = "If blacklisted statuses should be cleared."] enabled: bool,
+) -> Result<(), Error> {
+    {
+        let mut config = ctx.data().config.write();
+        config.vcstatus.clear_blacklisted = enabled;
+        config.write_config();
+    }
+
+    let msg = if enabled {
+        "Blacklisted statuses will now be cleared."
+    } else {
+        "Blacklisted statuses will no longer be cleared."
+    };
+    ctx.say(msg).await?;
+
+    Ok(())
+}
+
+#[must_use]
+pub fn commands() -> [crate::Command; 1] {
+    [vc_status()]
+}
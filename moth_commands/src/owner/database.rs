@@ -1,4 +1,5 @@
 use crate::{owner::admin, Context, Error};
+use chrono::{DateTime, Utc};
 use lumi::serenity_prelude::{self as serenity, CreateEmbedFooter};
 use moth_ansi::RESET;
 use sqlx::{query, Pool, Postgres, Row};
@@ -30,6 +31,8 @@ pub async fn dbstats(ctx: Context<'_>) -> Result<(), Error> {
         ("emote_usage", "id"),
         ("blocked_checked_emotes", "guild_id"),
         ("blocked_checked_stickers", "guild_id"),
+        ("emote_usage_daily", "day"),
+        ("sticker_usage_daily", "day"),
     ];
 
     let misc_tables = [
@@ -40,6 +43,7 @@ pub async fn dbstats(ctx: Context<'_>) -> Result<(), Error> {
         ("role_snapshots", "id"),
         ("audit_log", "audit_log_id"),
         ("executed_commands", "id"),
+        ("executed_commands_daily", "day"),
     ];
 
     let mut embed = serenity::CreateEmbed::default().title("Database Stats");
@@ -139,7 +143,197 @@ pub async fn sql(
     Ok(())
 }
 
+#[derive(Debug, Clone, Copy, lumi::ChoiceParameter)]
+pub enum TimeWindow {
+    Day,
+    Week,
+    Month,
+    Year,
+    #[name = "all-time"]
+    AllTime,
+}
+
+impl TimeWindow {
+    fn since(self) -> DateTime<Utc> {
+        let duration = match self {
+            TimeWindow::Day => chrono::Duration::days(1),
+            TimeWindow::Week => chrono::Duration::weeks(1),
+            TimeWindow::Month => chrono::Duration::days(30),
+            TimeWindow::Year => chrono::Duration::days(365),
+            TimeWindow::AllTime => return DateTime::<Utc>::MIN_UTC,
+        };
+
+        Utc::now() - duration
+    }
+}
+
+/// Shows command usage, failure rates and common errors over a time window.
+#[lumi::command(
+    rename = "command-stats",
+    aliases("cmd-stats", "cmdstats"),
+    prefix_command,
+    category = "Admin - Database",
+    check = "admin",
+    hide_in_help
+)]
+pub async fn command_stats(
+    ctx: Context<'_>,
+    #[description = "The time window to show stats for."] window: Option<TimeWindow>,
+    #[description = "Only show stats for this command."] command: Option<String>,
+) -> Result<(), Error> {
+    let window = window.unwrap_or(TimeWindow::Week);
+    let since = window.since();
+    let database = &ctx.data().database;
+
+    let (usage, users, errors) = tokio::join!(
+        database.get_command_usage(since, command.as_deref(), 10),
+        database.get_command_user_usage(since, command.as_deref(), 10),
+        database.get_command_errors(since, command.as_deref(), 5),
+    );
+    let (usage, users, errors) = (usage?, users?, errors?);
+
+    let mut commands = String::new();
+    for u in &usage {
+        let rate = u.failures as f64 / u.uses as f64 * 100.0;
+        writeln!(
+            commands,
+            "**{}**: {} uses, {} failed ({rate:.1}%)",
+            u.command, u.uses, u.failures
+        )
+        .unwrap();
+    }
+
+    let mut top_users = String::new();
+    for u in &users {
+        writeln!(top_users, "<@{}>: {}", u.user_id, u.uses).unwrap();
+    }
+
+    let mut top_errors = String::new();
+    for e in &errors {
+        let text = if e.error_text.chars().count() > 100 {
+            format!("{}...", e.error_text.chars().take(100).collect::<String>())
+        } else {
+            e.error_text.clone()
+        };
+        writeln!(top_errors, "`{text}`: {}", e.count).unwrap();
+    }
+
+    let or_none = |s: String| if s.is_empty() { "None".to_string() } else { s };
+
+    let title = match &command {
+        Some(command) => format!("Command Stats for {command} ({window:?})"),
+        None => format!("Command Stats ({window:?})"),
+    };
+
+    let embed = serenity::CreateEmbed::default()
+        .title(title)
+        .field("Commands", or_none(commands), false)
+        .field("Users", or_none(top_users), true)
+        .field("Errors", or_none(top_errors), true);
+
+    ctx.send(
+        lumi::CreateReply::default()
+            .embed(embed)
+            .allowed_mentions(serenity::CreateAllowedMentions::new()),
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// View or trigger pruning of old usage data.
+#[lumi::command(
+    prefix_command,
+    category = "Admin - Database",
+    check = "admin",
+    hide_in_help,
+    subcommands("report", "run")
+)]
+pub async fn retention(_: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+/// Shows the configured retention policies and the most recent runs.
+#[lumi::command(prefix_command, check = "admin", hide_in_help)]
+pub async fn report(ctx: Context<'_>) -> Result<(), Error> {
+    let data = ctx.data();
+    let config = data.retention.config();
+
+    let mut policies = String::new();
+    for policy in &config.policies {
+        writeln!(
+            policies,
+            "**{}**: {} days{}",
+            policy.table.name(),
+            policy.keep_for.num_days(),
+            if policy.rollup { ", rolled up" } else { "" }
+        )
+        .unwrap();
+    }
+
+    let reports = data.database.get_retention_reports(10).await?;
+
+    let mut runs = String::new();
+    for report in &reports {
+        let duration = report.finished_at - report.started_at;
+        writeln!(
+            runs,
+            "<t:{}:R> **{}**: {} rows in {}s",
+            report.finished_at.timestamp(),
+            report.table_name,
+            report.rows_pruned,
+            duration.num_seconds()
+        )
+        .unwrap();
+    }
+
+    let or_none = |s: String| if s.is_empty() { "None".to_string() } else { s };
+
+    let embed = serenity::CreateEmbed::default()
+        .title("Data Retention")
+        .field("Policies", or_none(policies), false)
+        .field("Recent runs", or_none(runs), false)
+        .footer(CreateEmbedFooter::new(format!(
+            "Runs every {} hours",
+            config.interval.as_secs() / 3600
+        )));
+
+    ctx.send(lumi::CreateReply::default().embed(embed)).await?;
+
+    Ok(())
+}
+
+/// Applies every retention policy now.
+#[lumi::command(prefix_command, check = "admin", hide_in_help)]
+pub async fn run(ctx: Context<'_>) -> Result<(), Error> {
+    let data = ctx.data();
+
+    ctx.say("Pruning, this may take a while...").await?;
+
+    let reports = match data.retention.run_now(&data.database).await {
+        Ok(reports) => reports,
+        Err(e) => {
+            ctx.say(e.to_string()).await?;
+            return Ok(());
+        }
+    };
+
+    let mut content = String::from("Pruned:\n");
+    for report in &reports {
+        writeln!(
+            content,
+            "**{}**: {} rows",
+            report.table_name, report.rows_pruned
+        )
+        .unwrap();
+    }
+
+    ctx.say(content).await?;
+
+    Ok(())
+}
+
 #[must_use]
-pub fn commands() -> [crate::Command; 2] {
-    [dbstats(), sql()]
+pub fn commands() -> [crate::Command; 4] {
+    [dbstats(), sql(), command_stats(), retention()]
 }
@@ -0,0 +1,177 @@
+use crate::{
+    owner::admin,
+    utility::expressions::{string_to_expression, Expression},
+    Context, Error,
+};
+use lumi::serenity_prelude::{CreateAllowedMentions, EmojiId, ReactionType, StickerId};
+use small_fixed_array::FixedString;
+use std::fmt::Write;
+
+/// Exclude emotes and stickers from the usage statistics of this guild.
+#[lumi::command(
+    prefix_command,
+    rename = "expression-block",
+    category = "Admin - Expressions",
+    check = "admin",
+    guild_only,
+    hide_in_help,
+    subcommands("emote", "sticker", "list")
+)]
+pub async fn expression_block(_: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+/// Toggle an emote being excluded from usage statistics.
+#[lumi::command(prefix_command, check = "admin", guild_only, hide_in_help)]
+pub async fn emote(
+    ctx: Context<'_>,
+    #[description = "The emote to block or unblock."] emote: String,
+) -> Result<(), Error> {
+    let Some(reaction) = resolve_emote(ctx, &emote) else {
+        ctx.say("Could not find that emote.").await?;
+        return Ok(());
+    };
+
+    let blocked = ctx
+        .data()
+        .database
+        .toggle_blocked_emote(ctx.guild_id().unwrap(), &reaction)
+        .await?;
+
+    let content = if blocked {
+        format!("{reaction} is now excluded from usage statistics.")
+    } else {
+        format!("{reaction} is no longer excluded from usage statistics.")
+    };
+
+    ctx.say(content).await?;
+
+    Ok(())
+}
+
+/// Toggle a sticker of this guild being excluded from usage statistics.
+#[lumi::command(prefix_command, check = "admin", guild_only, hide_in_help)]
+pub async fn sticker(
+    ctx: Context<'_>,
+    #[description = "The id or name of the sticker."]
+    #[rest]
+    sticker: String,
+) -> Result<(), Error> {
+    let found = {
+        let Some(guild) = ctx.guild() else {
+            return Ok(());
+        };
+
+        match sticker.parse::<u64>() {
+            Ok(id) => guild.stickers.get(&StickerId::new(id)),
+            Err(_) => guild
+                .stickers
+                .iter()
+                .find(|s| s.name.eq_ignore_ascii_case(&sticker)),
+        }
+        .map(|s| (s.id, s.name.to_string()))
+    };
+
+    let Some((sticker_id, name)) = found else {
+        ctx.say("Could not find that sticker in this guild.")
+            .await?;
+        return Ok(());
+    };
+
+    let blocked = ctx
+        .data()
+        .database
+        .toggle_blocked_sticker(ctx.guild_id().unwrap(), sticker_id, &name)
+        .await?;
+
+    let content = if blocked {
+        format!("`{name}` is now excluded from usage statistics.")
+    } else {
+        format!("`{name}` is no longer excluded from usage statistics.")
+    };
+
+    ctx.say(content).await?;
+
+    Ok(())
+}
+
+/// List the emotes and stickers excluded from usage statistics.
+#[lumi::command(prefix_command, check = "admin", guild_only, hide_in_help)]
+pub async fn list(ctx: Context<'_>) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().unwrap();
+    let database = &ctx.data().database;
+
+    let emotes = database.get_blocked_emotes(guild_id).await?;
+    let stickers = database.get_blocked_stickers(guild_id).await?;
+
+    if emotes.is_empty() && stickers.is_empty() {
+        ctx.say("Nothing is excluded from usage statistics.")
+            .await?;
+        return Ok(());
+    }
+
+    let mut content = String::from("**Emotes**\n");
+    for emote in &emotes {
+        match emote.discord_id {
+            Some(id) => writeln!(content, "<:{}:{id}> (`{id}`)", emote.name).unwrap(),
+            None => writeln!(content, "{}", emote.name).unwrap(),
+        }
+    }
+
+    content.push_str("\n**Stickers**\n");
+    {
+        let guild = ctx.guild();
+        for sticker_id in &stickers {
+            let name = guild
+                .as_ref()
+                .and_then(|g| g.stickers.get(sticker_id).map(|s| s.name.to_string()));
+
+            match name {
+                Some(name) => writeln!(content, "{name} (`{sticker_id}`)").unwrap(),
+                None => writeln!(content, "`{sticker_id}`").unwrap(),
+            }
+        }
+    }
+
+    ctx.send(
+        lumi::CreateReply::new()
+            .content(content)
+            .allowed_mentions(CreateAllowedMentions::new()),
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Resolves the input into an emote, ids and names are looked up in this guild's emotes.
+fn resolve_emote(ctx: Context<'_>, input: &str) -> Option<ReactionType> {
+    let custom = |id: EmojiId, name: &str, animated: bool| ReactionType::Custom {
+        animated,
+        id,
+        name: Some(FixedString::from_str_trunc(name)),
+    };
+
+    match string_to_expression(input)? {
+        Expression::Emote((id, name)) => {
+            Some(custom(EmojiId::new(id), &name, input.starts_with("<a:")))
+        }
+        Expression::Standard(emoji) => {
+            Some(ReactionType::Unicode(FixedString::from_str_trunc(emoji)))
+        }
+        Expression::Id(id) => {
+            let guild = ctx.guild()?;
+            let emoji = guild.emojis.get(&EmojiId::new(id))?;
+            Some(custom(emoji.id, &emoji.name, emoji.animated()))
+        }
+        Expression::Name(name) => {
+            let guild = ctx.guild()?;
+            let emoji = guild.emojis.iter().find(|e| e.name == name)?;
+            Some(custom(emoji.id, &emoji.name, emoji.animated()))
+        }
+    }
+}
+
+#[must_use]
+pub fn commands() -> [crate::Command; 1] {
+    [expression_block()]
+}
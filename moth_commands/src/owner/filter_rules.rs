@@ -0,0 +1,315 @@
+use crate::{
+    owner::admin,
+    utils::{toggle, RuleActionChoice},
+    Context, Error,
+};
+use lumi::serenity_prelude::{CreateAllowedMentions, GenericChannelId, Role, User};
+use moth_core::data::database::{
+    filter_rules::{EscalationStep, FilterRules},
+    rule_action::RuleAction,
+};
+use std::{fmt::Write, time::Duration};
+
+#[derive(Debug, Clone, Copy, lumi::ChoiceParameter)]
+pub enum FilterTrigger {
+    Moderate,
+    Severe,
+    Badlist,
+}
+
+/// Manage what happens to messages caught by the filter in this guild.
+#[lumi::command(
+    prefix_command,
+    rename = "filter-rules",
+    category = "Admin - Filter",
+    check = "admin",
+    guild_only,
+    hide_in_help,
+    subcommands(
+        "view",
+        "action",
+        "timeout",
+        "escalate",
+        "window",
+        "report_channel",
+        "exempt_role",
+        "exempt_channel",
+        "pardon",
+        "disable"
+    )
+)]
+pub async fn filter_rules(_: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+/// Show the filter rules of this guild.
+#[lumi::command(prefix_command, check = "admin", guild_only, hide_in_help)]
+pub async fn view(ctx: Context<'_>) -> Result<(), Error> {
+    let Some(rules) = ctx
+        .data()
+        .database
+        .get_filter_rules(ctx.guild_id().unwrap())
+    else {
+        ctx.say("Filtered messages aren't acted on in this guild.")
+            .await?;
+        return Ok(());
+    };
+
+    let action =
+        |action: Option<RuleAction>| action.map_or_else(|| "Off".to_owned(), |a| format!("{a:?}"));
+    let list = |ids: Vec<String>| {
+        if ids.is_empty() {
+            String::from("None")
+        } else {
+            ids.join(", ")
+        }
+    };
+
+    let mut string = format!(
+        "**Moderate:** {}\n**Severe:** {}\n**Badlist:** {}\n**Timeout:** {}m\n**Report \
+         channel:** {}\n**Exempt roles:** {}\n**Exempt channels:** {}\n\n**Escalation** (strikes \
+         within {}h)\n",
+        action(rules.moderate_action),
+        action(rules.severe_action),
+        action(rules.badlist_action),
+        rules.timeout_for.as_secs() / 60,
+        rules
+            .report_channel
+            .map_or_else(|| "Staff channel".to_owned(), |c| format!("<#{c}>")),
+        list(
+            rules
+                .exempt_roles
+                .iter()
+                .map(|r| format!("<@&{r}>"))
+                .collect()
+        ),
+        list(
+            rules
+                .exempt_channels
+                .iter()
+                .map(|c| format!("<#{c}>"))
+                .collect()
+        ),
+        rules.strike_window.as_secs() / 60 / 60,
+    );
+
+    if rules.escalation.is_empty() {
+        string.push_str("None");
+    }
+    for step in &rules.escalation {
+        write!(string, "{} strikes: {:?}", step.strikes, step.action).unwrap();
+        if step.action == RuleAction::Timeout {
+            write!(string, " ({}m)", step.timeout_for.as_secs() / 60).unwrap();
+        }
+        string.push('\n');
+    }
+
+    ctx.send(
+        lumi::CreateReply::new()
+            .content(string)
+            .allowed_mentions(CreateAllowedMentions::new()),
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Set what happens to messages at a level, enabling the rules. Leave the action out to ignore
+/// the level.
+#[lumi::command(prefix_command, check = "admin", guild_only, hide_in_help)]
+pub async fn action(
+    ctx: Context<'_>,
+    #[description = "What the message triggered."] trigger: FilterTrigger,
+    #[description = "What happens to the message."] action: Option<RuleActionChoice>,
+) -> Result<(), Error> {
+    update(ctx, |rules| {
+        let slot = match trigger {
+            FilterTrigger::Moderate => &mut rules.moderate_action,
+            FilterTrigger::Severe => &mut rules.severe_action,
+            FilterTrigger::Badlist => &mut rules.badlist_action,
+        };
+        *slot = action.map(RuleAction::from);
+
+        match action {
+            Some(action) => format!("{trigger:?} messages will now be {action:?}."),
+            None => format!("{trigger:?} messages will now be ignored."),
+        }
+    })
+    .await
+}
+
+/// Set how long members are timed out for when a rule times them out.
+#[lumi::command(prefix_command, check = "admin", guild_only, hide_in_help)]
+pub async fn timeout(
+    ctx: Context<'_>,
+    #[description = "How many minutes to time out for."] mins: u16,
+) -> Result<(), Error> {
+    let mins = clamp_timeout(mins);
+    update(ctx, |rules| {
+        rules.timeout_for = Duration::from_secs(u64::from(mins) * 60);
+        format!("Filter timeouts now last {mins}m.")
+    })
+    .await
+}
+
+/// Set the action for members with this many strikes, leave the action out to remove the step.
+#[lumi::command(prefix_command, check = "admin", guild_only, hide_in_help)]
+pub async fn escalate(
+    ctx: Context<'_>,
+    #[description = "Strikes within the window needed."] strikes: u16,
+    #[description = "What happens to the message."] action: Option<RuleActionChoice>,
+    #[description = "How many minutes to time out for."] timeout_mins: Option<u16>,
+) -> Result<(), Error> {
+    if strikes == 0 {
+        ctx.say("Escalation steps need at least 1 strike.").await?;
+        return Ok(());
+    }
+
+    update(ctx, |rules| {
+        rules.escalation.retain(|s| s.strikes != strikes);
+
+        let Some(action) = action.map(RuleAction::from) else {
+            return format!("Removed the step at {strikes} strikes.");
+        };
+
+        let timeout_for = timeout_mins.map_or(rules.timeout_for, |m| {
+            Duration::from_secs(u64::from(clamp_timeout(m)) * 60)
+        });
+        rules.escalation.push(EscalationStep {
+            strikes,
+            action,
+            timeout_for,
+        });
+
+        format!("Members with {strikes} strikes will now be {action:?}.")
+    })
+    .await
+}
+
+/// Set how many hours a strike counts towards escalation.
+#[lumi::command(prefix_command, check = "admin", guild_only, hide_in_help)]
+pub async fn window(
+    ctx: Context<'_>,
+    #[description = "How many hours strikes last."] hours: u16,
+) -> Result<(), Error> {
+    let hours = hours.max(1);
+    update(ctx, |rules| {
+        rules.strike_window = Duration::from_secs(u64::from(hours) * 60 * 60);
+        format!("Strikes now last {hours}h.")
+    })
+    .await
+}
+
+/// Set where filtered messages are reported, leave it out to use the staff channel.
+#[lumi::command(
+    prefix_command,
+    rename = "report-channel",
+    check = "admin",
+    guild_only,
+    hide_in_help
+)]
+pub async fn report_channel(
+    ctx: Context<'_>,
+    #[description = "The channel to report to."] channel: Option<GenericChannelId>,
+) -> Result<(), Error> {
+    update(ctx, |rules| {
+        rules.report_channel = channel;
+        match channel {
+            Some(channel) => format!("Filtered messages will now be reported in <#{channel}>."),
+            None => "Filtered messages will now be reported in the staff channel.".to_owned(),
+        }
+    })
+    .await
+}
+
+/// Toggle a role being ignored by the filter rules.
+#[lumi::command(
+    prefix_command,
+    rename = "exempt-role",
+    check = "admin",
+    guild_only,
+    hide_in_help
+)]
+pub async fn exempt_role(ctx: Context<'_>, role: Role) -> Result<(), Error> {
+    update(ctx, |rules| {
+        if toggle(&mut rules.exempt_roles, role.id) {
+            format!("{} is now exempt from the filter rules.", role.name)
+        } else {
+            format!("{} is no longer exempt from the filter rules.", role.name)
+        }
+    })
+    .await
+}
+
+/// Toggle a channel being ignored by the filter rules.
+#[lumi::command(
+    prefix_command,
+    rename = "exempt-channel",
+    check = "admin",
+    guild_only,
+    hide_in_help
+)]
+pub async fn exempt_channel(ctx: Context<'_>, channel: GenericChannelId) -> Result<(), Error> {
+    update(ctx, |rules| {
+        if toggle(&mut rules.exempt_channels, channel) {
+            format!("<#{channel}> is now exempt from the filter rules.")
+        } else {
+            format!("<#{channel}> is no longer exempt from the filter rules.")
+        }
+    })
+    .await
+}
+
+/// Clear the strikes of a member.
+#[lumi::command(prefix_command, check = "admin", guild_only, hide_in_help)]
+pub async fn pardon(ctx: Context<'_>, user: User) -> Result<(), Error> {
+    let removed = ctx
+        .data()
+        .database
+        .clear_filter_strikes(ctx.guild_id().unwrap(), user.id)
+        .await?;
+
+    ctx.say(format!("Removed {removed} strikes from {}.", user.tag()))
+        .await?;
+
+    Ok(())
+}
+
+/// Stop acting on filtered messages, clearing the rules.
+#[lumi::command(prefix_command, check = "admin", guild_only, hide_in_help)]
+pub async fn disable(ctx: Context<'_>) -> Result<(), Error> {
+    ctx.data()
+        .database
+        .set_filter_rules(ctx.guild_id().unwrap(), None)
+        .await?;
+
+    ctx.say("Filtered messages are no longer acted on.").await?;
+
+    Ok(())
+}
+
+async fn update(
+    ctx: Context<'_>,
+    change: impl FnOnce(&mut FilterRules) -> String,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().unwrap();
+    let database = &ctx.data().database;
+
+    let mut rules = database.get_filter_rules(guild_id).unwrap_or_default();
+    let msg = change(&mut rules);
+    database.set_filter_rules(guild_id, Some(rules)).await?;
+
+    ctx.say(msg).await?;
+
+    Ok(())
+}
+
+/// Discord caps timeouts at 28 days.
+fn clamp_timeout(mins: u16) -> u16 {
+    mins.clamp(1, 28 * 24 * 60)
+}
+
+#[must_use]
+pub fn commands() -> [crate::Command; 1] {
+    [filter_rules()]
+}
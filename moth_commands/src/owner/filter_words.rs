@@ -0,0 +1,237 @@
+use crate::{owner::admin, Context, Error};
+use lumi::serenity_prelude::GuildId;
+use moth_core::data::database::filter_words::{FilterWordKind, FilterWordSeverity};
+use moth_filter::Severity;
+use std::fmt::Write;
+
+#[derive(Debug, Clone, Copy, lumi::ChoiceParameter)]
+pub enum FilterWordChoice {
+    Bad,
+    Fix,
+    Safe,
+    Profane,
+    Offensive,
+    Sexual,
+    Mean,
+}
+
+impl FilterWordChoice {
+    fn kind(self) -> FilterWordKind {
+        match self {
+            Self::Bad => FilterWordKind::Bad,
+            Self::Fix => FilterWordKind::Fix,
+            Self::Safe => FilterWordKind::Safe,
+            Self::Profane => FilterWordKind::Profane,
+            Self::Offensive => FilterWordKind::Offensive,
+            Self::Sexual => FilterWordKind::Sexual,
+            Self::Mean => FilterWordKind::Mean,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, lumi::ChoiceParameter)]
+pub enum SeverityChoice {
+    Mild,
+    Moderate,
+    Severe,
+}
+
+impl SeverityChoice {
+    fn severity(self) -> FilterWordSeverity {
+        match self {
+            Self::Mild => FilterWordSeverity::Mild,
+            Self::Moderate => FilterWordSeverity::Moderate,
+            Self::Severe => FilterWordSeverity::Severe,
+        }
+    }
+}
+
+/// Manage the words the filter checks for, used in dms it manages the global words.
+#[lumi::command(
+    prefix_command,
+    category = "Admin - Filter",
+    check = "admin",
+    hide_in_help,
+    subcommands("add", "remove", "list", "test")
+)]
+pub async fn filter(_: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+/// Add a word to the filter, replacing what it was before.
+///
+/// bad words flag anything containing them and fix words stop that, safe words are never flagged
+/// by rustrict. The rest tell the filter what a word is, at the severity given.
+#[lumi::command(prefix_command, check = "admin", hide_in_help)]
+pub async fn add(
+    ctx: Context<'_>,
+    #[description = "What the word is."] kind: FilterWordChoice,
+    #[description = "The word."] word: String,
+    #[description = "How bad the word is, only for categories."] severity: Option<SeverityChoice>,
+) -> Result<(), Error> {
+    let Some(scope) = scope(ctx).await? else {
+        return Ok(());
+    };
+
+    let word = word.trim().to_lowercase();
+    if word.is_empty() || word.contains(char::is_whitespace) {
+        ctx.say("Filter words must be a single word.").await?;
+        return Ok(());
+    }
+
+    let kind = kind.kind();
+    if severity.is_some() && !kind.is_category() {
+        ctx.say(format!("{} words don't have a severity.", kind.name()))
+            .await?;
+        return Ok(());
+    }
+
+    ctx.data()
+        .database
+        .add_filter_word(scope, &word, kind, severity.map(SeverityChoice::severity))
+        .await?;
+
+    ctx.say(format!(
+        "`{word}` is now a {} word {}.",
+        kind.name(),
+        scope_name(scope)
+    ))
+    .await?;
+
+    Ok(())
+}
+
+/// Remove a word from the filter.
+#[lumi::command(prefix_command, check = "admin", hide_in_help)]
+pub async fn remove(
+    ctx: Context<'_>,
+    #[description = "The word."]
+    #[rest]
+    word: String,
+) -> Result<(), Error> {
+    let Some(scope) = scope(ctx).await? else {
+        return Ok(());
+    };
+
+    let removed = ctx.data().database.remove_filter_word(scope, &word).await?;
+
+    let word = word.trim().to_lowercase();
+    if removed {
+        ctx.say(format!("Removed `{word}` {}.", scope_name(scope)))
+            .await?;
+    } else {
+        ctx.say(format!(
+            "`{word}` isn't a filter word {}.",
+            scope_name(scope)
+        ))
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// List the filter words, optionally only of one kind.
+#[lumi::command(prefix_command, check = "admin", hide_in_help)]
+pub async fn list(
+    ctx: Context<'_>,
+    #[description = "Only list words of this kind."] kind: Option<FilterWordChoice>,
+) -> Result<(), Error> {
+    let scope = ctx.guild_id();
+    let kind = kind.map(FilterWordChoice::kind);
+
+    let mut words = ctx.data().database.get_filter_words(scope);
+    words.retain(|w| kind.is_none_or(|k| w.kind == k));
+    words.sort_unstable_by(|a, b| a.word.cmp(&b.word));
+
+    let header = format!("**Filter words {}**\n", scope_name(scope));
+    let mut pages = Vec::new();
+    for chunk in words.chunks(20) {
+        let mut page = header.clone();
+        for word in chunk {
+            write!(page, "`{}`: {}", word.word, word.kind.name()).unwrap();
+            if let Some(severity) = word.severity {
+                write!(page, " ({severity:?})").unwrap();
+            }
+            page.push('\n');
+        }
+        pages.push(page);
+    }
+
+    if pages.is_empty() {
+        pages.push(format!("{header}None"));
+    }
+
+    if scope.is_some() {
+        let global = ctx.data().database.get_filter_words(None).len();
+        for page in &mut pages {
+            write!(page, "\n-# Plus {global} global words.").unwrap();
+        }
+    }
+
+    let pages = pages.iter().map(String::as_str).collect::<Vec<_>>();
+    lumi::builtins::paginate(ctx, &pages).await?;
+
+    Ok(())
+}
+
+/// See what the filter thinks of some text here.
+#[lumi::command(prefix_command, check = "admin", hide_in_help)]
+pub async fn test(
+    ctx: Context<'_>,
+    #[description = "The text to check."]
+    #[rest]
+    text: String,
+) -> Result<(), Error> {
+    // leaves room for the highlighting and the verdict.
+    if text.len() > 1500 {
+        ctx.say("That's too much text to test at once.").await?;
+        return Ok(());
+    }
+
+    let lists = ctx.data().database.get_filter_lists(ctx.guild_id());
+    let (highlighted, verdict) = moth_filter::inspect(&text, &lists);
+
+    let severity = match verdict.severity {
+        Some(Severity::Severe) => "Severe",
+        Some(Severity::Moderate) => "Moderate",
+        None => "None",
+    };
+    let badlist = if verdict.badlist { "Yes" } else { "No" };
+
+    let highlighted = highlighted.replace("```", "`\u{200b}``");
+
+    ctx.say(format!(
+        "**Severity:** {severity}\n**Badlisted:** {badlist}\n```ansi\n{highlighted}\n```"
+    ))
+    .await?;
+
+    Ok(())
+}
+
+/// The guild words are managed for, `None` for the global words which only owners can manage.
+async fn scope(ctx: Context<'_>) -> Result<Option<Option<GuildId>>, Error> {
+    if let Some(guild_id) = ctx.guild_id() {
+        return Ok(Some(Some(guild_id)));
+    }
+
+    if ctx.framework().options.owners.contains(&ctx.author().id) {
+        return Ok(Some(None));
+    }
+
+    ctx.say("Only owners can manage the global filter words.")
+        .await?;
+    Ok(None)
+}
+
+fn scope_name(scope: Option<GuildId>) -> &'static str {
+    if scope.is_some() {
+        "in this server"
+    } else {
+        "globally"
+    }
+}
+
+#[must_use]
+pub fn commands() -> [crate::Command; 1] {
+    [filter()]
+}
@@ -0,0 +1,237 @@
+use crate::{
+    owner::admin,
+    utils::{toggle, RuleActionChoice},
+    Context, Error,
+};
+use lumi::serenity_prelude::{CreateAllowedMentions, GenericChannelId, GuildId, Role};
+use moth_core::data::database::{invite_rules::InviteRules, rule_action::RuleAction};
+use std::{fmt::Write, time::Duration};
+
+/// Manage which invites can be posted in this guild.
+#[lumi::command(
+    prefix_command,
+    rename = "invite-rules",
+    category = "Admin - Invites",
+    check = "admin",
+    guild_only,
+    hide_in_help,
+    subcommands(
+        "view",
+        "action",
+        "allow",
+        "deny",
+        "exempt_role",
+        "exempt_channel",
+        "disable"
+    )
+)]
+pub async fn invite_rules(_: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+/// Show the invite rules of this guild.
+#[lumi::command(prefix_command, check = "admin", guild_only, hide_in_help)]
+pub async fn view(ctx: Context<'_>) -> Result<(), Error> {
+    let Some(rules) = ctx
+        .data()
+        .database
+        .get_invite_rules(ctx.guild_id().unwrap())
+    else {
+        ctx.say("Invites aren't enforced in this guild.").await?;
+        return Ok(());
+    };
+
+    let list = |ids: Vec<String>| {
+        if ids.is_empty() {
+            String::from("None")
+        } else {
+            ids.join(", ")
+        }
+    };
+
+    let mut string = format!("**Action:** {:?}", rules.action);
+    if rules.action >= RuleAction::Timeout {
+        write!(string, " ({}m)", rules.timeout_for.as_secs() / 60).unwrap();
+    }
+
+    write!(
+        string,
+        "\n**Allowed guilds:** {}\n**Denied guilds:** {}\n**Exempt roles:** {}\n**Exempt \
+         channels:** {}",
+        if rules.allowed_guilds.is_empty() {
+            String::from("Any")
+        } else {
+            list(
+                rules
+                    .allowed_guilds
+                    .iter()
+                    .map(|g| format!("`{g}`"))
+                    .collect(),
+            )
+        },
+        list(
+            rules
+                .denied_guilds
+                .iter()
+                .map(|g| format!("`{g}`"))
+                .collect()
+        ),
+        list(
+            rules
+                .exempt_roles
+                .iter()
+                .map(|r| format!("<@&{r}>"))
+                .collect()
+        ),
+        list(
+            rules
+                .exempt_channels
+                .iter()
+                .map(|c| format!("<#{c}>"))
+                .collect()
+        ),
+    )
+    .unwrap();
+
+    ctx.send(
+        lumi::CreateReply::new()
+            .content(string)
+            .allowed_mentions(CreateAllowedMentions::new()),
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Set what happens when an invite that isn't allowed is posted, enabling the rules.
+#[lumi::command(prefix_command, check = "admin", guild_only, hide_in_help)]
+pub async fn action(
+    ctx: Context<'_>,
+    #[description = "What happens to the message."] action: RuleActionChoice,
+    #[description = "How long the author is timed out for."] timeout_mins: Option<u16>,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().unwrap();
+    let database = &ctx.data().database;
+
+    let mut rules = database.get_invite_rules(guild_id).unwrap_or_default();
+    rules.action = action.into();
+    if let Some(mins) = timeout_mins.filter(|&m| m != 0) {
+        // discord caps timeouts at 28 days.
+        rules.timeout_for = Duration::from_secs(u64::from(mins.min(28 * 24 * 60)) * 60);
+    }
+
+    database.set_invite_rules(guild_id, Some(rules)).await?;
+
+    ctx.say(format!(
+        "Invites that aren't allowed will now be {action:?}."
+    ))
+    .await?;
+
+    Ok(())
+}
+
+/// Toggle a guild being allowed, once any guild is allowed every other guild is not.
+#[lumi::command(prefix_command, check = "admin", guild_only, hide_in_help)]
+pub async fn allow(
+    ctx: Context<'_>,
+    #[description = "The guild invites can point to."] guild: GuildId,
+) -> Result<(), Error> {
+    update(ctx, |rules| {
+        rules.denied_guilds.retain(|g| *g != guild);
+        if toggle(&mut rules.allowed_guilds, guild) {
+            format!("Invites to `{guild}` are now allowed.")
+        } else {
+            format!("Invites to `{guild}` are no longer explicitly allowed.")
+        }
+    })
+    .await
+}
+
+/// Toggle a guild being denied.
+#[lumi::command(prefix_command, check = "admin", guild_only, hide_in_help)]
+pub async fn deny(
+    ctx: Context<'_>,
+    #[description = "The guild invites can't point to."] guild: GuildId,
+) -> Result<(), Error> {
+    update(ctx, |rules| {
+        rules.allowed_guilds.retain(|g| *g != guild);
+        if toggle(&mut rules.denied_guilds, guild) {
+            format!("Invites to `{guild}` are now denied.")
+        } else {
+            format!("Invites to `{guild}` are no longer denied.")
+        }
+    })
+    .await
+}
+
+/// Toggle a role being able to post any invite.
+#[lumi::command(
+    prefix_command,
+    rename = "exempt-role",
+    check = "admin",
+    guild_only,
+    hide_in_help
+)]
+pub async fn exempt_role(ctx: Context<'_>, role: Role) -> Result<(), Error> {
+    update(ctx, |rules| {
+        if toggle(&mut rules.exempt_roles, role.id) {
+            format!("{} can now post any invite.", role.name)
+        } else {
+            format!("{} is no longer exempt from the invite rules.", role.name)
+        }
+    })
+    .await
+}
+
+/// Toggle any invite being allowed in a channel.
+#[lumi::command(
+    prefix_command,
+    rename = "exempt-channel",
+    check = "admin",
+    guild_only,
+    hide_in_help
+)]
+pub async fn exempt_channel(ctx: Context<'_>, channel: GenericChannelId) -> Result<(), Error> {
+    update(ctx, |rules| {
+        if toggle(&mut rules.exempt_channels, channel) {
+            format!("Any invite can now be posted in <#{channel}>.")
+        } else {
+            format!("<#{channel}> is no longer exempt from the invite rules.")
+        }
+    })
+    .await
+}
+
+/// Stop enforcing invites, clearing the rules.
+#[lumi::command(prefix_command, check = "admin", guild_only, hide_in_help)]
+pub async fn disable(ctx: Context<'_>) -> Result<(), Error> {
+    ctx.data()
+        .database
+        .set_invite_rules(ctx.guild_id().unwrap(), None)
+        .await?;
+
+    ctx.say("Invites are no longer enforced.").await?;
+
+    Ok(())
+}
+
+async fn update(
+    ctx: Context<'_>,
+    change: impl FnOnce(&mut InviteRules) -> String,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().unwrap();
+    let database = &ctx.data().database;
+
+    let mut rules = database.get_invite_rules(guild_id).unwrap_or_default();
+    let msg = change(&mut rules);
+    database.set_invite_rules(guild_id, Some(rules)).await?;
+
+    ctx.say(msg).await?;
+
+    Ok(())
+}
+
+#[must_use]
+pub fn commands() -> [crate::Command; 1] {
+    [invite_rules()]
+}
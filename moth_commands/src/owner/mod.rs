@@ -2,11 +2,16 @@ pub mod cache;
 pub mod checks;
 pub mod cooldowns;
 pub mod database;
+pub mod expressions;
+pub mod filter_rules;
+pub mod filter_words;
+pub mod invites;
 pub mod other;
 pub mod phil;
 pub mod poll;
 pub mod presence;
 pub mod roles;
+pub mod settings;
 
 use crate::{Context, Error};
 
@@ -19,12 +24,17 @@ pub fn commands() -> Vec<crate::Command> {
             .into_iter()
             .chain(checks::commands())
             .chain(database::commands())
+            .chain(expressions::commands())
+            .chain(filter_rules::commands())
+            .chain(filter_words::commands())
+            .chain(invites::commands())
             .chain(presence::commands())
             .chain(other::commands())
             .chain(cooldowns::commands())
             .chain(phil::commands())
             .chain(poll::commands())
             .chain(roles::commands())
+            .chain(settings::commands())
             .collect()
     }
 }
@@ -288,7 +288,8 @@ async fn sudo(
     guild_only
 )]
 async fn analyze(ctx: Context<'_>, #[rest] msg: String) -> Result<(), Error> {
-    let kind = format!("{:?}", moth_filter::analyze(&msg));
+    let lists = ctx.data().database.get_filter_lists(ctx.guild_id());
+    let kind = format!("{:?}", moth_filter::analyze(&msg, &lists));
     ctx.say(kind).await?;
     Ok(())
 }
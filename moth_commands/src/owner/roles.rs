@@ -2,7 +2,7 @@ use lumi::serenity_prelude as serenity;
 use moth_core::emojis::{Checkmark, Question, X};
 use small_fixed_array::FixedString;
 
-use crate::{Error, PrefixContext};
+use crate::{Context, Error, PrefixContext, owner::admin};
 
 #[lumi::command(
     rename = "+",
@@ -239,7 +239,132 @@ pub async fn role_remove(
     Ok(())
 }
 
+/// View or restore the roles a user had when they last left.
+#[lumi::command(
+    rename = "role-snapshot",
+    prefix_command,
+    category = "Admin - Roles",
+    check = "admin",
+    hide_in_help,
+    guild_only,
+    subcommands("snapshot_view", "snapshot_restore")
+)]
+pub async fn role_snapshot(_: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+/// View the roles a user had when they last left.
+#[lumi::command(
+    rename = "view",
+    prefix_command,
+    check = "admin",
+    hide_in_help,
+    guild_only
+)]
+pub async fn snapshot_view(ctx: Context<'_>, user: serenity::User) -> Result<(), Error> {
+    let snapshot = ctx
+        .data()
+        .database
+        .get_latest_role_snapshot(ctx.guild_id().unwrap(), user.id)
+        .await?;
+
+    let Some(snapshot) = snapshot else {
+        ctx.say(format!("No role snapshot for {}.", user.tag()))
+            .await?;
+        return Ok(());
+    };
+
+    let roles = snapshot
+        .roles
+        .iter()
+        .map(|r| format!("<@&{r}>"))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let taken = snapshot
+        .taken_at
+        .map_or_else(|| "unknown".into(), |t| format!("<t:{}:R>", t.timestamp()));
+
+    ctx.send(
+        lumi::CreateReply::new()
+            .content(format!("Roles of {} taken {taken}:\n{roles}", user.tag()))
+            .allowed_mentions(serenity::CreateAllowedMentions::new()),
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Give a member back every role from their last snapshot.
+#[lumi::command(
+    rename = "restore",
+    prefix_command,
+    check = "admin",
+    hide_in_help,
+    guild_only
+)]
+pub async fn snapshot_restore(ctx: Context<'_>, member: serenity::Member) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().unwrap();
+    let snapshot = ctx
+        .data()
+        .database
+        .get_latest_role_snapshot(guild_id, member.user.id)
+        .await?;
+
+    let Some(snapshot) = snapshot else {
+        ctx.say(format!("No role snapshot for {}.", member.user.tag()))
+            .await?;
+        return Ok(());
+    };
+
+    let roles = {
+        let Some(guild) = ctx.guild() else {
+            ctx.say("Could not retrieve guild from cache.").await?;
+            return Ok(());
+        };
+
+        let highest_position = guild
+            .member_highest_role(
+                guild
+                    .members
+                    .get(&ctx.cache().current_user().id)
+                    .expect("Discord docs indicate the bot user is always in cache"),
+            )
+            .map_or(0, |r| r.position);
+
+        snapshot
+            .roles
+            .iter()
+            .filter(|r| r.get() != guild_id.get() && !member.roles.contains(r))
+            .filter_map(|r| guild.roles.get(r))
+            .filter(|r| is_assignable(r) && r.position < highest_position)
+            .map(|r| r.id)
+            .collect::<Vec<_>>()
+    };
+
+    let reason = format!("Role snapshot restored by {}.", ctx.author().name);
+    let mut restored = 0;
+    for role_id in &roles {
+        if ctx
+            .http()
+            .add_member_role(guild_id, member.user.id, *role_id, Some(&reason))
+            .await
+            .is_ok()
+        {
+            restored += 1;
+        }
+    }
+
+    ctx.say(format!(
+        "Restored {restored}/{} roles to {}.",
+        roles.len(),
+        member.user.tag()
+    ))
+    .await?;
+
+    Ok(())
+}
+
 #[must_use]
-pub fn commands() -> [crate::Command; 2] {
-    [role_add(), role_remove()]
+pub fn commands() -> [crate::Command; 3] {
+    [role_add(), role_remove(), role_snapshot()]
 }
@@ -0,0 +1,601 @@
+use crate::{owner::admin, Context, Error};
+use lumi::serenity_prelude::{CreateAllowedMentions, GenericChannelId, Role, RoleId};
+use moth_core::data::database::{
+    mod_log::{ModLogEvent, ModLogTarget},
+    GuildSettings,
+};
+use std::fmt::Write;
+
+#[derive(Debug, lumi::ChoiceParameter)]
+pub enum ChannelSetting {
+    Staff,
+    Automod,
+    #[name = "vc-status"]
+    VcStatusAlert,
+    #[name = "invites"]
+    InviteLog,
+    #[name = "mass-delete"]
+    MassDelete,
+    Verification,
+    #[name = "private-vc"]
+    PrivateVc,
+    #[name = "super-poop"]
+    SuperPoop,
+    Modmail,
+}
+
+#[derive(Debug, lumi::ChoiceParameter)]
+pub enum Feature {
+    #[name = "raid-alerts"]
+    RaidAlerts,
+    #[name = "dm-activity"]
+    DmActivityAlerts,
+    #[name = "automod-alerts"]
+    AutomodAlerts,
+    #[name = "invites"]
+    InviteLogging,
+    #[name = "mass-delete"]
+    MassDeleteAlerts,
+    #[name = "private-vcs"]
+    PrivateVcs,
+    Verification,
+    #[name = "super-poop"]
+    AutoSuperPoop,
+    #[name = "role-persistence"]
+    RolePersistence,
+    #[name = "ocr"]
+    OcrScanning,
+    Modmail,
+    #[name = "presence-history"]
+    PresenceHistory,
+}
+
+#[derive(Debug, Clone, Copy, lumi::ChoiceParameter)]
+pub enum ModLogSetting {
+    #[name = "message-edit"]
+    MessageEdit,
+    #[name = "message-delete"]
+    MessageDelete,
+    #[name = "member-join"]
+    MemberJoin,
+    #[name = "member-leave"]
+    MemberLeave,
+    #[name = "member-update"]
+    MemberUpdate,
+    #[name = "role-update"]
+    RoleUpdate,
+    #[name = "channel-update"]
+    ChannelUpdate,
+}
+
+impl From<ModLogSetting> for ModLogEvent {
+    fn from(setting: ModLogSetting) -> Self {
+        match setting {
+            ModLogSetting::MessageEdit => ModLogEvent::MessageEdit,
+            ModLogSetting::MessageDelete => ModLogEvent::MessageDelete,
+            ModLogSetting::MemberJoin => ModLogEvent::MemberJoin,
+            ModLogSetting::MemberLeave => ModLogEvent::MemberLeave,
+            ModLogSetting::MemberUpdate => ModLogEvent::MemberUpdate,
+            ModLogSetting::RoleUpdate => ModLogEvent::RoleUpdate,
+            ModLogSetting::ChannelUpdate => ModLogEvent::ChannelUpdate,
+        }
+    }
+}
+
+/// View or change the settings for this guild.
+#[lumi::command(
+    prefix_command,
+    category = "Admin - Settings",
+    check = "admin",
+    guild_only,
+    hide_in_help,
+    subcommands(
+        "view",
+        "channel",
+        "toggle",
+        "moderator_role",
+        "persistable_role",
+        "archive_channel",
+        "mod_log",
+        "mass_delete",
+        "super_poop_role",
+        "automod_rule"
+    )
+)]
+pub async fn settings(_: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+/// View the settings for this guild.
+#[lumi::command(prefix_command, check = "admin", guild_only, hide_in_help)]
+pub async fn view(ctx: Context<'_>) -> Result<(), Error> {
+    let settings = ctx
+        .data()
+        .database
+        .get_guild_settings(ctx.guild_id().unwrap());
+
+    let channel =
+        |c: Option<GenericChannelId>| c.map_or_else(|| "None".into(), |c| format!("<#{c}>"));
+    let toggle = |b: bool| if b { "✅" } else { "❌" };
+
+    let roles = |roles: &[RoleId]| {
+        roles
+            .iter()
+            .map(|r| format!("<@&{r}>"))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    let moderator_roles = roles(&settings.moderator_roles);
+    let persistable_roles = roles(&settings.persistable_roles);
+    let archived_channels = settings
+        .archived_channels
+        .iter()
+        .map(|c| format!("<#{c}>"))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    let thresholds = settings.mass_delete_thresholds();
+
+    let mut string = format!(
+        "**Channels**\nStaff: {}\nAutomod: {}\nVC status alerts: {}\nInvite log: {}\nMass \
+         delete: {}\nVerification log: {}\nPrivate VC: {}\nSuper poop: {}\nModmail: \
+         {}\n\n**Features**\nRaid alerts: {}\nDM activity: {}\nAutomod alerts: {}\nInvites: \
+         {}\nMass delete: {}\nPrivate VCs: {}\nVerification: {}\nSuper poop: {}\nRole \
+         persistence: {}\nOCR: {}\nModmail: {}\nPresence history: {}\n\n**Automod**\nRule: {}\n\n**Roles**\nSuper poop: {}\nModerators: {moderator_roles}\nPersistable: \
+         {persistable_roles}\n\n**Archived channels**\n{archived_channels}\n\n**Mass \
+         delete**\nCheck at heat: {}\nMax heat: {}\nDecays after: {}s",
+        channel(settings.staff_channel),
+        channel(settings.automod_channel),
+        channel(settings.vc_status_alert_channel),
+        channel(settings.invite_log_channel),
+        channel(settings.mass_delete_channel),
+        channel(settings.verification_log_channel),
+        channel(settings.private_vc_channel.map(|c| c.widen())),
+        channel(settings.super_poop_channel),
+        channel(settings.modmail_channel.map(|c| c.widen())),
+        toggle(settings.raid_alerts()),
+        toggle(settings.dm_activity_alerts()),
+        toggle(settings.automod_alerts()),
+        toggle(settings.invite_logging()),
+        toggle(settings.mass_delete_alerts()),
+        toggle(settings.private_vcs()),
+        toggle(settings.verification()),
+        toggle(settings.auto_super_poop()),
+        toggle(settings.role_persistence()),
+        toggle(settings.ocr_scanning()),
+        toggle(settings.modmail()),
+        toggle(settings.presence_history()),
+        settings.automod_rule.as_deref().unwrap_or("Any"),
+        settings
+            .super_poop_role
+            .map_or_else(|| "None".into(), |r| format!("<@&{r}>")),
+        thresholds.check_heat,
+        thresholds.max_heat,
+        thresholds.decay_after.as_secs(),
+    );
+
+    let mut routes = ctx
+        .data()
+        .database
+        .get_mod_log_targets(ctx.guild_id().unwrap());
+    routes.sort_by_key(|(event, _)| *event as u8);
+
+    string.push_str("\n\n**Mod log**");
+    for (event, target) in routes {
+        // webhook urls contain the token, so they're never shown.
+        let target = match target {
+            ModLogTarget::Channel(channel_id) => format!("<#{channel_id}>"),
+            ModLogTarget::Webhook(_) => "Webhook".to_owned(),
+        };
+        write!(string, "\n{}: {target}", event.name()).unwrap();
+    }
+
+    ctx.send(
+        lumi::CreateReply::new()
+            .content(string)
+            .allowed_mentions(CreateAllowedMentions::new()),
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Set or unset a log channel for this guild.
+#[lumi::command(prefix_command, check = "admin", guild_only, hide_in_help)]
+pub async fn channel(
+    ctx: Context<'_>,
+    #[description = "The channel setting to change."] setting: ChannelSetting,
+    #[description = "The channel to use, unsets the setting when not provided."] channel: Option<
+        GenericChannelId,
+    >,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().unwrap();
+    let database = &ctx.data().database;
+
+    let mut settings = (*database.get_guild_settings(guild_id)).clone();
+    match setting {
+        ChannelSetting::Staff => settings.staff_channel = channel,
+        ChannelSetting::Automod => settings.automod_channel = channel,
+        ChannelSetting::VcStatusAlert => settings.vc_status_alert_channel = channel,
+        ChannelSetting::InviteLog => settings.invite_log_channel = channel,
+        ChannelSetting::MassDelete => settings.mass_delete_channel = channel,
+        ChannelSetting::Verification => settings.verification_log_channel = channel,
+        ChannelSetting::PrivateVc => {
+            settings.private_vc_channel = channel.map(GenericChannelId::expect_channel);
+        }
+        ChannelSetting::SuperPoop => settings.super_poop_channel = channel,
+        ChannelSetting::Modmail => {
+            settings.modmail_channel = channel.map(GenericChannelId::expect_channel);
+        }
+    }
+
+    database.set_guild_settings(guild_id, settings).await?;
+
+    match channel {
+        Some(channel) => ctx.say(format!("Set {setting:?} to <#{channel}>.")).await?,
+        None => ctx.say(format!("Unset {setting:?}.")).await?,
+    };
+
+    Ok(())
+}
+
+/// Enable or disable a feature in this guild.
+#[lumi::command(prefix_command, check = "admin", guild_only, hide_in_help)]
+pub async fn toggle(
+    ctx: Context<'_>,
+    #[description = "The feature to toggle."] feature: Feature,
+    #[description = "If the feature should be enabled."] enabled: bool,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().unwrap();
+    let database = &ctx.data().database;
+
+    let mut settings = (*database.get_guild_settings(guild_id)).clone();
+    let setter: fn(&mut GuildSettings, bool) = match feature {
+        Feature::RaidAlerts => GuildSettings::set_raid_alerts,
+        Feature::DmActivityAlerts => GuildSettings::set_dm_activity_alerts,
+        Feature::AutomodAlerts => GuildSettings::set_automod_alerts,
+        Feature::InviteLogging => GuildSettings::set_invite_logging,
+        Feature::MassDeleteAlerts => GuildSettings::set_mass_delete_alerts,
+        Feature::PrivateVcs => GuildSettings::set_private_vcs,
+        Feature::Verification => GuildSettings::set_verification,
+        Feature::AutoSuperPoop => GuildSettings::set_auto_super_poop,
+        Feature::RolePersistence => GuildSettings::set_role_persistence,
+        Feature::OcrScanning => GuildSettings::set_ocr_scanning,
+        Feature::Modmail => GuildSettings::set_modmail,
+        Feature::PresenceHistory => GuildSettings::set_presence_history,
+    };
+    setter(&mut settings, enabled);
+
+    database.set_guild_settings(guild_id, settings).await?;
+
+    if matches!(feature, Feature::PresenceHistory) && !enabled {
+        database.end_guild_presence_activities(guild_id).await?;
+    }
+
+    let state = if enabled { "Enabled" } else { "Disabled" };
+    ctx.say(format!("{state} {feature:?}.")).await?;
+
+    Ok(())
+}
+
+/// Toggle a role being treated as a moderator role.
+#[lumi::command(
+    prefix_command,
+    rename = "moderator-role",
+    check = "admin",
+    guild_only,
+    hide_in_help
+)]
+pub async fn moderator_role(ctx: Context<'_>, role: Role) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().unwrap();
+    let database = &ctx.data().database;
+
+    let mut settings = (*database.get_guild_settings(guild_id)).clone();
+    let added = if let Some(pos) = settings.moderator_roles.iter().position(|r| *r == role.id) {
+        settings.moderator_roles.remove(pos);
+        false
+    } else {
+        settings.moderator_roles.push(role.id);
+        true
+    };
+
+    database.set_guild_settings(guild_id, settings).await?;
+
+    let msg = if added {
+        format!("{} is now a moderator role.", role.name)
+    } else {
+        format!("{} is no longer a moderator role.", role.name)
+    };
+    ctx.say(msg).await?;
+
+    Ok(())
+}
+
+/// Toggle a role being given back to members that leave and rejoin.
+#[lumi::command(
+    prefix_command,
+    rename = "persistable-role",
+    check = "admin",
+    guild_only,
+    hide_in_help
+)]
+pub async fn persistable_role(ctx: Context<'_>, role: Role) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().unwrap();
+    let database = &ctx.data().database;
+
+    let mut settings = (*database.get_guild_settings(guild_id)).clone();
+    let added = if let Some(pos) = settings
+        .persistable_roles
+        .iter()
+        .position(|r| *r == role.id)
+    {
+        settings.persistable_roles.remove(pos);
+        false
+    } else {
+        settings.persistable_roles.push(role.id);
+        true
+    };
+
+    database.set_guild_settings(guild_id, settings).await?;
+
+    let msg = if added {
+        format!("{} will now persist when members rejoin.", role.name)
+    } else {
+        format!("{} will no longer persist when members rejoin.", role.name)
+    };
+    ctx.say(msg).await?;
+
+    Ok(())
+}
+
+/// Toggle a channel having the content and edits of its messages archived.
+#[lumi::command(
+    prefix_command,
+    rename = "archive-channel",
+    check = "admin",
+    guild_only,
+    hide_in_help
+)]
+pub async fn archive_channel(ctx: Context<'_>, channel: GenericChannelId) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().unwrap();
+    let database = &ctx.data().database;
+
+    let mut settings = (*database.get_guild_settings(guild_id)).clone();
+    let added = if let Some(pos) = settings
+        .archived_channels
+        .iter()
+        .position(|c| *c == channel)
+    {
+        settings.archived_channels.remove(pos);
+        false
+    } else {
+        settings.archived_channels.push(channel);
+        true
+    };
+
+    database.set_guild_settings(guild_id, settings).await?;
+
+    let msg = if added {
+        format!("Messages in <#{channel}> will now be archived.")
+    } else {
+        format!("Messages in <#{channel}> will no longer be archived.")
+    };
+    ctx.say(msg).await?;
+
+    Ok(())
+}
+
+/// Route an event to a channel or webhook, unsets the route when no target is provided.
+#[lumi::command(
+    prefix_command,
+    rename = "mod-log",
+    check = "admin",
+    guild_only,
+    hide_in_help
+)]
+pub async fn mod_log(
+    ctx: Context<'_>,
+    #[description = "The event to route."] event: ModLogSetting,
+    #[description = "A channel or webhook url."] target: Option<String>,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().unwrap();
+    let event = ModLogEvent::from(event);
+
+    let target = match target {
+        Some(target) if target.starts_with("https://") => {
+            // webhook urls contain their token, so they can't be left in the channel.
+            if let lumi::Context::Prefix(pctx) = ctx {
+                let _ = pctx.msg.delete(ctx.http(), None).await;
+            }
+
+            if !target.contains("/api/webhooks/") {
+                ctx.say("That doesn't look like a webhook url.").await?;
+                return Ok(());
+            }
+            Some(ModLogTarget::Webhook(target))
+        }
+        Some(target) => {
+            let channel_id = target
+                .chars()
+                .filter(char::is_ascii_digit)
+                .collect::<String>()
+                .parse::<u64>()
+                .ok()
+                .filter(|&id| id != 0)
+                .map(GenericChannelId::new);
+
+            let in_guild = channel_id.is_some_and(|c| {
+                ctx.guild().is_some_and(|g| {
+                    g.channels.contains_key(&c.expect_channel())
+                        || g.threads.iter().any(|t| t.id == c.expect_thread())
+                })
+            });
+
+            if !in_guild {
+                ctx.say("Could not find that channel in this guild.")
+                    .await?;
+                return Ok(());
+            }
+            channel_id.map(ModLogTarget::Channel)
+        }
+        None => None,
+    };
+
+    let set = target.is_some();
+    ctx.data()
+        .database
+        .set_mod_log_target(guild_id, event, target)
+        .await?;
+
+    if set {
+        ctx.say(format!("{} will now be logged.", event.name()))
+            .await?;
+    } else {
+        ctx.say(format!("{} will no longer be logged.", event.name()))
+            .await?;
+    }
+
+    Ok(())
+}
+
+/// Change how deletes heat up this guild, anything not provided is kept and providing nothing
+/// resets to the defaults.
+#[lumi::command(
+    prefix_command,
+    rename = "mass-delete",
+    check = "admin",
+    guild_only,
+    hide_in_help
+)]
+pub async fn mass_delete(
+    ctx: Context<'_>,
+    #[description = "Heat needed before deletes are checked."] check_heat: Option<u16>,
+    #[description = "The most heat a guild can have."] max_heat: Option<u16>,
+    #[description = "Seconds without deletes before heat drops."] decay_secs: Option<u16>,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().unwrap();
+    let database = &ctx.data().database;
+
+    if check_heat == Some(0) || decay_secs == Some(0) {
+        ctx.say("Heat and decay must be at least 1.").await?;
+        return Ok(());
+    }
+
+    if [check_heat, max_heat, decay_secs]
+        .into_iter()
+        .flatten()
+        .any(|v| i16::try_from(v).is_err())
+    {
+        ctx.say(format!("Heat and decay can't be above {}.", i16::MAX))
+            .await?;
+        return Ok(());
+    }
+
+    let mut settings = (*database.get_guild_settings(guild_id)).clone();
+    if check_heat.is_none() && max_heat.is_none() && decay_secs.is_none() {
+        settings.mass_delete_check_heat = None;
+        settings.mass_delete_max_heat = None;
+        settings.mass_delete_decay_secs = None;
+    } else {
+        settings.mass_delete_check_heat = check_heat.or(settings.mass_delete_check_heat);
+        settings.mass_delete_max_heat = max_heat.or(settings.mass_delete_max_heat);
+        settings.mass_delete_decay_secs = decay_secs.or(settings.mass_delete_decay_secs);
+    }
+
+    let thresholds = settings.mass_delete_thresholds();
+    if settings
+        .mass_delete_max_heat
+        .is_some_and(|max| max < thresholds.check_heat)
+    {
+        ctx.say("The max heat can't be lower than the check heat.")
+            .await?;
+        return Ok(());
+    }
+
+    database.set_guild_settings(guild_id, settings).await?;
+
+    ctx.say(format!(
+        "Deletes are now checked at {} heat, heat caps at {} and decays after {}s.",
+        thresholds.check_heat,
+        thresholds.max_heat,
+        thresholds.decay_after.as_secs()
+    ))
+    .await?;
+
+    Ok(())
+}
+
+/// Set or unset the role given out by auto super poop.
+#[lumi::command(
+    prefix_command,
+    rename = "super-poop-role",
+    check = "admin",
+    guild_only,
+    hide_in_help
+)]
+pub async fn super_poop_role(ctx: Context<'_>, role: Option<Role>) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().unwrap();
+    let database = &ctx.data().database;
+
+    let mut settings = (*database.get_guild_settings(guild_id)).clone();
+    settings.super_poop_role = role.as_ref().map(|r| r.id);
+
+    database.set_guild_settings(guild_id, settings).await?;
+
+    match role {
+        Some(role) => {
+            ctx.say(format!("Set super poop role to {}.", role.name))
+                .await?
+        }
+        None => ctx.say("Unset super poop role.").await?,
+    };
+
+    Ok(())
+}
+
+/// Set or unset the automod rule whose flagged statuses are alerted for, any rule alerts when
+/// unset.
+#[lumi::command(
+    prefix_command,
+    rename = "automod-rule",
+    check = "admin",
+    guild_only,
+    hide_in_help
+)]
+pub async fn automod_rule(
+    ctx: Context<'_>,
+    #[description = "The name of the rule."]
+    #[rest]
+    rule: Option<String>,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().unwrap();
+    let database = &ctx.data().database;
+
+    let rule = rule.map(|r| r.trim().to_owned()).filter(|r| !r.is_empty());
+    let mut settings = (*database.get_guild_settings(guild_id)).clone();
+    settings.automod_rule = rule.clone();
+
+    database.set_guild_settings(guild_id, settings).await?;
+
+    match rule {
+        Some(rule) => {
+            ctx.send(
+                lumi::CreateReply::new()
+                    .content(format!("Only statuses flagged by {rule} will be alerted."))
+                    .allowed_mentions(CreateAllowedMentions::new()),
+            )
+            .await?
+        }
+        None => {
+            ctx.say("Statuses flagged by any rule will be alerted.")
+                .await?
+        }
+    };
+
+    Ok(())
+}
+
+#[must_use]
+pub fn commands() -> [crate::Command; 1] {
+    [settings()]
+}
@@ -0,0 +1,160 @@
+use crate::{
+    utils::{format_secs, paginate_text},
+    Context, Error,
+};
+use chrono::{Duration, Utc};
+use lumi::serenity_prelude::User;
+use moth_core::data::database::presence::PresenceEvent;
+use std::fmt::Write;
+
+/// See what members have been playing, requires presence history to be enabled.
+#[lumi::command(
+    prefix_command,
+    slash_command,
+    category = "Utility - Activity",
+    guild_only,
+    subcommands("games", "timeline")
+)]
+pub async fn activity(_: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+/// Show the most played games in this server.
+#[lumi::command(prefix_command, slash_command, guild_only, user_cooldown = 15)]
+pub async fn games(
+    ctx: Context<'_>,
+    #[description = "How many days back to look."] days: Option<u16>,
+) -> Result<(), Error> {
+    if !enabled(ctx).await? {
+        return Ok(());
+    }
+
+    let days = days.unwrap_or(7).max(1);
+    let since = Utc::now() - Duration::days(days.into());
+
+    let games = ctx
+        .data()
+        .database
+        .get_top_games(ctx.guild_id().unwrap(), since, 100)
+        .await?;
+
+    if games.is_empty() {
+        ctx.say(format!(
+            "Nobody has played anything in the last {days} days."
+        ))
+        .await?;
+        return Ok(());
+    }
+
+    let mut pages = Vec::new();
+    for (i, chunk) in games.chunks(10).enumerate() {
+        let mut page = format!("**Most played games, last {days} days**\n");
+        for (j, game) in chunk.iter().enumerate() {
+            writeln!(
+                page,
+                "{}. **{}**: {} by {} members",
+                i * 10 + j + 1,
+                game.name,
+                format_secs(game.total_secs),
+                game.players
+            )
+            .unwrap();
+        }
+        pages.push(page);
+    }
+
+    paginate_text(ctx, &pages).await?;
+
+    Ok(())
+}
+
+/// Show the games and status changes of a member.
+#[lumi::command(
+    prefix_command,
+    slash_command,
+    required_permissions = "MODERATE_MEMBERS",
+    guild_only
+)]
+pub async fn timeline(
+    ctx: Context<'_>,
+    #[description = "The member to show the timeline of."] user: User,
+    #[description = "How many days back to look."] days: Option<u16>,
+) -> Result<(), Error> {
+    if !enabled(ctx).await? {
+        return Ok(());
+    }
+
+    let days = days.unwrap_or(7).max(1);
+    let since = Utc::now() - Duration::days(days.into());
+
+    let timeline = ctx
+        .data()
+        .database
+        .get_presence_timeline(ctx.guild_id().unwrap(), user.id, since)
+        .await?;
+
+    if timeline.is_empty() {
+        ctx.say(format!(
+            "There is no activity for {} in the last {days} days.",
+            user.tag()
+        ))
+        .await?;
+        return Ok(());
+    }
+
+    let mut pages = Vec::new();
+    for chunk in timeline.chunks(15) {
+        let mut page = format!("**Activity of {}**\n", user.tag());
+        for event in chunk {
+            match event {
+                PresenceEvent::Activity {
+                    name,
+                    started_at,
+                    ended_at,
+                } => {
+                    let ended = match ended_at {
+                        Some(ended_at) => format!(
+                            "for {}",
+                            format_secs((*ended_at - *started_at).num_seconds())
+                        ),
+                        None => "(ongoing)".to_owned(),
+                    };
+                    writeln!(
+                        page,
+                        "<t:{}:f> Playing **{name}** {ended}",
+                        started_at.timestamp()
+                    )
+                    .unwrap();
+                }
+                PresenceEvent::Status { status, changed_at } => {
+                    writeln!(page, "<t:{}:f> Went {status}", changed_at.timestamp()).unwrap();
+                }
+            }
+        }
+        pages.push(page);
+    }
+
+    paginate_text(ctx, &pages).await?;
+
+    Ok(())
+}
+
+async fn enabled(ctx: Context<'_>) -> Result<bool, Error> {
+    let enabled = ctx
+        .data()
+        .database
+        .get_guild_settings(ctx.guild_id().unwrap())
+        .presence_history();
+
+    if !enabled {
+        ctx.say("Presence history isn't enabled in this server.")
+            .await?;
+    }
+
+    Ok(enabled)
+}
+
+#[must_use]
+pub fn commands() -> [crate::Command; 1] {
+    [activity()]
+}
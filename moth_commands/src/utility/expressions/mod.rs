@@ -3,9 +3,10 @@ use std::{borrow::Cow, fmt};
 mod query;
 mod utils;
 
+use lumi::serenity_prelude::CreateAllowedMentions;
 use moth_core::data::database::EmoteUsageType;
 use moth_events::handlers::messages::EMOJI_REGEX;
-use query::handle_expression_query;
+use query::{handle_expression_query, is_blocked};
 
 use utils::{check_in_guild, display_expressions};
 
@@ -128,6 +129,21 @@ async fn shared(
     )
     .await?;
 
+    // blocked emotes are left out of the results, which would otherwise look like no usage.
+    if results.is_empty()
+        && is_blocked(&ctx.data().database, &expression, ctx.guild_id().unwrap()).await?
+    {
+        ctx.send(
+            lumi::CreateReply::new()
+                .content(format!(
+                    "{expression} is blocked from usage stats in this server."
+                ))
+                .allowed_mentions(CreateAllowedMentions::new()),
+        )
+        .await?;
+        return Ok(());
+    }
+
     display_expressions(ctx, &results, &expression, in_guild, msg_type).await
 }
 
@@ -2,8 +2,11 @@ use super::{Expression, ExpressionCounts};
 use crate::Error;
 use moth_core::data::database::{Database, EmoteUsageType};
 use serenity::all::GuildId;
-use sqlx::query_as;
+use sqlx::{query, query_as};
 
+/// Gets the usage of an expression per user, including usage rolled up by retention.
+///
+/// Emotes on the guild's block list are excluded.
 pub(super) async fn handle_expression_query(
     database: &Database,
     expression: &Expression<'_>,
@@ -17,13 +20,25 @@ pub(super) async fn handle_expression_query(
             let id = *id;
             query_as!(
                 ExpressionCounts,
-                "SELECT u.user_id, COUNT(eu.id) AS reaction_count
-                 FROM emote_usage eu
-                 JOIN emotes e ON eu.emote_id = e.id
+                "WITH matched AS (
+                     SELECT e.id FROM emotes e
+                     WHERE e.discord_id = $1
+                     AND NOT EXISTS (
+                         SELECT 1 FROM blocked_checked_emotes b
+                         WHERE b.guild_id = $2 AND b.emote_id = e.id
+                     )
+                 )
+                 SELECT u.user_id, SUM(eu.uses)::bigint AS reaction_count
+                 FROM (
+                     SELECT user_id, 1 AS uses FROM emote_usage
+                     WHERE guild_id = $2 AND usage_type = ANY($3)
+                     AND emote_id IN (SELECT id FROM matched)
+                     UNION ALL
+                     SELECT user_id, uses FROM emote_usage_daily
+                     WHERE guild_id = $2 AND usage_type = ANY($3)
+                     AND emote_id IN (SELECT id FROM matched)
+                 ) eu
                  JOIN users u ON eu.user_id = u.id
-                 WHERE eu.usage_type = ANY($3)
-                 AND e.discord_id = $1
-                 AND eu.guild_id = $2
                  GROUP BY u.user_id
                  ORDER BY reaction_count DESC",
                 id as i64,
@@ -36,13 +51,25 @@ pub(super) async fn handle_expression_query(
         Expression::Name(string) => {
             query_as!(
                 ExpressionCounts,
-                "SELECT u.user_id, COUNT(eu.id) AS reaction_count
-                 FROM emote_usage eu
-                 JOIN emotes e ON eu.emote_id = e.id
+                "WITH matched AS (
+                     SELECT e.id FROM emotes e
+                     WHERE e.emote_name = $1
+                     AND NOT EXISTS (
+                         SELECT 1 FROM blocked_checked_emotes b
+                         WHERE b.guild_id = $2 AND b.emote_id = e.id
+                     )
+                 )
+                 SELECT u.user_id, SUM(eu.uses)::bigint AS reaction_count
+                 FROM (
+                     SELECT user_id, 1 AS uses FROM emote_usage
+                     WHERE guild_id = $2 AND usage_type = ANY($3)
+                     AND emote_id IN (SELECT id FROM matched)
+                     UNION ALL
+                     SELECT user_id, uses FROM emote_usage_daily
+                     WHERE guild_id = $2 AND usage_type = ANY($3)
+                     AND emote_id IN (SELECT id FROM matched)
+                 ) eu
                  JOIN users u ON eu.user_id = u.id
-                 WHERE eu.usage_type = ANY($3)
-                 AND e.emote_name = $1
-                 AND eu.guild_id = $2
                  GROUP BY u.user_id
                  ORDER BY reaction_count DESC",
                 string,
@@ -55,14 +82,26 @@ pub(super) async fn handle_expression_query(
         Expression::Standard(string) => {
             query_as!(
                 ExpressionCounts,
-                "SELECT u.user_id, COUNT(eu.id) AS reaction_count
-                 FROM emote_usage eu
-                 JOIN emotes e ON eu.emote_id = e.id
+                "WITH matched AS (
+                     SELECT e.id FROM emotes e
+                     WHERE e.emote_name = $1
+                     AND e.discord_id IS NULL
+                     AND NOT EXISTS (
+                         SELECT 1 FROM blocked_checked_emotes b
+                         WHERE b.guild_id = $2 AND b.emote_id = e.id
+                     )
+                 )
+                 SELECT u.user_id, SUM(eu.uses)::bigint AS reaction_count
+                 FROM (
+                     SELECT user_id, 1 AS uses FROM emote_usage
+                     WHERE guild_id = $2 AND usage_type = ANY($3)
+                     AND emote_id IN (SELECT id FROM matched)
+                     UNION ALL
+                     SELECT user_id, uses FROM emote_usage_daily
+                     WHERE guild_id = $2 AND usage_type = ANY($3)
+                     AND emote_id IN (SELECT id FROM matched)
+                 ) eu
                  JOIN users u ON eu.user_id = u.id
-                 WHERE eu.usage_type = ANY($3)
-                 AND e.emote_name = $1
-                 AND eu.guild_id = $2
-                 AND e.discord_id IS NULL
                  GROUP BY u.user_id
                  ORDER BY reaction_count DESC",
                 string,
@@ -76,3 +115,38 @@ pub(super) async fn handle_expression_query(
 
     Ok(results)
 }
+
+/// If the expression matches an emote on the guild's block list.
+pub(super) async fn is_blocked(
+    database: &Database,
+    expression: &Expression<'_>,
+    guild_id: GuildId,
+) -> Result<bool, Error> {
+    let guild_id = database.get_guild(guild_id).await?;
+
+    let (discord_id, name, standard) = match expression {
+        Expression::Id(id) | Expression::Emote((id, _)) => (Some(*id as i64), None, false),
+        Expression::Name(name) => (None, Some(*name), false),
+        Expression::Standard(name) => (None, Some(*name), true),
+    };
+
+    let blocked = query!(
+        r#"SELECT EXISTS (
+               SELECT 1 FROM blocked_checked_emotes b
+               JOIN emotes e ON b.emote_id = e.id
+               WHERE b.guild_id = $1
+               AND ($2::bigint IS NULL OR e.discord_id = $2)
+               AND ($3::text IS NULL OR e.emote_name = $3)
+               AND (NOT $4 OR e.discord_id IS NULL)
+           ) AS "blocked!""#,
+        guild_id,
+        discord_id,
+        name,
+        standard
+    )
+    .fetch_one(&database.db)
+    .await?
+    .blocked;
+
+    Ok(blocked)
+}
@@ -22,6 +22,12 @@ pub async fn stickers(ctx: Context<'_>) -> Result<(), Error> {
         guild.stickers.clone()
     };
 
+    let blocked = ctx
+        .data()
+        .database
+        .get_blocked_stickers(ctx.guild_id().unwrap())
+        .await?;
+
     let mut pages = vec![];
     let emoji_regex = regex::Regex::new(r"[\p{Emoji}]+").unwrap();
     for sticker in stickers {
@@ -62,6 +68,9 @@ pub async fn stickers(ctx: Context<'_>) -> Result<(), Error> {
         )
         .unwrap();
         writeln!(description, "**Available:** {}", sticker.available).unwrap();
+        if blocked.contains(&sticker.id) {
+            writeln!(description, "**Excluded from statistics**").unwrap();
+        }
         embed = embed.description(description);
 
         if let Some(url) = sticker.image_url() {
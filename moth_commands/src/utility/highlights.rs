@@ -0,0 +1,222 @@
+use crate::{Context, Error, utils::toggle};
+use lumi::serenity_prelude::{CreateAllowedMentions, GenericChannelId, User};
+use moth_core::data::database::highlights::{Highlight, MAX_HIGHLIGHTS};
+use std::fmt::Write;
+
+/// Get dmed when keywords you care about are said in this server.
+#[lumi::command(
+    prefix_command,
+    slash_command,
+    category = "Utility - Highlights",
+    guild_only,
+    subcommands(
+        "add",
+        "regex",
+        "remove",
+        "list",
+        "clear",
+        "ignore_channel",
+        "ignore_user"
+    )
+)]
+pub async fn highlight(_: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+/// Get highlighted when a word or phrase is said.
+#[lumi::command(prefix_command, slash_command, guild_only)]
+pub async fn add(
+    ctx: Context<'_>,
+    #[description = "The keyword, matched as a whole word regardless of case."]
+    #[rest]
+    keyword: String,
+) -> Result<(), Error> {
+    add_highlight(ctx, keyword, false).await
+}
+
+/// Get highlighted when a regex matches.
+#[lumi::command(prefix_command, slash_command, guild_only)]
+pub async fn regex(
+    ctx: Context<'_>,
+    #[description = "The regex to match messages against."]
+    #[rest]
+    pattern: String,
+) -> Result<(), Error> {
+    add_highlight(ctx, pattern, true).await
+}
+
+/// Stop being highlighted for a keyword or regex.
+#[lumi::command(prefix_command, slash_command, guild_only)]
+pub async fn remove(
+    ctx: Context<'_>,
+    #[description = "The keyword or regex, exactly as it was added."]
+    #[rest]
+    pattern: String,
+) -> Result<(), Error> {
+    let removed = ctx
+        .data()
+        .database
+        .remove_highlight(ctx.guild_id().unwrap(), ctx.author().id, pattern.trim())
+        .await?;
+
+    if removed {
+        reply(ctx, "Removed that highlight.").await
+    } else {
+        reply(ctx, "You don't have that highlight.").await
+    }
+}
+
+/// List your highlights in this server.
+#[lumi::command(prefix_command, slash_command, guild_only)]
+pub async fn list(ctx: Context<'_>) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().unwrap();
+    let database = &ctx.data().database;
+
+    let highlights = database.get_user_highlights(guild_id, ctx.author().id);
+    if highlights.is_empty() {
+        return reply(ctx, "You don't have any highlights in this server.").await;
+    }
+
+    let mut string = String::from("**Your highlights**\n");
+    for highlight in &highlights {
+        let kind = if highlight.is_regex { " (regex)" } else { "" };
+        writeln!(string, "`{}`{kind}", highlight.pattern.replace('`', "'")).unwrap();
+    }
+
+    let settings = database.get_highlight_settings(guild_id, ctx.author().id);
+    if !settings.ignored_channels.is_empty() {
+        let channels = settings
+            .ignored_channels
+            .iter()
+            .map(|c| format!("<#{c}>"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        write!(string, "\n**Ignored channels:** {channels}").unwrap();
+    }
+    if !settings.ignored_users.is_empty() {
+        let users = settings
+            .ignored_users
+            .iter()
+            .map(|u| format!("<@{u}>"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        write!(string, "\n**Ignored users:** {users}").unwrap();
+    }
+
+    reply(ctx, string).await
+}
+
+/// Remove all of your highlights in this server.
+#[lumi::command(prefix_command, slash_command, guild_only)]
+pub async fn clear(ctx: Context<'_>) -> Result<(), Error> {
+    let removed = ctx
+        .data()
+        .database
+        .clear_highlights(ctx.guild_id().unwrap(), ctx.author().id)
+        .await?;
+
+    reply(ctx, format!("Removed {removed} highlights.")).await
+}
+
+/// Toggle never being highlighted by messages in a channel.
+#[lumi::command(prefix_command, slash_command, rename = "ignore-channel", guild_only)]
+pub async fn ignore_channel(
+    ctx: Context<'_>,
+    #[description = "The channel to ignore."] channel: GenericChannelId,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().unwrap();
+    let database = &ctx.data().database;
+
+    let mut settings = database.get_highlight_settings(guild_id, ctx.author().id);
+    let ignored = toggle(&mut settings.ignored_channels, channel);
+    database
+        .set_highlight_settings(guild_id, ctx.author().id, settings)
+        .await?;
+
+    if ignored {
+        reply(ctx, format!("You won't be highlighted in <#{channel}>.")).await
+    } else {
+        reply(
+            ctx,
+            format!("You can be highlighted in <#{channel}> again."),
+        )
+        .await
+    }
+}
+
+/// Toggle never being highlighted by a user's messages.
+#[lumi::command(prefix_command, slash_command, rename = "ignore-user", guild_only)]
+pub async fn ignore_user(
+    ctx: Context<'_>,
+    #[description = "The user to ignore."] user: User,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().unwrap();
+    let database = &ctx.data().database;
+
+    let mut settings = database.get_highlight_settings(guild_id, ctx.author().id);
+    let ignored = toggle(&mut settings.ignored_users, user.id);
+    database
+        .set_highlight_settings(guild_id, ctx.author().id, settings)
+        .await?;
+
+    if ignored {
+        reply(ctx, format!("You won't be highlighted by {}.", user.tag())).await
+    } else {
+        reply(
+            ctx,
+            format!("You can be highlighted by {} again.", user.tag()),
+        )
+        .await
+    }
+}
+
+async fn add_highlight(ctx: Context<'_>, pattern: String, is_regex: bool) -> Result<(), Error> {
+    let pattern = pattern.trim().to_owned();
+    if pattern.is_empty() || pattern.chars().count() > 100 {
+        return reply(ctx, "Highlights must be between 1 and 100 characters.").await;
+    }
+
+    let guild_id = ctx.guild_id().unwrap();
+    let database = &ctx.data().database;
+
+    if database
+        .get_user_highlights(guild_id, ctx.author().id)
+        .len()
+        >= MAX_HIGHLIGHTS
+    {
+        return reply(
+            ctx,
+            format!("You can only have {MAX_HIGHLIGHTS} highlights in a server."),
+        )
+        .await;
+    }
+
+    let highlight = match Highlight::new(ctx.author().id, pattern, is_regex) {
+        Ok(highlight) => highlight,
+        Err(e) => return reply(ctx, format!("That regex is invalid: {e}")).await,
+    };
+
+    if database.add_highlight(guild_id, highlight).await? {
+        reply(ctx, "Added that highlight.").await
+    } else {
+        reply(ctx, "You already have that highlight.").await
+    }
+}
+
+/// Highlights are personal, so replies are kept out of the channel where possible.
+async fn reply(ctx: Context<'_>, content: impl Into<String>) -> Result<(), Error> {
+    ctx.send(
+        lumi::CreateReply::new()
+            .content(content.into())
+            .ephemeral(true)
+            .allowed_mentions(CreateAllowedMentions::new()),
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[must_use]
+pub fn commands() -> [crate::Command; 1] {
+    [highlight()]
+}
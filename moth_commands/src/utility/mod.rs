@@ -1,9 +1,13 @@
+pub mod activity;
 pub mod charinfo;
 pub mod expressions;
 pub mod guild;
+pub mod highlights;
+pub mod ocr;
 pub mod random;
 pub mod roles;
 pub mod users;
+pub mod voice;
 
 #[must_use]
 pub fn commands() -> Vec<crate::Command> {
@@ -13,8 +17,12 @@ pub fn commands() -> Vec<crate::Command> {
             .chain(random::commands())
             .chain(users::commands())
             .chain(guild::commands())
+            .chain(highlights::commands())
             .chain(charinfo::commands())
             .chain(roles::commands())
+            .chain(ocr::commands())
+            .chain(voice::commands())
+            .chain(activity::commands())
             .collect()
     }
 }
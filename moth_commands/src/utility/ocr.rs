@@ -0,0 +1,175 @@
+use crate::{utils::paginate_embeds, Context, Error, PrefixContext};
+use lumi::serenity_prelude::{self as serenity, Message};
+use moth_ansi::RESET;
+use moth_core::ocr::MAX_IMAGE_SIZE;
+use std::fmt::Write;
+
+/// Discord caps embed descriptions at 4096, leave room for the codeblock.
+const MAX_TEXT_LENGTH: usize = 4000;
+/// Discord caps embed titles at 256 characters.
+const MAX_TITLE_LENGTH: usize = 256;
+
+/// Extract the text from every image on a message.
+#[lumi::command(
+    context_menu_command = "Extract text",
+    category = "Utility",
+    required_permissions = "MANAGE_MESSAGES",
+    guild_only,
+    user_cooldown = 10
+)]
+pub async fn extract_text_menu(ctx: Context<'_>, message: Message) -> Result<(), Error> {
+    extract_text_inner(ctx, &message).await
+}
+
+/// Extract the text from every image on the replied to message.
+#[lumi::command(
+    rename = "extract-text",
+    aliases("ocr"),
+    prefix_command,
+    category = "Utility",
+    required_permissions = "MANAGE_MESSAGES",
+    guild_only,
+    user_cooldown = 10
+)]
+pub async fn extract_text(ctx: PrefixContext<'_>) -> Result<(), Error> {
+    let message = ctx.msg.referenced_message.as_deref().unwrap_or(ctx.msg);
+    extract_text_inner(lumi::Context::Prefix(ctx), message).await
+}
+
+async fn extract_text_inner(ctx: Context<'_>, message: &Message) -> Result<(), Error> {
+    let images = image_sources(message);
+    if images.is_empty() {
+        ctx.say("There are no images on this message.").await?;
+        return Ok(());
+    }
+
+    ctx.defer().await?;
+
+    let mut pages = Vec::with_capacity(images.len());
+    for (mut name, source) in images {
+        let text = match download(ctx, &source).await {
+            Ok(image_data) => match ctx.data().ocr_engine.process(image_data).await {
+                Ok(text) if text.trim().is_empty() => "No text found.".to_owned(),
+                Ok(text) => highlight(ctx, text.trim()),
+                Err(e) => format!("Failed to extract text: {e}"),
+            },
+            Err(e) => format!("Failed to download image: {e}"),
+        };
+
+        if let Some((end, _)) = name.char_indices().nth(MAX_TITLE_LENGTH) {
+            name.truncate(end);
+        }
+
+        pages.push(
+            serenity::CreateEmbed::new()
+                .title(name)
+                .url(source.url())
+                .description(text),
+        );
+    }
+
+    paginate_embeds(ctx, pages).await?;
+
+    Ok(())
+}
+
+enum ImageSource<'a> {
+    Attachment(&'a serenity::Attachment),
+    Url(&'a str),
+}
+
+impl ImageSource<'_> {
+    fn url(&self) -> &str {
+        match self {
+            ImageSource::Attachment(attachment) => &attachment.url,
+            ImageSource::Url(url) => url,
+        }
+    }
+}
+
+/// Every image attachment and embed image on the message, along with a name to show.
+fn image_sources(message: &Message) -> Vec<(String, ImageSource<'_>)> {
+    let attachments = message
+        .attachments
+        .iter()
+        .filter(|a| {
+            a.content_type
+                .as_deref()
+                .is_some_and(|c| c.starts_with("image/"))
+        })
+        .map(|a| (a.filename.to_string(), ImageSource::Attachment(a)));
+
+    // link embeds to an image only have a thumbnail, the proxied copy is fetched instead of
+    // whatever url the embed points at.
+    let embeds = message.embeds.iter().enumerate().filter_map(|(i, embed)| {
+        let url = embed
+            .image
+            .as_ref()
+            .and_then(|i| i.proxy_url.as_deref())
+            .or_else(|| {
+                embed
+                    .thumbnail
+                    .as_ref()
+                    .and_then(|t| t.proxy_url.as_deref())
+            })?;
+        Some((format!("Embed {}", i + 1), ImageSource::Url(url)))
+    });
+
+    attachments.chain(embeds).collect()
+}
+
+async fn download(ctx: Context<'_>, source: &ImageSource<'_>) -> Result<Vec<u8>, Error> {
+    match source {
+        ImageSource::Attachment(attachment) => {
+            if attachment.size > MAX_IMAGE_SIZE {
+                return Err("Image is too large.".into());
+            }
+
+            Ok(attachment.download().await?)
+        }
+        ImageSource::Url(url) => {
+            let mut response = ctx.data().reqwest.get(*url).send().await?;
+            if response
+                .content_length()
+                .is_some_and(|len| len > u64::from(MAX_IMAGE_SIZE))
+            {
+                return Err("Image is too large.".into());
+            }
+
+            // the length isn't always sent, so stop reading once the body goes past the cap.
+            let mut bytes = Vec::new();
+            while let Some(chunk) = response.chunk().await? {
+                if bytes.len() + chunk.len() > MAX_IMAGE_SIZE as usize {
+                    return Err("Image is too large.".into());
+                }
+                bytes.extend_from_slice(&chunk);
+            }
+
+            Ok(bytes)
+        }
+    }
+}
+
+/// Highlights filtered words and wraps the text in an ansi codeblock.
+fn highlight(ctx: Context<'_>, text: &str) -> String {
+    let lists = ctx.data().database.get_filter_lists(ctx.guild_id());
+    let highlighted = moth_filter::filter_content(text, &lists);
+
+    let mut highlighted = highlighted.replace("```", "`\u{200b}``");
+    if highlighted.len() > MAX_TEXT_LENGTH {
+        let mut end = MAX_TEXT_LENGTH;
+        while !highlighted.is_char_boundary(end) {
+            end -= 1;
+        }
+        highlighted.truncate(end);
+        // the cut could've landed inside a highlighted word.
+        write!(highlighted, "{RESET}...").unwrap();
+    }
+
+    format!("```ansi\n{highlighted}\n```")
+}
+
+#[must_use]
+pub fn commands() -> [crate::Command; 2] {
+    [extract_text_menu(), extract_text()]
+}
@@ -111,7 +111,11 @@ pub async fn playing(ctx: Context<'_>) -> Result<(), Error> {
     required_permissions = "MANAGE_MESSAGES"
 )]
 pub async fn dm_activity_check(ctx: Context<'_>, user: User) -> Result<(), Error> {
-    if ctx.guild_id().unwrap() != 98226572468690944 {
+    let settings = ctx
+        .data()
+        .database
+        .get_guild_settings(ctx.guild_id().unwrap());
+    if !settings.dm_activity_alerts() {
         return Ok(());
     }
 
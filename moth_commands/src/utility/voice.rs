@@ -0,0 +1,166 @@
+use crate::{Context, Error, utils::format_secs};
+use chrono::{Duration, Utc};
+use lumi::serenity_prelude::{CreateAllowedMentions, GenericChannelId, User};
+use std::fmt::Write;
+
+/// How far back stats go when no window is given.
+const DEFAULT_DAYS: u16 = 30;
+
+/// See how much time is spent in voice channels.
+#[lumi::command(
+    prefix_command,
+    slash_command,
+    category = "Utility - Voice",
+    guild_only,
+    subcommands("user", "channel", "leaderboard")
+)]
+pub async fn voice(_: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+/// Show the voice time of a user, yourself by default.
+#[lumi::command(prefix_command, slash_command, guild_only)]
+pub async fn user(
+    ctx: Context<'_>,
+    #[description = "The user to check."] user: Option<User>,
+    #[description = "How many days back to look."] days: Option<u16>,
+) -> Result<(), Error> {
+    let user = user.as_ref().unwrap_or_else(|| ctx.author());
+    let days = days.unwrap_or(DEFAULT_DAYS).max(1);
+    let since = Utc::now() - Duration::days(days.into());
+
+    let channels = ctx
+        .data()
+        .database
+        .get_user_voice_stats(ctx.guild_id().unwrap(), user.id, since)
+        .await?;
+
+    if channels.is_empty() {
+        ctx.say(format!(
+            "{} hasn't been in voice in the last {days} days.",
+            user.tag()
+        ))
+        .await?;
+        return Ok(());
+    }
+
+    let (mut total, mut muted, mut deafened, mut sessions) = (0, 0, 0, 0);
+    for (_, totals) in &channels {
+        total += totals.total_secs;
+        muted += totals.muted_secs;
+        deafened += totals.deafened_secs;
+        sessions += totals.sessions;
+    }
+
+    let mut string = format!(
+        "**{}** in the last {days} days\n{} over {sessions} sessions, {} muted and {} deafened\n\n",
+        user.tag(),
+        format_secs(total),
+        format_secs(muted),
+        format_secs(deafened)
+    );
+    for (channel_id, totals) in channels.iter().take(10) {
+        writeln!(
+            string,
+            "<#{channel_id}>: {}",
+            format_secs(totals.total_secs)
+        )
+        .unwrap();
+    }
+
+    send(ctx, string).await
+}
+
+/// Show how much a voice channel is used.
+#[lumi::command(prefix_command, slash_command, guild_only)]
+pub async fn channel(
+    ctx: Context<'_>,
+    #[description = "The voice channel."] channel: GenericChannelId,
+    #[description = "How many days back to look."] days: Option<u16>,
+) -> Result<(), Error> {
+    let days = days.unwrap_or(DEFAULT_DAYS).max(1);
+    let since = Utc::now() - Duration::days(days.into());
+
+    let (totals, top) = ctx
+        .data()
+        .database
+        .get_channel_voice_stats(ctx.guild_id().unwrap(), channel.expect_channel(), since, 10)
+        .await?;
+
+    if totals.sessions == 0 {
+        ctx.say(format!(
+            "Nobody has used <#{channel}> in the last {days} days."
+        ))
+        .await?;
+        return Ok(());
+    }
+
+    let mut string = format!(
+        "<#{channel}> in the last {days} days\n{} over {} sessions\n\n",
+        format_secs(totals.total_secs),
+        totals.sessions
+    );
+    for (user_id, secs) in top {
+        writeln!(string, "<@{user_id}>: {}", format_secs(secs)).unwrap();
+    }
+
+    send(ctx, string).await
+}
+
+/// Show the members with the most voice time.
+#[lumi::command(prefix_command, slash_command, guild_only)]
+pub async fn leaderboard(
+    ctx: Context<'_>,
+    #[description = "How many days back to look."] days: Option<u16>,
+) -> Result<(), Error> {
+    let days = days.unwrap_or(DEFAULT_DAYS).max(1);
+    let since = Utc::now() - Duration::days(days.into());
+
+    let leaderboard = ctx
+        .data()
+        .database
+        .get_voice_leaderboard(ctx.guild_id().unwrap(), since, 100)
+        .await?;
+
+    if leaderboard.is_empty() {
+        ctx.say(format!("Nobody has been in voice in the last {days} days."))
+            .await?;
+        return Ok(());
+    }
+
+    let mut pages = Vec::new();
+    for (i, chunk) in leaderboard.chunks(10).enumerate() {
+        let mut page = format!("**Voice leaderboard, last {days} days**\n");
+        for (j, (user_id, secs)) in chunk.iter().enumerate() {
+            writeln!(
+                page,
+                "{}. <@{user_id}>: {}",
+                i * 10 + j + 1,
+                format_secs(*secs)
+            )
+            .unwrap();
+        }
+        pages.push(page);
+    }
+
+    let pages = pages.iter().map(String::as_str).collect::<Vec<_>>();
+    lumi::builtins::paginate(ctx, &pages).await?;
+
+    Ok(())
+}
+
+async fn send(ctx: Context<'_>, content: String) -> Result<(), Error> {
+    ctx.send(
+        lumi::CreateReply::new()
+            .content(content)
+            .allowed_mentions(CreateAllowedMentions::new()),
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[must_use]
+pub fn commands() -> [crate::Command; 1] {
+    [voice()]
+}
@@ -5,7 +5,10 @@ use std::time::Duration;
 
 pub use checks::*;
 use lumi::CreateReply;
-use moth_core::data::structs::{Context, Error};
+use moth_core::data::{
+    database::rule_action::RuleAction,
+    structs::{Context, Error},
+};
 pub use pagination::*;
 
 pub async fn handle_cooldown(remaining_cooldown: Duration, ctx: Context<'_>) -> Result<(), Error> {
@@ -108,3 +111,47 @@ pub async fn prefix_member_perms(
 
     Ok(permissions)
 }
+
+/// Formats a length of time in seconds as hours and minutes.
+#[must_use]
+pub fn format_secs(secs: i64) -> String {
+    let hours = secs / 3600;
+    let minutes = (secs % 3600) / 60;
+
+    if hours > 0 {
+        format!("{hours}h {minutes}m")
+    } else {
+        format!("{minutes}m")
+    }
+}
+
+/// Adds or removes the value, returning true if it was added.
+pub fn toggle<T: PartialEq>(values: &mut Vec<T>, value: T) -> bool {
+    if let Some(pos) = values.iter().position(|v| *v == value) {
+        values.remove(pos);
+        false
+    } else {
+        values.push(value);
+        true
+    }
+}
+
+/// What happens to a message that breaks a rule, for invite and filter rules.
+#[derive(Debug, Clone, Copy, lumi::ChoiceParameter)]
+pub enum RuleActionChoice {
+    Report,
+    Delete,
+    Warn,
+    Timeout,
+}
+
+impl From<RuleActionChoice> for RuleAction {
+    fn from(choice: RuleActionChoice) -> Self {
+        match choice {
+            RuleActionChoice::Report => RuleAction::Report,
+            RuleActionChoice::Delete => RuleAction::Delete,
+            RuleActionChoice::Warn => RuleAction::Warn,
+            RuleActionChoice::Timeout => RuleAction::Timeout,
+        }
+    }
+}
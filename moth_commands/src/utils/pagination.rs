@@ -2,8 +2,8 @@ use std::borrow::Cow;
 
 use ::serenity::all::CreateComponent;
 use lumi::serenity_prelude::{
-    self as serenity, ComponentInteractionCollector, CreateActionRow, CreateEmbedFooter,
-    CreateInteractionResponse,
+    self as serenity, ComponentInteractionCollector, CreateActionRow, CreateAllowedMentions,
+    CreateEmbedFooter, CreateInteractionResponse,
 };
 use lumi::{Context, CreateReply};
 
@@ -72,6 +72,164 @@ pub async fn presence_builder<U: Send + Sync + 'static, E>(
     Ok(())
 }
 
+/// Sends the embeds as pages that can be flipped through with buttons.
+pub async fn paginate_embeds<U: Send + Sync + 'static, E>(
+    ctx: Context<'_, U, E>,
+    pages: Vec<serenity::CreateEmbed<'_>>,
+) -> Result<(), serenity::Error> {
+    let page = |index: usize| {
+        pages[index].clone().footer(CreateEmbedFooter::new(format!(
+            "Page {}/{}",
+            index + 1,
+            pages.len()
+        )))
+    };
+
+    if pages.len() <= 1 {
+        if !pages.is_empty() {
+            ctx.send(CreateReply::default().embed(pages[0].clone()))
+                .await?;
+        }
+        return Ok(());
+    }
+
+    let ctx_id = ctx.id();
+    let prev_button_id = format!("{ctx_id}prev");
+    let next_button_id = format!("{ctx_id}next");
+
+    let sctx = ctx.serenity_context();
+    let mut current_page = 0;
+
+    let msg = ctx
+        .send(
+            CreateReply::default()
+                .embed(page(current_page))
+                .components(vec![CreateComponent::ActionRow(CreateActionRow::Buttons(
+                    Cow::Owned(vec![
+                        serenity::CreateButton::new(&prev_button_id).emoji('◀'),
+                        serenity::CreateButton::new(&next_button_id).emoji('▶'),
+                    ]),
+                ))]),
+        )
+        .await?;
+
+    while let Some(press) = ComponentInteractionCollector::new(sctx)
+        .filter(move |press| press.data.custom_id.starts_with(&ctx_id.to_string()))
+        .timeout(std::time::Duration::from_secs(180))
+        .await
+    {
+        if press.data.custom_id == next_button_id {
+            current_page += 1;
+            if current_page >= pages.len() {
+                current_page = 0;
+            }
+        } else if press.data.custom_id == prev_button_id {
+            current_page = current_page.checked_sub(1).unwrap_or(pages.len() - 1);
+        } else {
+            continue;
+        }
+
+        press
+            .create_response(
+                &sctx.http,
+                CreateInteractionResponse::UpdateMessage(
+                    serenity::CreateInteractionResponseMessage::default().embed(page(current_page)),
+                ),
+            )
+            .await?;
+    }
+
+    msg.edit(
+        ctx,
+        CreateReply::default()
+            .embed(page(current_page))
+            .components(vec![]),
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Sends text pages that can be flipped through with buttons, nothing on them pings anyone.
+pub async fn paginate_text<U: Send + Sync + 'static, E>(
+    ctx: Context<'_, U, E>,
+    pages: &[String],
+) -> Result<(), serenity::Error> {
+    let page = |index: usize| format!("{}\nPage {}/{}", pages[index], index + 1, pages.len());
+
+    if pages.len() <= 1 {
+        if !pages.is_empty() {
+            ctx.send(
+                CreateReply::default()
+                    .content(&pages[0])
+                    .allowed_mentions(CreateAllowedMentions::new()),
+            )
+            .await?;
+        }
+        return Ok(());
+    }
+
+    let ctx_id = ctx.id();
+    let prev_button_id = format!("{ctx_id}prev");
+    let next_button_id = format!("{ctx_id}next");
+
+    let sctx = ctx.serenity_context();
+    let mut current_page = 0;
+
+    let msg = ctx
+        .send(
+            CreateReply::default()
+                .content(page(current_page))
+                .allowed_mentions(CreateAllowedMentions::new())
+                .components(vec![CreateComponent::ActionRow(CreateActionRow::Buttons(
+                    Cow::Owned(vec![
+                        serenity::CreateButton::new(&prev_button_id).emoji('◀'),
+                        serenity::CreateButton::new(&next_button_id).emoji('▶'),
+                    ]),
+                ))]),
+        )
+        .await?;
+
+    while let Some(press) = ComponentInteractionCollector::new(sctx)
+        .filter(move |press| press.data.custom_id.starts_with(&ctx_id.to_string()))
+        .timeout(std::time::Duration::from_secs(180))
+        .await
+    {
+        if press.data.custom_id == next_button_id {
+            current_page += 1;
+            if current_page >= pages.len() {
+                current_page = 0;
+            }
+        } else if press.data.custom_id == prev_button_id {
+            current_page = current_page.checked_sub(1).unwrap_or(pages.len() - 1);
+        } else {
+            continue;
+        }
+
+        press
+            .create_response(
+                &sctx.http,
+                CreateInteractionResponse::UpdateMessage(
+                    serenity::CreateInteractionResponseMessage::default()
+                        .content(page(current_page))
+                        .allowed_mentions(CreateAllowedMentions::new()),
+                ),
+            )
+            .await?;
+    }
+
+    msg.edit(
+        ctx,
+        CreateReply::default()
+            .content(page(current_page))
+            .allowed_mentions(CreateAllowedMentions::new())
+            .components(vec![]),
+    )
+    .await?;
+
+    Ok(())
+}
+
 // This is split to make the code more pleasant
 fn create_presence_embed<'a>(
     current_page: usize,
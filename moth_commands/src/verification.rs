@@ -3,9 +3,9 @@ use crate::{Context, Error};
 use lumi::CreateReply;
 
 use ::serenity::all::{Colour, CreateEmbed, CreateEmbedFooter};
-use moth_core::verification::roles::{update_roles, MetadataType, LOG_CHANNEL};
+use moth_core::verification::roles::{MetadataType, update_roles, verification_guild};
 use rosu_v2::{model::GameMode, prelude::UserExtended};
-use serenity::all::{CreateAllowedMentions, CreateEmbedAuthor, CreateMessage, UserId};
+use serenity::all::{CreateAllowedMentions, CreateEmbedAuthor, CreateMessage};
 
 // TODO: osu guild only
 
@@ -58,19 +58,18 @@ pub async fn verify(ctx: Context<'_>) -> Result<(), Error> {
                 .everyone(false)
                 .all_roles(false);
 
-            let _ = moth_core::verification::roles::LOG_CHANNEL
-                .send_message(
-                    &ctx.serenity_context().http,
-                    CreateMessage::new()
-                        .content(format!(
-                            "✅ <@{}> has verified as {} (osu ID: {})",
-                            ctx.author().id,
-                            profile.username,
-                            profile.user_id
-                        ))
-                        .allowed_mentions(mentions),
-                )
-                .await;
+            log(
+                ctx,
+                CreateMessage::new()
+                    .content(format!(
+                        "✅ <@{}> has verified as {} (osu ID: {})",
+                        ctx.author().id,
+                        profile.username,
+                        profile.user_id
+                    ))
+                    .allowed_mentions(mentions),
+            )
+            .await;
         }
         Err(_) => {
             handle
@@ -123,6 +122,13 @@ pub async fn verify_force(
     Ok(())
 }
 
+/// Sends a message to the verification log channel, if one is configured.
+async fn log(ctx: Context<'_>, builder: CreateMessage<'_>) {
+    if let Some((_, Some(log_channel))) = verification_guild(&ctx.data()) {
+        let _ = log_channel.send_message(ctx.http(), builder).await;
+    }
+}
+
 async fn verify_wrapper(ctx: Context<'_>, user: &UserExtended) -> Result<(), Error> {
     // first, we check for existing verifications to this osu accaunt, and remove them.
     // this is to prevent people giving their friends roles they shouldn't have.
@@ -154,30 +160,39 @@ async fn verify_wrapper(ctx: Context<'_>, user: &UserExtended) -> Result<(), Err
         )
         .await;
 
-        let mentions = serenity::all::CreateAllowedMentions::new()
+        // the moderators of the verification guild are pinged to check for abuse.
+        let moderator_roles = verification_guild(&ctx.data())
+            .map(|(guild_id, _)| {
+                ctx.data()
+                    .database
+                    .get_guild_settings(guild_id)
+                    .moderator_roles
+                    .clone()
+            })
+            .unwrap_or_default();
+        let pings = moderator_roles
+            .iter()
+            .map(|r| format!("<@&{r}> "))
+            .collect::<String>();
+
+        let mentions = CreateAllowedMentions::new()
             .everyone(false)
-            .all_roles(false)
-            .users(vec![
-                UserId::new(101090238067113984),
-                UserId::new(291089948709486593),
-                UserId::new(158567567487795200),
-            ]);
-
-        let _ = LOG_CHANNEL
-            .send_message(
-                ctx.http(),
-                CreateMessage::new()
-                    .content(format!(
-                        "<@101090238067113984> <@291089948709486593> <@158567567487795200> \
-                         Unlinked <@{existing_user}> from {} (osu ID: {}) because they linked to \
-                         <@{}>",
-                        user.username,
-                        user.user_id,
-                        ctx.author().id,
-                    ))
-                    .allowed_mentions(mentions),
-            )
-            .await;
+            .all_users(false)
+            .roles(moderator_roles);
+
+        log(
+            ctx,
+            CreateMessage::new()
+                .content(format!(
+                    "{pings}Unlinked <@{existing_user}> from {} (osu ID: {}) because they linked \
+                     to <@{}>",
+                    user.username,
+                    user.user_id,
+                    ctx.author().id,
+                ))
+                .allowed_mentions(mentions),
+        )
+        .await;
     }
 
     let (already_verified, gamemode) = if let Some((osu_id, gamemode)) =
@@ -269,14 +284,13 @@ pub async fn update(ctx: Context<'_>) -> Result<(), Error> {
         .all_roles(false);
 
     // TODO: set it in delayqueue - or remove because like... 1 day ?
-    let _ = moth_core::verification::roles::LOG_CHANNEL
-        .send_message(
-            &ctx.serenity_context().http,
-            CreateMessage::new()
-                .content(format!("✅ updating <@{}> manually.", ctx.author().id,))
-                .allowed_mentions(mentions),
-        )
-        .await;
+    log(
+        ctx,
+        CreateMessage::new()
+            .content(format!("✅ updating <@{}> manually.", ctx.author().id,))
+            .allowed_mentions(mentions),
+    )
+    .await;
 
     // TODO: embed.
     ctx.say("Updated!").await?;
@@ -315,17 +329,16 @@ pub async fn unlink(ctx: Context<'_>) -> Result<(), Error> {
         .everyone(false)
         .all_roles(false);
 
-    let _ = moth_core::verification::roles::LOG_CHANNEL
-        .send_message(
-            &ctx.serenity_context().http,
-            CreateMessage::new()
-                .content(format!(
-                    "✅ <@{}> has unlinked their account.",
-                    ctx.author().id,
-                ))
-                .allowed_mentions(mentions),
-        )
-        .await;
+    log(
+        ctx,
+        CreateMessage::new()
+            .content(format!(
+                "✅ <@{}> has unlinked their account.",
+                ctx.author().id,
+            ))
+            .allowed_mentions(mentions),
+    )
+    .await;
 
     ctx.say("Successfully unlinked.").await?;
 
@@ -1,4 +1,4 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::HashMap;
 
 use regex::Regex;
 use serde::{Deserialize, Serialize};
@@ -7,7 +7,7 @@ use lumi::serenity_prelude::{ChannelId, GuildId};
 
 mod serialize;
 use serenity::all::{ThreadId, WebhookId};
-use serialize::{read_words_from_file, regex_patterns};
+use serialize::regex_patterns;
 
 #[derive(Clone, Debug, Deserialize, Serialize, Default)]
 pub struct MothConfig {
@@ -33,6 +33,7 @@ impl MothConfig {
             let writer = std::fs::OpenOptions::new()
                 .read(true)
                 .write(true)
+                .truncate(true)
                 .create(false)
                 .open("config/config.json");
 
@@ -52,11 +53,7 @@ impl MothConfig {
 
         let config_result = std::fs::read_to_string("config/config.json");
         if let Ok(config_file) = config_result {
-            if let Ok(mut config) = serde_json::from_str::<MothConfig>(&config_file) {
-                // Set value of unconfigurable properties.
-                config.events.badlist = read_words_from_file("config/lists/badwords.txt");
-                config.events.fixlist = read_words_from_file("config/lists/fixwords.txt");
-
+            if let Ok(config) = serde_json::from_str::<MothConfig>(&config_file) {
                 config
             } else {
                 eprintln!("Error: Failed to parse config.json. Using default configuration.");
@@ -80,6 +77,9 @@ pub struct VCStatus {
     pub announce_channel: Option<ChannelId>,
     #[serde(with = "regex_patterns")]
     pub regex: Option<Vec<Regex>>,
+    /// Clear statuses that match the blacklist.
+    #[serde(default)]
+    pub clear_blacklisted: bool,
     pub guilds: Option<Vec<GuildId>>,
 }
 
@@ -87,11 +87,12 @@ pub struct VCStatus {
 pub struct Events {
     pub no_log_channels: Option<Vec<u64>>,
     pub no_log_users: Option<Vec<u64>>,
-    #[serde(with = "regex_patterns")]
+    /// Patterns from before highlights existed, moved into the owner's highlights on startup.
+    #[serde(
+        default,
+        with = "regex_patterns",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub regex: Option<Vec<Regex>>,
-    #[serde(skip)]
-    pub badlist: HashSet<String>,
-    #[serde(skip)]
-    pub fixlist: HashSet<String>,
     pub guild_name_override: Option<HashMap<GuildId, String>>,
 }
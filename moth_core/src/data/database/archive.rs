@@ -0,0 +1,232 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serenity::all::{GenericChannelId, GuildId, Message, MessageId, UserId};
+use sqlx::query;
+
+use crate::data::structs::Error;
+
+/// The parts of an attachment worth keeping, the url stops working shortly after a delete.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ArchivedAttachment {
+    pub filename: String,
+    pub size: u32,
+    pub content_type: Option<String>,
+    pub url: String,
+}
+
+#[derive(Clone, Debug)]
+pub struct MessageRevision {
+    pub content: String,
+    pub attachments: Vec<ArchivedAttachment>,
+    pub revised_at: DateTime<Utc>,
+    /// If this is the content the message was sent with rather than an edit.
+    pub is_original: bool,
+}
+
+#[derive(Clone, Debug)]
+pub struct MessageHistory {
+    pub guild_id: Option<GuildId>,
+    pub channel_id: GenericChannelId,
+    pub user_id: UserId,
+    pub deleted_at: Option<DateTime<Utc>>,
+    /// Oldest first.
+    pub revisions: Vec<MessageRevision>,
+}
+
+/// What is known about a message from the database alone.
+#[derive(Clone, Debug)]
+pub struct StoredMessage {
+    pub message_id: MessageId,
+    pub user_id: UserId,
+    /// The latest archived content, only present in archived channels.
+    pub content: Option<String>,
+}
+
+impl super::Database {
+    /// Stores the current content of a message as a new revision, nothing is stored if it matches
+    /// the latest revision as discord sends updates for things like embeds loading.
+    pub async fn insert_message_revision(
+        &self,
+        message: &Message,
+        revised_at: DateTime<Utc>,
+        is_original: bool,
+    ) -> Result<(), Error> {
+        let message_data = self
+            .get_message(
+                message.id,
+                message.channel_id,
+                message.guild_id,
+                message.author.id,
+            )
+            .await?;
+
+        let attachments = message
+            .attachments
+            .iter()
+            .map(|a| ArchivedAttachment {
+                filename: a.filename.to_string(),
+                size: a.size,
+                content_type: a.content_type.as_ref().map(ToString::to_string),
+                url: a.url.to_string(),
+            })
+            .collect::<Vec<_>>();
+
+        query!(
+            r#"
+            INSERT INTO message_revisions (message_id, content, attachments, revised_at,
+                is_original)
+            SELECT $1, $2, $3, $4, $5
+            WHERE NOT EXISTS (
+                SELECT 1 FROM (
+                    SELECT content, attachments FROM message_revisions
+                    WHERE message_id = $1
+                    ORDER BY revised_at DESC, id DESC
+                    LIMIT 1
+                ) latest
+                WHERE latest.content = $2 AND latest.attachments = $3
+            )
+            "#,
+            message_data.id,
+            message.content.as_str(),
+            serde_json::to_value(&attachments)?,
+            revised_at,
+            is_original
+        )
+        .execute(&self.db)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Marks a stored message as deleted, messages that were never stored are ignored.
+    pub async fn mark_message_deleted(
+        &self,
+        message_id: MessageId,
+        deleted_at: DateTime<Utc>,
+    ) -> Result<(), Error> {
+        query!(
+            "UPDATE messages SET deleted_at = $2 WHERE message_id = $1 AND deleted_at IS NULL",
+            message_id.get() as i64,
+            deleted_at
+        )
+        .execute(&self.db)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Marks every stored message as deleted at once, for bulk deletes.
+    pub async fn mark_messages_deleted(
+        &self,
+        message_ids: &[MessageId],
+        deleted_at: DateTime<Utc>,
+    ) -> Result<(), Error> {
+        let message_ids = message_ids
+            .iter()
+            .map(|id| id.get() as i64)
+            .collect::<Vec<_>>();
+
+        query!(
+            "UPDATE messages SET deleted_at = $2 WHERE message_id = ANY($1) AND deleted_at IS NULL",
+            &message_ids,
+            deleted_at
+        )
+        .execute(&self.db)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Gets the author and latest archived content of each message that was stored, messages
+    /// that were never stored are left out.
+    pub async fn get_stored_messages(
+        &self,
+        message_ids: &[MessageId],
+    ) -> Result<Vec<StoredMessage>, Error> {
+        let message_ids = message_ids
+            .iter()
+            .map(|id| id.get() as i64)
+            .collect::<Vec<_>>();
+
+        let rows = query!(
+            r#"
+            SELECT m.message_id, u.user_id, r.content AS "content?"
+            FROM messages m
+            JOIN users u ON m.user_id = u.id
+            LEFT JOIN LATERAL (
+                SELECT content FROM message_revisions
+                WHERE message_id = m.id
+                ORDER BY revised_at DESC, id DESC
+                LIMIT 1
+            ) r ON TRUE
+            WHERE m.message_id = ANY($1)
+            ORDER BY m.message_id
+            "#,
+            &message_ids
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|r| StoredMessage {
+                message_id: MessageId::new(r.message_id as u64),
+                user_id: UserId::new(r.user_id as u64),
+                content: r.content,
+            })
+            .collect())
+    }
+
+    /// Gets every archived revision of a message, `None` if the message was never archived.
+    pub async fn get_message_history(
+        &self,
+        message_id: MessageId,
+    ) -> Result<Option<MessageHistory>, Error> {
+        let Some(message) = query!(
+            r#"
+            SELECT m.id, m.deleted_at, c.channel_id, g.guild_id AS "guild_id?", u.user_id
+            FROM messages m
+            JOIN channels c ON m.channel_id = c.id
+            JOIN users u ON m.user_id = u.id
+            LEFT JOIN guilds g ON m.guild_id = g.id
+            WHERE m.message_id = $1
+            "#,
+            message_id.get() as i64
+        )
+        .fetch_optional(&self.db)
+        .await?
+        else {
+            return Ok(None);
+        };
+
+        let rows = query!(
+            "SELECT content, attachments, revised_at, is_original FROM message_revisions WHERE \
+             message_id = $1 ORDER BY revised_at, id",
+            message.id
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        if rows.is_empty() {
+            return Ok(None);
+        }
+
+        let revisions = rows
+            .into_iter()
+            .map(|r| MessageRevision {
+                content: r.content,
+                attachments: serde_json::from_value(r.attachments).unwrap_or_default(),
+                revised_at: r.revised_at,
+                is_original: r.is_original,
+            })
+            .collect();
+
+        Ok(Some(MessageHistory {
+            guild_id: message.guild_id.map(|id| GuildId::new(id as u64)),
+            channel_id: GenericChannelId::new(message.channel_id as u64),
+            user_id: UserId::new(message.user_id as u64),
+            deleted_at: message.deleted_at,
+            revisions,
+        }))
+    }
+}
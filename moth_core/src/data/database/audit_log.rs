@@ -0,0 +1,128 @@
+use chrono::{DateTime, Utc};
+use serenity::all::{AuditLogEntry, AuditLogEntryId, GuildId, UserId};
+use sqlx::query;
+
+use crate::data::structs::Error;
+
+/// A stored audit log entry, without the change and option payloads.
+#[derive(Clone, Debug)]
+pub struct AuditLogRecord {
+    pub id: AuditLogEntryId,
+    pub user_id: Option<UserId>,
+    pub target_id: Option<u64>,
+    pub action_kind: u8,
+    pub reason: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Filters for searching the audit log, every filter is optional.
+#[derive(Clone, Debug, Default)]
+pub struct AuditLogSearch {
+    pub actor: Option<UserId>,
+    pub target: Option<u64>,
+    /// Inclusive range of action kinds.
+    pub action_kinds: Option<(u8, u8)>,
+    pub after: Option<DateTime<Utc>>,
+    pub before: Option<DateTime<Utc>>,
+}
+
+impl super::Database {
+    /// Stores an audit log entry, returning if the entry wasn't already stored.
+    pub async fn insert_audit_log_entry(
+        &self,
+        guild_id: GuildId,
+        entry: &AuditLogEntry,
+    ) -> Result<bool, Error> {
+        let guild_id = self.get_guild(guild_id).await?;
+        let user_id = match entry.user_id {
+            Some(user_id) => Some(self.get_user(user_id).await?.id),
+            None => None,
+        };
+
+        let change = if entry.changes.is_empty() {
+            None
+        } else {
+            Some(serde_json::to_value(&entry.changes)?)
+        };
+        let options = entry
+            .options
+            .as_ref()
+            .map(serde_json::to_value)
+            .transpose()?;
+
+        let result = query!(
+            r#"
+            INSERT INTO audit_log (audit_log_id, guild_id, target_id, action_kind, reason, user_id,
+                                   change, options, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            ON CONFLICT (audit_log_id) DO NOTHING
+            "#,
+            entry.id.get() as i64,
+            i64::from(guild_id),
+            entry.target_id.map(|t| t.get() as i64),
+            i16::from(entry.action.num()),
+            entry.reason.as_deref(),
+            user_id,
+            change,
+            options,
+            *entry.id.created_at(),
+        )
+        .execute(&self.db)
+        .await?;
+
+        Ok(result.rows_affected() != 0)
+    }
+
+    /// Searches the stored audit log of a guild, newest first.
+    pub async fn search_audit_log(
+        &self,
+        guild_id: GuildId,
+        search: &AuditLogSearch,
+        limit: i64,
+    ) -> Result<Vec<AuditLogRecord>, Error> {
+        let guild_id = self.get_guild(guild_id).await?;
+        let (min_kind, max_kind) = search.action_kinds.unwrap_or((0, u8::MAX));
+
+        let rows = query!(
+            r#"
+            SELECT a.audit_log_id, u.user_id AS "user_id?", a.target_id, a.action_kind, a.reason,
+                   a.created_at
+            FROM audit_log a
+            LEFT JOIN users u ON a.user_id = u.id
+            WHERE a.guild_id = $1
+                AND ($2::bigint IS NULL OR u.user_id = $2)
+                AND ($3::bigint IS NULL OR a.target_id = $3)
+                AND a.action_kind BETWEEN $4 AND $5
+                AND ($6::timestamptz IS NULL OR a.created_at >= $6)
+                AND ($7::timestamptz IS NULL OR a.created_at <= $7)
+            ORDER BY a.audit_log_id DESC
+            LIMIT $8
+            "#,
+            i64::from(guild_id),
+            search.actor.map(|u| u.get() as i64),
+            search.target.map(|t| t as i64),
+            i16::from(min_kind),
+            i16::from(max_kind),
+            search.after,
+            search.before,
+            limit,
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|r| {
+                let id = AuditLogEntryId::new(r.audit_log_id as u64);
+                AuditLogRecord {
+                    id,
+                    user_id: r.user_id.map(|u| UserId::new(u as u64)),
+                    target_id: r.target_id.map(|t| t as u64),
+                    action_kind: r.action_kind as u8,
+                    reason: r.reason,
+                    created_at: r.created_at.unwrap_or_else(|| *id.created_at()),
+                }
+            })
+            .collect())
+    }
+}
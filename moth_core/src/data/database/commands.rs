@@ -0,0 +1,183 @@
+use chrono::{DateTime, Utc};
+use serenity::all::{GenericChannelId, GuildId, UserId};
+use sqlx::query;
+
+use super::CommandType;
+use crate::data::structs::Error;
+
+#[derive(Clone, Debug)]
+pub struct CommandUsage {
+    pub command: String,
+    pub uses: i64,
+    pub failures: i64,
+}
+
+#[derive(Clone, Debug)]
+pub struct CommandUserUsage {
+    pub user_id: UserId,
+    pub uses: i64,
+}
+
+#[derive(Clone, Debug)]
+pub struct CommandErrorCount {
+    pub error_text: String,
+    pub count: i64,
+}
+
+impl super::Database {
+    /// Records a single command invocation, successful or not.
+    #[expect(clippy::too_many_arguments)]
+    pub async fn insert_executed_command(
+        &self,
+        user_id: UserId,
+        channel_id: GenericChannelId,
+        guild_id: Option<GuildId>,
+        command: &str,
+        command_type: CommandType,
+        executed_at: DateTime<Utc>,
+        error_text: Option<&str>,
+    ) -> Result<(), Error> {
+        let user_id = self.get_user(user_id).await?.id;
+        let (channel_id, guild_id) = self.get_channel(channel_id, guild_id).await?;
+
+        query!(
+            r#"
+            INSERT INTO executed_commands (user_id, channel_id, guild_id, command, command_type,
+                                           executed_at, executed_successfully, error_text)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            "#,
+            user_id,
+            channel_id,
+            guild_id,
+            command,
+            command_type as CommandType,
+            executed_at,
+            error_text.is_none(),
+            error_text,
+        )
+        .execute(&self.db)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Gets the usage and failure count of every command executed since `since`, most used first.
+    pub async fn get_command_usage(
+        &self,
+        since: DateTime<Utc>,
+        command: Option<&str>,
+        limit: i64,
+    ) -> Result<Vec<CommandUsage>, Error> {
+        let rows = query!(
+            r#"
+            SELECT command, SUM(uses)::bigint AS "uses!", SUM(failures)::bigint AS "failures!"
+            FROM (
+                SELECT command, 1 AS uses, (NOT executed_successfully)::int AS failures
+                FROM executed_commands
+                WHERE executed_at >= $1 AND ($2::text IS NULL OR command = $2)
+                UNION ALL
+                SELECT command, uses, failures
+                FROM executed_commands_daily
+                WHERE day >= ($1 AT TIME ZONE 'UTC')::date
+                    AND ($2::text IS NULL OR command = $2)
+            ) usage
+            GROUP BY command
+            ORDER BY 2 DESC
+            LIMIT $3
+            "#,
+            since,
+            command,
+            limit
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|r| CommandUsage {
+                command: r.command,
+                uses: r.uses,
+                failures: r.failures,
+            })
+            .collect())
+    }
+
+    /// Gets the users with the most command invocations since `since`, optionally for a single
+    /// command.
+    pub async fn get_command_user_usage(
+        &self,
+        since: DateTime<Utc>,
+        command: Option<&str>,
+        limit: i64,
+    ) -> Result<Vec<CommandUserUsage>, Error> {
+        let rows = query!(
+            r#"
+            SELECT u.user_id, SUM(e.uses)::bigint AS "uses!"
+            FROM (
+                SELECT user_id, 1 AS uses
+                FROM executed_commands
+                WHERE executed_at >= $1 AND ($2::text IS NULL OR command = $2)
+                UNION ALL
+                SELECT user_id, uses
+                FROM executed_commands_daily
+                WHERE day >= ($1 AT TIME ZONE 'UTC')::date
+                    AND ($2::text IS NULL OR command = $2)
+            ) e
+            JOIN users u ON e.user_id = u.id
+            GROUP BY u.user_id
+            ORDER BY 2 DESC
+            LIMIT $3
+            "#,
+            since,
+            command,
+            limit
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|r| CommandUserUsage {
+                user_id: UserId::new(r.user_id as u64),
+                uses: r.uses,
+            })
+            .collect())
+    }
+
+    /// Gets the most common error texts since `since`, optionally for a single command.
+    ///
+    /// Error texts are not kept once invocations are rolled up by retention.
+    pub async fn get_command_errors(
+        &self,
+        since: DateTime<Utc>,
+        command: Option<&str>,
+        limit: i64,
+    ) -> Result<Vec<CommandErrorCount>, Error> {
+        let rows = query!(
+            r#"
+            SELECT error_text AS "error_text!", COUNT(*) AS "count!"
+            FROM executed_commands
+            WHERE executed_at >= $1
+                AND NOT executed_successfully
+                AND error_text IS NOT NULL
+                AND ($2::text IS NULL OR command = $2)
+            GROUP BY error_text
+            ORDER BY 2 DESC
+            LIMIT $3
+            "#,
+            since,
+            command,
+            limit
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|r| CommandErrorCount {
+                error_text: r.error_text,
+                count: r.count,
+            })
+            .collect())
+    }
+}
@@ -0,0 +1,129 @@
+use serenity::all::{EmojiId, GuildId, ReactionType, StickerId};
+use sqlx::query;
+
+use crate::data::structs::Error;
+
+/// An emote excluded from usage statistics.
+#[derive(Clone, Debug)]
+pub struct BlockedEmote {
+    pub name: String,
+    pub discord_id: Option<EmojiId>,
+}
+
+impl super::Database {
+    /// Toggles an emote being excluded from usage statistics, returning if it is now blocked.
+    pub async fn toggle_blocked_emote(
+        &self,
+        guild_id: GuildId,
+        emote: &ReactionType,
+    ) -> Result<bool, Error> {
+        let guild_id = self.get_guild(guild_id).await?;
+        let emote_id = self.get_emote_id(emote).await?;
+
+        let removed = query!(
+            "DELETE FROM blocked_checked_emotes WHERE guild_id = $1 AND emote_id = $2",
+            guild_id,
+            emote_id
+        )
+        .execute(&self.db)
+        .await?
+        .rows_affected();
+
+        if removed != 0 {
+            return Ok(false);
+        }
+
+        query!(
+            "INSERT INTO blocked_checked_emotes (guild_id, emote_id) VALUES ($1, $2)",
+            guild_id,
+            emote_id
+        )
+        .execute(&self.db)
+        .await?;
+
+        Ok(true)
+    }
+
+    /// Toggles a sticker being excluded from usage statistics, returning if it is now blocked.
+    pub async fn toggle_blocked_sticker(
+        &self,
+        guild_id: GuildId,
+        sticker_id: StickerId,
+        sticker_name: &str,
+    ) -> Result<bool, Error> {
+        let guild_id = self.get_guild(guild_id).await?;
+        let sticker_id = sticker_id.get() as i64;
+
+        let removed = query!(
+            "DELETE FROM blocked_checked_stickers WHERE guild_id = $1 AND sticker_id = $2",
+            guild_id,
+            sticker_id
+        )
+        .execute(&self.db)
+        .await?
+        .rows_affected();
+
+        if removed != 0 {
+            return Ok(false);
+        }
+
+        let mut tx = self.db.begin().await?;
+
+        query!(
+            "INSERT INTO stickers (sticker_id, sticker_name) VALUES ($1, $2) ON CONFLICT \
+             (sticker_id) DO NOTHING",
+            sticker_id,
+            sticker_name
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        query!(
+            "INSERT INTO blocked_checked_stickers (guild_id, sticker_id) VALUES ($1, $2)",
+            guild_id,
+            sticker_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(true)
+    }
+
+    pub async fn get_blocked_emotes(&self, guild_id: GuildId) -> Result<Vec<BlockedEmote>, Error> {
+        let guild_id = self.get_guild(guild_id).await?;
+
+        let rows = query!(
+            "SELECT e.emote_name, e.discord_id FROM blocked_checked_emotes b JOIN emotes e ON \
+             b.emote_id = e.id WHERE b.guild_id = $1",
+            guild_id
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|r| BlockedEmote {
+                name: r.emote_name,
+                discord_id: r.discord_id.map(|id| EmojiId::new(id as u64)),
+            })
+            .collect())
+    }
+
+    pub async fn get_blocked_stickers(&self, guild_id: GuildId) -> Result<Vec<StickerId>, Error> {
+        let guild_id = self.get_guild(guild_id).await?;
+
+        let rows = query!(
+            "SELECT sticker_id FROM blocked_checked_stickers WHERE guild_id = $1",
+            guild_id
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|r| StickerId::new(r.sticker_id as u64))
+            .collect())
+    }
+}
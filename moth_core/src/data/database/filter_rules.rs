@@ -0,0 +1,292 @@
+use std::time::Duration;
+
+use chrono::Utc;
+use dashmap::DashMap;
+use serenity::all::{GenericChannelId, GuildId, MessageId, RoleId, UserId};
+use sqlx::query;
+
+use super::rule_action::RuleAction;
+use crate::data::structs::Error;
+
+/// A harsher action for members that keep getting filtered.
+#[derive(Debug, Clone, Copy)]
+pub struct EscalationStep {
+    /// Strikes within the window, including the current one, needed for this step.
+    pub strikes: u16,
+    pub action: RuleAction,
+    pub timeout_for: Duration,
+}
+
+#[derive(Debug, Clone)]
+pub struct FilterRules {
+    /// `None` ignores content at that level.
+    pub moderate_action: Option<RuleAction>,
+    pub severe_action: Option<RuleAction>,
+    /// Words from the badlist, regardless of how severe they are.
+    pub badlist_action: Option<RuleAction>,
+    pub timeout_for: Duration,
+    /// Falls back to the staff channel.
+    pub report_channel: Option<GenericChannelId>,
+    pub exempt_roles: Vec<RoleId>,
+    pub exempt_channels: Vec<GenericChannelId>,
+    /// Sorted by strikes, fewest first.
+    pub escalation: Vec<EscalationStep>,
+    pub strike_window: Duration,
+}
+
+impl Default for FilterRules {
+    fn default() -> Self {
+        Self {
+            moderate_action: None,
+            severe_action: None,
+            badlist_action: None,
+            timeout_for: Duration::from_secs(60 * 10),
+            report_channel: None,
+            exempt_roles: Vec::new(),
+            exempt_channels: Vec::new(),
+            escalation: Vec::new(),
+            strike_window: Duration::from_secs(60 * 60 * 24),
+        }
+    }
+}
+
+impl FilterRules {
+    /// If the rules don't apply to a member with these roles in this channel.
+    #[must_use]
+    pub fn is_exempt(&self, roles: &[RoleId], channel_id: GenericChannelId) -> bool {
+        self.exempt_channels.contains(&channel_id)
+            || roles.iter().any(|r| self.exempt_roles.contains(r))
+    }
+
+    /// The step a member with this many strikes is on, if any.
+    #[must_use]
+    pub fn escalation_for(&self, strikes: u16) -> Option<&EscalationStep> {
+        self.escalation.iter().rev().find(|s| s.strikes <= strikes)
+    }
+}
+
+pub(super) async fn load_filter_rules(
+    db: &sqlx::PgPool,
+) -> Result<DashMap<GuildId, FilterRules>, Error> {
+    let rows = query!(
+        r#"
+        SELECT g.guild_id, r.moderate_action AS "moderate_action: RuleAction",
+               r.severe_action AS "severe_action: RuleAction",
+               r.badlist_action AS "badlist_action: RuleAction", r.timeout_mins,
+               r.report_channel, r.exempt_roles, r.exempt_channels, r.strike_window_hours
+        FROM filter_rules r
+        JOIN guilds g ON r.guild_id = g.id
+        "#
+    )
+    .fetch_all(db)
+    .await?;
+
+    let steps = query!(
+        r#"
+        SELECT g.guild_id, e.strikes, e.action AS "action: RuleAction", e.timeout_mins
+        FROM filter_escalation e
+        JOIN guilds g ON e.guild_id = g.id
+        ORDER BY e.strikes
+        "#
+    )
+    .fetch_all(db)
+    .await?;
+
+    let map = DashMap::with_capacity(rows.len());
+    for row in rows {
+        let guild_id = GuildId::new(row.guild_id as u64);
+        let rules = FilterRules {
+            moderate_action: row.moderate_action,
+            severe_action: row.severe_action,
+            badlist_action: row.badlist_action,
+            timeout_for: Duration::from_secs(row.timeout_mins as u64 * 60),
+            report_channel: row.report_channel.map(|c| GenericChannelId::new(c as u64)),
+            exempt_roles: row
+                .exempt_roles
+                .into_iter()
+                .map(|r| RoleId::new(r as u64))
+                .collect(),
+            exempt_channels: row
+                .exempt_channels
+                .into_iter()
+                .map(|c| GenericChannelId::new(c as u64))
+                .collect(),
+            escalation: steps
+                .iter()
+                .filter(|s| s.guild_id == row.guild_id)
+                .map(|s| EscalationStep {
+                    strikes: s.strikes as u16,
+                    action: s.action,
+                    timeout_for: Duration::from_secs(s.timeout_mins as u64 * 60),
+                })
+                .collect(),
+            strike_window: Duration::from_secs(row.strike_window_hours as u64 * 60 * 60),
+        };
+
+        map.insert(guild_id, rules);
+    }
+
+    Ok(map)
+}
+
+impl super::Database {
+    /// Gets the filter rules of a guild, `None` if filtered messages are only printed there.
+    #[must_use]
+    pub fn get_filter_rules(&self, guild_id: GuildId) -> Option<FilterRules> {
+        self.filter_rules.get(&guild_id).map(|r| r.value().clone())
+    }
+
+    /// Sets the filter rules of a guild, `None` stops acting on filtered messages.
+    pub async fn set_filter_rules(
+        &self,
+        guild_id: GuildId,
+        rules: Option<FilterRules>,
+    ) -> Result<(), Error> {
+        let id = self.get_guild(guild_id).await?;
+
+        let Some(mut rules) = rules else {
+            query!("DELETE FROM filter_rules WHERE guild_id = $1", id)
+                .execute(&self.db)
+                .await?;
+
+            self.filter_rules.remove(&guild_id);
+            return Ok(());
+        };
+
+        rules.escalation.sort_by_key(|s| s.strikes);
+
+        let exempt_roles = rules
+            .exempt_roles
+            .iter()
+            .map(|r| r.get() as i64)
+            .collect::<Vec<_>>();
+        let exempt_channels = rules
+            .exempt_channels
+            .iter()
+            .map(|c| c.get() as i64)
+            .collect::<Vec<_>>();
+
+        let mut transaction = self.db.begin().await?;
+
+        query!(
+            r#"
+            INSERT INTO filter_rules (guild_id, moderate_action, severe_action, badlist_action,
+                                      timeout_mins, report_channel, exempt_roles,
+                                      exempt_channels, strike_window_hours)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            ON CONFLICT (guild_id) DO UPDATE SET
+                moderate_action = EXCLUDED.moderate_action,
+                severe_action = EXCLUDED.severe_action,
+                badlist_action = EXCLUDED.badlist_action,
+                timeout_mins = EXCLUDED.timeout_mins,
+                report_channel = EXCLUDED.report_channel,
+                exempt_roles = EXCLUDED.exempt_roles,
+                exempt_channels = EXCLUDED.exempt_channels,
+                strike_window_hours = EXCLUDED.strike_window_hours
+            "#,
+            id,
+            rules.moderate_action as Option<RuleAction>,
+            rules.severe_action as Option<RuleAction>,
+            rules.badlist_action as Option<RuleAction>,
+            (rules.timeout_for.as_secs() / 60) as i32,
+            rules.report_channel.map(|c| c.get() as i64),
+            &exempt_roles,
+            &exempt_channels,
+            (rules.strike_window.as_secs() / 60 / 60) as i32
+        )
+        .execute(&mut *transaction)
+        .await?;
+
+        query!("DELETE FROM filter_escalation WHERE guild_id = $1", id)
+            .execute(&mut *transaction)
+            .await?;
+
+        for step in &rules.escalation {
+            query!(
+                "INSERT INTO filter_escalation (guild_id, strikes, action, timeout_mins) VALUES \
+                 ($1, $2, $3, $4)",
+                id,
+                step.strikes as i16,
+                step.action as RuleAction,
+                (step.timeout_for.as_secs() / 60) as i32
+            )
+            .execute(&mut *transaction)
+            .await?;
+        }
+
+        transaction.commit().await?;
+
+        self.filter_rules.insert(guild_id, rules);
+
+        Ok(())
+    }
+
+    /// Gives a member a strike for a message, returning how many they have within the window
+    /// including this one. A message that already earned a strike doesn't add another.
+    pub async fn add_filter_strike(
+        &self,
+        guild_id: GuildId,
+        user_id: UserId,
+        message_id: MessageId,
+        window: Duration,
+    ) -> Result<u16, Error> {
+        let id = self.get_guild(guild_id).await?;
+        let user = self.get_user(user_id).await?.id;
+        let cutoff = Utc::now() - chrono::TimeDelta::from_std(window).unwrap_or_default();
+
+        let mut transaction = self.db.begin().await?;
+
+        query!(
+            "INSERT INTO filter_strikes (guild_id, user_id, message_id) VALUES ($1, $2, $3) ON \
+             CONFLICT (message_id) DO NOTHING",
+            id,
+            user,
+            message_id.get() as i64
+        )
+        .execute(&mut *transaction)
+        .await?;
+
+        // nothing reads past the window, so there's no point keeping them.
+        query!(
+            "DELETE FROM filter_strikes WHERE guild_id = $1 AND user_id = $2 AND struck_at < $3",
+            id,
+            user,
+            cutoff
+        )
+        .execute(&mut *transaction)
+        .await?;
+
+        let count = query!(
+            r#"SELECT COUNT(*) AS "count!" FROM filter_strikes WHERE guild_id = $1 AND user_id = $2"#,
+            id,
+            user
+        )
+        .fetch_one(&mut *transaction)
+        .await?
+        .count;
+
+        transaction.commit().await?;
+
+        Ok(count.min(i64::from(u16::MAX)) as u16)
+    }
+
+    /// Removes every strike a member has, returning how many were removed.
+    pub async fn clear_filter_strikes(
+        &self,
+        guild_id: GuildId,
+        user_id: UserId,
+    ) -> Result<u64, Error> {
+        let id = self.get_guild(guild_id).await?;
+        let user = self.get_user(user_id).await?.id;
+
+        let result = query!(
+            "DELETE FROM filter_strikes WHERE guild_id = $1 AND user_id = $2",
+            id,
+            user
+        )
+        .execute(&self.db)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+}
@@ -0,0 +1,264 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{Arc, atomic::Ordering},
+};
+
+use dashmap::DashMap;
+use moth_filter::{Type, WordLists};
+use serenity::all::GuildId;
+use sqlx::query;
+
+use crate::data::structs::Error;
+
+/// What a word on the filter lists does.
+#[derive(Debug, Clone, Copy, sqlx::Type, PartialEq, Eq)]
+#[sqlx(type_name = "filterwordkind", rename_all = "lowercase")]
+pub enum FilterWordKind {
+    /// Flags any word containing it.
+    Bad,
+    /// Stops words containing it from being flagged by the badlist.
+    Fix,
+    /// Never flagged by rustrict, use fix words to stop the badlist.
+    Safe,
+    Profane,
+    Offensive,
+    Sexual,
+    Mean,
+}
+
+impl FilterWordKind {
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Bad => "bad",
+            Self::Fix => "fix",
+            Self::Safe => "safe",
+            Self::Profane => "profane",
+            Self::Offensive => "offensive",
+            Self::Sexual => "sexual",
+            Self::Mean => "mean",
+        }
+    }
+
+    /// If the kind tells rustrict what a word is, and so needs a severity.
+    #[must_use]
+    pub fn is_category(self) -> bool {
+        !matches!(self, Self::Bad | Self::Fix | Self::Safe)
+    }
+}
+
+#[derive(Debug, Clone, Copy, sqlx::Type, PartialEq, Eq)]
+#[sqlx(type_name = "filterwordseverity", rename_all = "lowercase")]
+pub enum FilterWordSeverity {
+    Mild,
+    Moderate,
+    Severe,
+}
+
+#[derive(Debug, Clone)]
+pub struct FilterWord {
+    pub word: String,
+    pub kind: FilterWordKind,
+    /// Only set for categories.
+    pub severity: Option<FilterWordSeverity>,
+}
+
+impl FilterWord {
+    /// What rustrict should see the word as, `None` for the substring lists.
+    #[must_use]
+    pub fn override_type(&self) -> Option<Type> {
+        let category = match self.kind {
+            FilterWordKind::Bad | FilterWordKind::Fix => return None,
+            FilterWordKind::Safe => return Some(Type::SAFE),
+            FilterWordKind::Profane => Type::PROFANE,
+            FilterWordKind::Offensive => Type::OFFENSIVE,
+            FilterWordKind::Sexual => Type::SEXUAL,
+            FilterWordKind::Mean => Type::MEAN,
+        };
+
+        let severity = match self.severity.unwrap_or(FilterWordSeverity::Moderate) {
+            FilterWordSeverity::Mild => Type::MILD,
+            FilterWordSeverity::Moderate => Type::MODERATE,
+            FilterWordSeverity::Severe => Type::SEVERE,
+        };
+
+        Some(category & severity)
+    }
+}
+
+pub(super) async fn load_filter_words(
+    db: &sqlx::PgPool,
+) -> Result<DashMap<Option<GuildId>, Vec<FilterWord>>, Error> {
+    let rows = query!(
+        r#"
+        SELECT g.guild_id AS "guild_id?", w.word, w.kind AS "kind: FilterWordKind",
+               w.severity AS "severity: FilterWordSeverity"
+        FROM filter_words w
+        LEFT JOIN guilds g ON w.guild_id = g.id
+        ORDER BY w.word
+        "#
+    )
+    .fetch_all(db)
+    .await?;
+
+    let map: DashMap<Option<GuildId>, Vec<FilterWord>> = DashMap::new();
+    for row in rows {
+        map.entry(row.guild_id.map(|g| GuildId::new(g as u64)))
+            .or_default()
+            .push(FilterWord {
+                word: row.word,
+                kind: row.kind,
+                severity: row.severity,
+            });
+    }
+
+    Ok(map)
+}
+
+impl super::Database {
+    /// The lists content in a guild is checked against, `None` for the global lists alone.
+    #[must_use]
+    pub fn get_filter_lists(&self, guild_id: Option<GuildId>) -> Arc<WordLists> {
+        if let Some(lists) = self.filter_lists.get(&guild_id) {
+            return lists.clone();
+        }
+
+        let generation = self.filter_lists_generation.load(Ordering::SeqCst);
+
+        // guild words come last so they replace the global kind of a word.
+        let mut words = HashMap::new();
+        let guild_words = guild_id.map(|g| self.get_filter_words(Some(g)));
+        for word in self
+            .get_filter_words(None)
+            .into_iter()
+            .chain(guild_words.into_iter().flatten())
+        {
+            words.insert(word.word.clone(), word);
+        }
+
+        let mut badlist = HashSet::new();
+        let mut fixlist = HashSet::new();
+        let mut overrides = Vec::new();
+        for (text, word) in words {
+            match word.kind {
+                FilterWordKind::Bad => {
+                    badlist.insert(text);
+                }
+                FilterWordKind::Fix => {
+                    fixlist.insert(text);
+                }
+                // safe words are whole words to rustrict, the fixlist would let any word
+                // containing them through.
+                _ => overrides.extend(word.override_type().map(|kind| (text, kind))),
+            }
+        }
+
+        let lists = Arc::new(WordLists::new(badlist, fixlist, overrides));
+        // checked under the entry lock, so an invalidation either comes first and skips the
+        // insert or comes after and removes it.
+        let entry = self.filter_lists.entry(guild_id);
+        if self.filter_lists_generation.load(Ordering::SeqCst) == generation {
+            entry.insert(lists.clone());
+        }
+        lists
+    }
+
+    /// The words of a guild, `None` for the words that apply to every guild.
+    #[must_use]
+    pub fn get_filter_words(&self, guild_id: Option<GuildId>) -> Vec<FilterWord> {
+        self.filter_words
+            .get(&guild_id)
+            .map(|w| w.value().clone())
+            .unwrap_or_default()
+    }
+
+    /// Adds a word to the lists, replacing whatever it was before.
+    pub async fn add_filter_word(
+        &self,
+        guild_id: Option<GuildId>,
+        word: &str,
+        kind: FilterWordKind,
+        severity: Option<FilterWordSeverity>,
+    ) -> Result<(), Error> {
+        let word = word.trim().to_lowercase();
+        let severity = if kind.is_category() {
+            Some(severity.unwrap_or(FilterWordSeverity::Moderate))
+        } else {
+            None
+        };
+
+        let id = match guild_id {
+            Some(guild_id) => Some(self.get_guild(guild_id).await?),
+            None => None,
+        };
+
+        query!(
+            r#"
+            INSERT INTO filter_words (guild_id, word, kind, severity)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT ((COALESCE(guild_id, 0)), word) DO UPDATE SET
+                kind = EXCLUDED.kind,
+                severity = EXCLUDED.severity
+            "#,
+            id,
+            word,
+            kind as FilterWordKind,
+            severity as Option<FilterWordSeverity>
+        )
+        .execute(&self.db)
+        .await?;
+
+        {
+            let mut words = self.filter_words.entry(guild_id).or_default();
+            words.retain(|w| w.word != word);
+            words.push(FilterWord {
+                word,
+                kind,
+                severity,
+            });
+        }
+
+        self.invalidate_filter_lists(guild_id);
+
+        Ok(())
+    }
+
+    /// Removes a word from the lists, returning false if it wasn't on them.
+    pub async fn remove_filter_word(
+        &self,
+        guild_id: Option<GuildId>,
+        word: &str,
+    ) -> Result<bool, Error> {
+        let word = word.trim().to_lowercase();
+        let id = match guild_id {
+            Some(guild_id) => Some(self.get_guild(guild_id).await?),
+            None => None,
+        };
+
+        let result = query!(
+            "DELETE FROM filter_words WHERE guild_id IS NOT DISTINCT FROM $1 AND word = $2",
+            id,
+            word
+        )
+        .execute(&self.db)
+        .await?;
+
+        if let Some(mut words) = self.filter_words.get_mut(&guild_id) {
+            words.retain(|w| w.word != word);
+        }
+
+        self.invalidate_filter_lists(guild_id);
+
+        Ok(result.rows_affected() != 0)
+    }
+
+    fn invalidate_filter_lists(&self, guild_id: Option<GuildId>) {
+        self.filter_lists_generation.fetch_add(1, Ordering::SeqCst);
+        if guild_id.is_some() {
+            self.filter_lists.remove(&guild_id);
+        } else {
+            // every guild's lists are built on top of the global ones.
+            self.filter_lists.clear();
+        }
+    }
+}
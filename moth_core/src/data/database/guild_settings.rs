@@ -0,0 +1,325 @@
+use std::{sync::Arc, time::Duration};
+
+use dashmap::DashMap;
+use serenity::all::{ChannelId, GenericChannelId, GuildId, RoleId};
+
+use crate::{data::structs::Error, verification::roles::verification_roles};
+
+/// Per guild configuration, anything that was once hardcoded to a single guild lives here.
+#[bool_to_bitflags::bool_to_bitflags]
+#[derive(Default, Clone, Debug)]
+pub struct GuildSettings {
+    /// General staff channel, used for raid alerts, dm activity and automod alerts.
+    pub staff_channel: Option<GenericChannelId>,
+    /// The channel discord automod posts flagged content into.
+    pub automod_channel: Option<GenericChannelId>,
+    /// The automod rule flagged statuses are alerted for, any rule when unset.
+    pub automod_rule: Option<String>,
+    pub vc_status_alert_channel: Option<GenericChannelId>,
+    pub invite_log_channel: Option<GenericChannelId>,
+    pub mass_delete_channel: Option<GenericChannelId>,
+    pub verification_log_channel: Option<GenericChannelId>,
+    /// The channel users join to create a private vc.
+    pub private_vc_channel: Option<ChannelId>,
+    pub super_poop_role: Option<RoleId>,
+    pub super_poop_channel: Option<GenericChannelId>,
+    /// The forum modmail threads are opened in.
+    pub modmail_channel: Option<ChannelId>,
+    pub moderator_roles: Vec<RoleId>,
+    /// Roles reapplied to members that leave and rejoin, on top of the super poop role.
+    pub persistable_roles: Vec<RoleId>,
+    /// Channels that have the content and revisions of their messages stored.
+    pub archived_channels: Vec<GenericChannelId>,
+    /// Overrides for [`MassDeleteThresholds`], see [`GuildSettings::mass_delete_thresholds`].
+    pub mass_delete_check_heat: Option<u16>,
+    pub mass_delete_max_heat: Option<u16>,
+    pub mass_delete_decay_secs: Option<u16>,
+    pub raid_alerts: bool,
+    pub dm_activity_alerts: bool,
+    /// Alert staff when discord automod flags a voice channel status.
+    pub automod_alerts: bool,
+    pub invite_logging: bool,
+    pub mass_delete_alerts: bool,
+    pub private_vcs: bool,
+    pub verification: bool,
+    pub auto_super_poop: bool,
+    pub role_persistence: bool,
+    /// Run image attachments through OCR and filter the text like a normal message.
+    pub ocr_scanning: bool,
+    pub modmail: bool,
+    /// Record game activity and status changes of members.
+    pub presence_history: bool,
+}
+
+/// How deletes heat up a guild before the mass deletion check kicks in.
+#[derive(Clone, Copy, Debug)]
+pub struct MassDeleteThresholds {
+    /// Deletes are only attributed to a user once the heat reaches this.
+    pub check_heat: u16,
+    /// Heat stops increasing past this.
+    pub max_heat: u16,
+    /// How long a guild goes without deletes before the heat drops by one.
+    pub decay_after: Duration,
+}
+
+impl GuildSettings {
+    /// If the role should be given back to members that leave and rejoin, verification roles are
+    /// only given back to members that still have an osu account linked.
+    #[must_use]
+    pub fn is_persistable(&self, role_id: RoleId, linked: bool) -> bool {
+        self.persistable_roles.contains(&role_id)
+            || self.super_poop_role == Some(role_id)
+            || (linked && self.verification() && verification_roles().any(|r| r == role_id))
+    }
+
+    /// If the content of messages in this channel should be archived.
+    #[must_use]
+    pub fn is_archived(&self, channel_id: GenericChannelId) -> bool {
+        self.archived_channels.contains(&channel_id)
+    }
+
+    /// The configured thresholds, with defaults for anything that hasn't been set.
+    #[must_use]
+    pub fn mass_delete_thresholds(&self) -> MassDeleteThresholds {
+        let check_heat = self.mass_delete_check_heat.unwrap_or(3);
+
+        MassDeleteThresholds {
+            check_heat,
+            max_heat: self.mass_delete_max_heat.unwrap_or(5).max(check_heat),
+            decay_after: Duration::from_secs(self.mass_delete_decay_secs.unwrap_or(5).into()),
+        }
+    }
+}
+
+fn channel(id: Option<i64>) -> Option<GenericChannelId> {
+    id.map(|id| GenericChannelId::new(id as u64))
+}
+
+pub(super) async fn load_guild_settings(
+    db: &sqlx::PgPool,
+) -> Result<DashMap<GuildId, Arc<GuildSettings>>, Error> {
+    let rows = sqlx::query!(
+        r#"
+        SELECT g.guild_id, s.staff_channel, s.automod_channel, s.vc_status_alert_channel,
+               s.invite_log_channel, s.mass_delete_channel, s.verification_log_channel,
+               s.private_vc_channel, s.super_poop_role, s.super_poop_channel, s.moderator_roles,
+               s.raid_alerts, s.dm_activity_alerts, s.automod_alerts, s.invite_logging,
+               s.mass_delete_alerts, s.private_vcs, s.verification, s.auto_super_poop,
+               s.persistable_roles, s.role_persistence, s.ocr_scanning, s.archived_channels,
+               s.mass_delete_check_heat, s.mass_delete_max_heat, s.mass_delete_decay_secs,
+               s.modmail_channel, s.modmail, s.presence_history, s.automod_rule
+        FROM guild_settings s
+        JOIN guilds g ON s.guild_id = g.id
+        "#
+    )
+    .fetch_all(db)
+    .await?;
+
+    let map = DashMap::with_capacity(rows.len());
+    for row in rows {
+        let mut settings = GuildSettings {
+            staff_channel: channel(row.staff_channel),
+            automod_channel: channel(row.automod_channel),
+            automod_rule: row.automod_rule,
+            vc_status_alert_channel: channel(row.vc_status_alert_channel),
+            invite_log_channel: channel(row.invite_log_channel),
+            mass_delete_channel: channel(row.mass_delete_channel),
+            verification_log_channel: channel(row.verification_log_channel),
+            private_vc_channel: row.private_vc_channel.map(|c| ChannelId::new(c as u64)),
+            super_poop_role: row.super_poop_role.map(|r| RoleId::new(r as u64)),
+            super_poop_channel: channel(row.super_poop_channel),
+            modmail_channel: row.modmail_channel.map(|c| ChannelId::new(c as u64)),
+            moderator_roles: row
+                .moderator_roles
+                .into_iter()
+                .map(|r| RoleId::new(r as u64))
+                .collect(),
+            persistable_roles: row
+                .persistable_roles
+                .into_iter()
+                .map(|r| RoleId::new(r as u64))
+                .collect(),
+            archived_channels: row
+                .archived_channels
+                .into_iter()
+                .map(|c| GenericChannelId::new(c as u64))
+                .collect(),
+            mass_delete_check_heat: row.mass_delete_check_heat.map(|h| h as u16),
+            mass_delete_max_heat: row.mass_delete_max_heat.map(|h| h as u16),
+            mass_delete_decay_secs: row.mass_delete_decay_secs.map(|s| s as u16),
+            ..Default::default()
+        };
+
+        settings.set_raid_alerts(row.raid_alerts);
+        settings.set_dm_activity_alerts(row.dm_activity_alerts);
+        settings.set_automod_alerts(row.automod_alerts);
+        settings.set_invite_logging(row.invite_logging);
+        settings.set_mass_delete_alerts(row.mass_delete_alerts);
+        settings.set_private_vcs(row.private_vcs);
+        settings.set_verification(row.verification);
+        settings.set_auto_super_poop(row.auto_super_poop);
+        settings.set_role_persistence(row.role_persistence);
+        settings.set_ocr_scanning(row.ocr_scanning);
+        settings.set_modmail(row.modmail);
+        settings.set_presence_history(row.presence_history);
+
+        map.insert(GuildId::new(row.guild_id as u64), Arc::new(settings));
+    }
+
+    Ok(map)
+}
+
+impl super::Database {
+    /// Gets the settings for a guild, every setting is loaded on startup so a miss means the guild
+    /// has never been configured and the defaults are cached instead.
+    #[must_use]
+    pub fn get_guild_settings(&self, guild_id: GuildId) -> Arc<GuildSettings> {
+        if let Some(settings) = self.guild_settings.get(&guild_id) {
+            return settings.clone();
+        }
+
+        let settings = Arc::new(GuildSettings::default());
+        self.guild_settings.insert(guild_id, settings.clone());
+        settings
+    }
+
+    /// Finds the first guild matching the predicate, used for features that can only run in one
+    /// guild at a time like verification.
+    pub fn find_guild_settings(
+        &self,
+        predicate: impl Fn(&GuildSettings) -> bool,
+    ) -> Option<(GuildId, Arc<GuildSettings>)> {
+        self.guild_settings
+            .iter()
+            .find(|entry| predicate(entry.value()))
+            .map(|entry| (*entry.key(), entry.value().clone()))
+    }
+
+    /// Every guild matching the predicate, for features that dms need to route to a guild.
+    pub fn filter_guild_settings(
+        &self,
+        predicate: impl Fn(&GuildSettings) -> bool,
+    ) -> Vec<(GuildId, Arc<GuildSettings>)> {
+        self.guild_settings
+            .iter()
+            .filter(|entry| predicate(entry.value()))
+            .map(|entry| (*entry.key(), entry.value().clone()))
+            .collect()
+    }
+
+    /// Writes the settings for a guild, replacing any existing settings.
+    pub async fn set_guild_settings(
+        &self,
+        guild_id: GuildId,
+        settings: GuildSettings,
+    ) -> Result<(), Error> {
+        let id = self.get_guild(guild_id).await?;
+
+        let channel = |c: Option<GenericChannelId>| c.map(|c| c.get() as i64);
+        let moderator_roles = settings
+            .moderator_roles
+            .iter()
+            .map(|r| r.get() as i64)
+            .collect::<Vec<_>>();
+        let persistable_roles = settings
+            .persistable_roles
+            .iter()
+            .map(|r| r.get() as i64)
+            .collect::<Vec<_>>();
+        let archived_channels = settings
+            .archived_channels
+            .iter()
+            .map(|c| c.get() as i64)
+            .collect::<Vec<_>>();
+
+        sqlx::query!(
+            r#"
+            INSERT INTO guild_settings (
+                guild_id, staff_channel, automod_channel, vc_status_alert_channel,
+                invite_log_channel, mass_delete_channel, verification_log_channel,
+                private_vc_channel, super_poop_role, super_poop_channel, moderator_roles,
+                raid_alerts, dm_activity_alerts, automod_alerts, invite_logging,
+                mass_delete_alerts, private_vcs, verification, auto_super_poop,
+                persistable_roles, role_persistence, ocr_scanning, archived_channels,
+                mass_delete_check_heat, mass_delete_max_heat, mass_delete_decay_secs,
+                modmail_channel, modmail, presence_history, automod_rule
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17,
+                    $18, $19, $20, $21, $22, $23, $24, $25, $26, $27, $28, $29, $30)
+            ON CONFLICT (guild_id) DO UPDATE SET
+                staff_channel = EXCLUDED.staff_channel,
+                automod_channel = EXCLUDED.automod_channel,
+                vc_status_alert_channel = EXCLUDED.vc_status_alert_channel,
+                invite_log_channel = EXCLUDED.invite_log_channel,
+                mass_delete_channel = EXCLUDED.mass_delete_channel,
+                verification_log_channel = EXCLUDED.verification_log_channel,
+                private_vc_channel = EXCLUDED.private_vc_channel,
+                super_poop_role = EXCLUDED.super_poop_role,
+                super_poop_channel = EXCLUDED.super_poop_channel,
+                moderator_roles = EXCLUDED.moderator_roles,
+                raid_alerts = EXCLUDED.raid_alerts,
+                dm_activity_alerts = EXCLUDED.dm_activity_alerts,
+                automod_alerts = EXCLUDED.automod_alerts,
+                invite_logging = EXCLUDED.invite_logging,
+                mass_delete_alerts = EXCLUDED.mass_delete_alerts,
+                private_vcs = EXCLUDED.private_vcs,
+                verification = EXCLUDED.verification,
+                auto_super_poop = EXCLUDED.auto_super_poop,
+                persistable_roles = EXCLUDED.persistable_roles,
+                role_persistence = EXCLUDED.role_persistence,
+                ocr_scanning = EXCLUDED.ocr_scanning,
+                archived_channels = EXCLUDED.archived_channels,
+                mass_delete_check_heat = EXCLUDED.mass_delete_check_heat,
+                mass_delete_max_heat = EXCLUDED.mass_delete_max_heat,
+                mass_delete_decay_secs = EXCLUDED.mass_delete_decay_secs,
+                modmail_channel = EXCLUDED.modmail_channel,
+                modmail = EXCLUDED.modmail,
+                presence_history = EXCLUDED.presence_history,
+                automod_rule = EXCLUDED.automod_rule
+            "#,
+            id,
+            channel(settings.staff_channel),
+            channel(settings.automod_channel),
+            channel(settings.vc_status_alert_channel),
+            channel(settings.invite_log_channel),
+            channel(settings.mass_delete_channel),
+            channel(settings.verification_log_channel),
+            settings.private_vc_channel.map(|c| c.get() as i64),
+            settings.super_poop_role.map(|r| r.get() as i64),
+            channel(settings.super_poop_channel),
+            &moderator_roles,
+            settings.raid_alerts(),
+            settings.dm_activity_alerts(),
+            settings.automod_alerts(),
+            settings.invite_logging(),
+            settings.mass_delete_alerts(),
+            settings.private_vcs(),
+            settings.verification(),
+            settings.auto_super_poop(),
+            &persistable_roles,
+            settings.role_persistence(),
+            settings.ocr_scanning(),
+            &archived_channels,
+            // the columns are SMALLINT, anything past that is far beyond a useful value anyway.
+            settings
+                .mass_delete_check_heat
+                .map(|h| i16::try_from(h).unwrap_or(i16::MAX)),
+            settings
+                .mass_delete_max_heat
+                .map(|h| i16::try_from(h).unwrap_or(i16::MAX)),
+            settings
+                .mass_delete_decay_secs
+                .map(|s| i16::try_from(s).unwrap_or(i16::MAX)),
+            settings.modmail_channel.map(|c| c.get() as i64),
+            settings.modmail(),
+            settings.presence_history(),
+            settings.automod_rule.as_deref(),
+        )
+        .execute(&self.db)
+        .await?;
+
+        self.guild_settings.insert(guild_id, Arc::new(settings));
+
+        Ok(())
+    }
+}
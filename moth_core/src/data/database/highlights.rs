@@ -0,0 +1,284 @@
+use dashmap::DashMap;
+use regex::{Regex, RegexBuilder};
+use serenity::all::{GenericChannelId, GuildId, UserId};
+use sqlx::query;
+
+use crate::data::structs::Error;
+
+/// How many highlights a member can have in a single guild.
+pub const MAX_HIGHLIGHTS: usize = 20;
+
+/// Keeps user provided regexes from being expensive to compile or run.
+const REGEX_SIZE_LIMIT: usize = 1 << 16;
+
+#[derive(Debug, Clone)]
+pub struct Highlight {
+    pub user_id: UserId,
+    /// The keyword or regex as the user wrote it.
+    pub pattern: String,
+    pub is_regex: bool,
+    matcher: Regex,
+}
+
+impl Highlight {
+    /// Builds a highlight, keywords match case insensitively and only on whole words.
+    pub fn new(user_id: UserId, pattern: String, is_regex: bool) -> Result<Self, regex::Error> {
+        let source = if is_regex {
+            pattern.clone()
+        } else {
+            // \b doesn't work for keywords that start or end with punctuation.
+            format!(r"(?i)(?:^|\W){}(?:\W|$)", regex::escape(&pattern))
+        };
+
+        let matcher = RegexBuilder::new(&source)
+            .size_limit(REGEX_SIZE_LIMIT)
+            .build()?;
+
+        Ok(Self {
+            user_id,
+            pattern,
+            is_regex,
+            matcher,
+        })
+    }
+
+    #[must_use]
+    pub fn is_match(&self, content: &str) -> bool {
+        self.matcher.is_match(content)
+    }
+}
+
+/// What a member doesn't want to be highlighted by within a guild.
+#[derive(Debug, Clone, Default)]
+pub struct HighlightSettings {
+    pub ignored_channels: Vec<GenericChannelId>,
+    pub ignored_users: Vec<UserId>,
+}
+
+pub(super) async fn load_highlights(
+    db: &sqlx::PgPool,
+) -> Result<DashMap<GuildId, Vec<Highlight>>, Error> {
+    let rows = query!(
+        r#"
+        SELECT g.guild_id, u.user_id, h.pattern, h.is_regex
+        FROM highlights h
+        JOIN guilds g ON h.guild_id = g.id
+        JOIN users u ON h.user_id = u.id
+        "#
+    )
+    .fetch_all(db)
+    .await?;
+
+    let map: DashMap<GuildId, Vec<Highlight>> = DashMap::new();
+    for row in rows {
+        let user_id = UserId::new(row.user_id as u64);
+        match Highlight::new(user_id, row.pattern, row.is_regex) {
+            Ok(highlight) => map
+                .entry(GuildId::new(row.guild_id as u64))
+                .or_default()
+                .push(highlight),
+            Err(e) => println!("Skipping highlight for {user_id}: {e}"),
+        }
+    }
+
+    Ok(map)
+}
+
+pub(super) async fn load_highlight_settings(
+    db: &sqlx::PgPool,
+) -> Result<DashMap<(GuildId, UserId), HighlightSettings>, Error> {
+    let rows = query!(
+        r#"
+        SELECT g.guild_id, u.user_id, s.ignored_channels, s.ignored_users
+        FROM highlight_settings s
+        JOIN guilds g ON s.guild_id = g.id
+        JOIN users u ON s.user_id = u.id
+        "#
+    )
+    .fetch_all(db)
+    .await?;
+
+    let map = DashMap::with_capacity(rows.len());
+    for row in rows {
+        let settings = HighlightSettings {
+            ignored_channels: row
+                .ignored_channels
+                .into_iter()
+                .map(|c| GenericChannelId::new(c as u64))
+                .collect(),
+            ignored_users: row
+                .ignored_users
+                .into_iter()
+                .map(|u| UserId::new(u as u64))
+                .collect(),
+        };
+
+        map.insert(
+            (
+                GuildId::new(row.guild_id as u64),
+                UserId::new(row.user_id as u64),
+            ),
+            settings,
+        );
+    }
+
+    Ok(map)
+}
+
+impl super::Database {
+    /// Every member with a highlight matching the content, along with the first pattern that
+    /// matched for them.
+    #[must_use]
+    pub fn matching_highlights(&self, guild_id: GuildId, content: &str) -> Vec<(UserId, String)> {
+        let Some(highlights) = self.highlights.get(&guild_id) else {
+            return Vec::new();
+        };
+
+        let mut matches: Vec<(UserId, String)> = Vec::new();
+        for highlight in highlights.iter() {
+            if matches.iter().any(|(u, _)| *u == highlight.user_id) {
+                continue;
+            }
+
+            if highlight.is_match(content) {
+                matches.push((highlight.user_id, highlight.pattern.clone()));
+            }
+        }
+
+        matches
+    }
+
+    /// The highlights a member has in a guild.
+    #[must_use]
+    pub fn get_user_highlights(&self, guild_id: GuildId, user_id: UserId) -> Vec<Highlight> {
+        self.highlights
+            .get(&guild_id)
+            .map(|h| h.iter().filter(|h| h.user_id == user_id).cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Adds a highlight, returning false if the member already has that pattern.
+    pub async fn add_highlight(
+        &self,
+        guild_id: GuildId,
+        highlight: Highlight,
+    ) -> Result<bool, Error> {
+        let id = self.get_guild(guild_id).await?;
+        let user_id = self.get_user(highlight.user_id).await?.id;
+
+        let result = query!(
+            "INSERT INTO highlights (guild_id, user_id, pattern, is_regex) VALUES ($1, $2, $3, \
+             $4) ON CONFLICT DO NOTHING",
+            id,
+            user_id,
+            highlight.pattern,
+            highlight.is_regex
+        )
+        .execute(&self.db)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Ok(false);
+        }
+
+        self.highlights.entry(guild_id).or_default().push(highlight);
+
+        Ok(true)
+    }
+
+    /// Removes a highlight, returning false if the member didn't have it.
+    pub async fn remove_highlight(
+        &self,
+        guild_id: GuildId,
+        user_id: UserId,
+        pattern: &str,
+    ) -> Result<bool, Error> {
+        let id = self.get_guild(guild_id).await?;
+        let user = self.get_user(user_id).await?.id;
+
+        let result = query!(
+            "DELETE FROM highlights WHERE guild_id = $1 AND user_id = $2 AND pattern = $3",
+            id,
+            user,
+            pattern
+        )
+        .execute(&self.db)
+        .await?;
+
+        if let Some(mut highlights) = self.highlights.get_mut(&guild_id) {
+            highlights.retain(|h| h.user_id != user_id || h.pattern != pattern);
+        }
+
+        Ok(result.rows_affected() != 0)
+    }
+
+    /// Removes every highlight a member has in a guild, returning how many were removed.
+    pub async fn clear_highlights(&self, guild_id: GuildId, user_id: UserId) -> Result<u64, Error> {
+        let id = self.get_guild(guild_id).await?;
+        let user = self.get_user(user_id).await?.id;
+
+        let result = query!(
+            "DELETE FROM highlights WHERE guild_id = $1 AND user_id = $2",
+            id,
+            user
+        )
+        .execute(&self.db)
+        .await?;
+
+        if let Some(mut highlights) = self.highlights.get_mut(&guild_id) {
+            highlights.retain(|h| h.user_id != user_id);
+        }
+
+        Ok(result.rows_affected())
+    }
+
+    #[must_use]
+    pub fn get_highlight_settings(&self, guild_id: GuildId, user_id: UserId) -> HighlightSettings {
+        self.highlight_settings
+            .get(&(guild_id, user_id))
+            .map(|s| s.value().clone())
+            .unwrap_or_default()
+    }
+
+    pub async fn set_highlight_settings(
+        &self,
+        guild_id: GuildId,
+        user_id: UserId,
+        settings: HighlightSettings,
+    ) -> Result<(), Error> {
+        let id = self.get_guild(guild_id).await?;
+        let user = self.get_user(user_id).await?.id;
+
+        let ignored_channels = settings
+            .ignored_channels
+            .iter()
+            .map(|c| c.get() as i64)
+            .collect::<Vec<_>>();
+        let ignored_users = settings
+            .ignored_users
+            .iter()
+            .map(|u| u.get() as i64)
+            .collect::<Vec<_>>();
+
+        query!(
+            r#"
+            INSERT INTO highlight_settings (guild_id, user_id, ignored_channels, ignored_users)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (guild_id, user_id) DO UPDATE SET
+                ignored_channels = EXCLUDED.ignored_channels,
+                ignored_users = EXCLUDED.ignored_users
+            "#,
+            id,
+            user,
+            &ignored_channels,
+            &ignored_users
+        )
+        .execute(&self.db)
+        .await?;
+
+        self.highlight_settings
+            .insert((guild_id, user_id), settings);
+
+        Ok(())
+    }
+}
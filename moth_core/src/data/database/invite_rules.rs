@@ -0,0 +1,168 @@
+use std::time::Duration;
+
+use dashmap::DashMap;
+use serenity::all::{GenericChannelId, GuildId, RoleId};
+use sqlx::query;
+
+use super::rule_action::RuleAction;
+use crate::data::structs::Error;
+
+#[derive(Debug, Clone)]
+pub struct InviteRules {
+    pub action: RuleAction,
+    /// When empty, every guild that isn't denied is allowed.
+    pub allowed_guilds: Vec<GuildId>,
+    pub denied_guilds: Vec<GuildId>,
+    pub exempt_roles: Vec<RoleId>,
+    pub exempt_channels: Vec<GenericChannelId>,
+    pub timeout_for: Duration,
+}
+
+impl Default for InviteRules {
+    fn default() -> Self {
+        Self {
+            action: RuleAction::Report,
+            allowed_guilds: Vec::new(),
+            denied_guilds: Vec::new(),
+            exempt_roles: Vec::new(),
+            exempt_channels: Vec::new(),
+            timeout_for: Duration::from_secs(60 * 10),
+        }
+    }
+}
+
+impl InviteRules {
+    /// If an invite to `target` can be posted in `guild_id`, `None` being an invite discord
+    /// doesn't know about.
+    #[must_use]
+    pub fn is_allowed(&self, guild_id: GuildId, target: Option<GuildId>) -> bool {
+        let Some(target) = target else {
+            // nothing is known about it, so it can only be allowed if everything is.
+            return self.allowed_guilds.is_empty();
+        };
+
+        if target == guild_id {
+            return true;
+        }
+
+        if self.denied_guilds.contains(&target) {
+            return false;
+        }
+
+        self.allowed_guilds.is_empty() || self.allowed_guilds.contains(&target)
+    }
+
+    /// If the rules don't apply to a member with these roles in this channel.
+    #[must_use]
+    pub fn is_exempt(&self, roles: &[RoleId], channel_id: GenericChannelId) -> bool {
+        self.exempt_channels.contains(&channel_id)
+            || roles.iter().any(|r| self.exempt_roles.contains(r))
+    }
+}
+
+pub(super) async fn load_invite_rules(
+    db: &sqlx::PgPool,
+) -> Result<DashMap<GuildId, InviteRules>, Error> {
+    let rows = query!(
+        r#"
+        SELECT g.guild_id, r.action AS "action: RuleAction", r.allowed_guilds, r.denied_guilds,
+               r.exempt_roles, r.exempt_channels, r.timeout_mins
+        FROM invite_rules r
+        JOIN guilds g ON r.guild_id = g.id
+        "#
+    )
+    .fetch_all(db)
+    .await?;
+
+    let guilds = |ids: Vec<i64>| ids.into_iter().map(|id| GuildId::new(id as u64)).collect();
+
+    let map = DashMap::with_capacity(rows.len());
+    for row in rows {
+        let rules = InviteRules {
+            action: row.action,
+            allowed_guilds: guilds(row.allowed_guilds),
+            denied_guilds: guilds(row.denied_guilds),
+            exempt_roles: row
+                .exempt_roles
+                .into_iter()
+                .map(|r| RoleId::new(r as u64))
+                .collect(),
+            exempt_channels: row
+                .exempt_channels
+                .into_iter()
+                .map(|c| GenericChannelId::new(c as u64))
+                .collect(),
+            timeout_for: Duration::from_secs(row.timeout_mins as u64 * 60),
+        };
+
+        map.insert(GuildId::new(row.guild_id as u64), rules);
+    }
+
+    Ok(map)
+}
+
+impl super::Database {
+    /// Gets the invite rules of a guild, `None` if invites aren't enforced there.
+    #[must_use]
+    pub fn get_invite_rules(&self, guild_id: GuildId) -> Option<InviteRules> {
+        self.invite_rules.get(&guild_id).map(|r| r.value().clone())
+    }
+
+    /// Sets the invite rules of a guild, `None` stops enforcing invites.
+    pub async fn set_invite_rules(
+        &self,
+        guild_id: GuildId,
+        rules: Option<InviteRules>,
+    ) -> Result<(), Error> {
+        let id = self.get_guild(guild_id).await?;
+
+        let Some(rules) = rules else {
+            query!("DELETE FROM invite_rules WHERE guild_id = $1", id)
+                .execute(&self.db)
+                .await?;
+
+            self.invite_rules.remove(&guild_id);
+            return Ok(());
+        };
+
+        let guilds = |ids: &[GuildId]| ids.iter().map(|g| g.get() as i64).collect::<Vec<_>>();
+        let exempt_roles = rules
+            .exempt_roles
+            .iter()
+            .map(|r| r.get() as i64)
+            .collect::<Vec<_>>();
+        let exempt_channels = rules
+            .exempt_channels
+            .iter()
+            .map(|c| c.get() as i64)
+            .collect::<Vec<_>>();
+
+        query!(
+            r#"
+            INSERT INTO invite_rules (guild_id, action, allowed_guilds, denied_guilds,
+                                      exempt_roles, exempt_channels, timeout_mins)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            ON CONFLICT (guild_id) DO UPDATE SET
+                action = EXCLUDED.action,
+                allowed_guilds = EXCLUDED.allowed_guilds,
+                denied_guilds = EXCLUDED.denied_guilds,
+                exempt_roles = EXCLUDED.exempt_roles,
+                exempt_channels = EXCLUDED.exempt_channels,
+                timeout_mins = EXCLUDED.timeout_mins
+            "#,
+            id,
+            rules.action as RuleAction,
+            &guilds(&rules.allowed_guilds),
+            &guilds(&rules.denied_guilds),
+            &exempt_roles,
+            &exempt_channels,
+            (rules.timeout_for.as_secs() / 60) as i32
+        )
+        .execute(&self.db)
+        .await?;
+
+        self.invite_rules.insert(guild_id, rules);
+
+        Ok(())
+    }
+}
@@ -6,22 +6,44 @@ use dashmap::{DashMap, DashSet};
 use parking_lot::Mutex;
 use serenity::all::UserId;
 use sqlx::{Executor, PgPool, postgres::PgPoolOptions, query};
-use std::{collections::HashSet, env, sync::Arc, time::Duration};
-
-use crate::data::{
-    database::private_vcs::task::GUILD,
-    structs::{DmActivity, Error},
+use std::{
+    collections::HashSet,
+    env,
+    sync::{Arc, atomic::AtomicU64},
+    time::Duration,
 };
 
+use crate::data::structs::{DmActivity, Error};
+
 use lumi::serenity_prelude as serenity;
 
+pub mod archive;
+pub mod audit_log;
 pub mod auth;
+pub mod commands;
+pub mod expressions;
+pub mod filter_rules;
+pub mod filter_words;
+pub mod guild_settings;
+pub mod highlights;
+pub mod invite_rules;
+pub mod mod_log;
+pub mod modmail;
+pub mod names;
+pub mod presence;
 pub mod starboard;
+pub mod vc_status;
+pub mod voice_sessions;
 pub mod wrappers;
+pub use guild_settings::{GuildSettings, MassDeleteThresholds};
 pub use starboard::*;
 pub use wrappers::*;
 pub mod private_vcs;
+pub mod raid;
 pub mod reactions;
+pub mod retention;
+pub mod role_snapshots;
+pub mod rule_action;
 
 pub async fn init_data() -> Database {
     let database_url =
@@ -42,26 +64,71 @@ pub async fn init_data() -> Database {
         .await
         .expect("Unable to apply migrations!");
 
+    presence::close_stale_activities(&database)
+        .await
+        .expect("should be setup correctly.");
+
     let cache = mini_moka::sync::CacheBuilder::new(500)
         .time_to_idle(Duration::from_secs(3600))
         .build();
 
+    let message_cache = mini_moka::sync::CacheBuilder::new(10_000)
+        .time_to_idle(Duration::from_secs(3600))
+        .build();
+
     let private_vc_cache = mini_moka::sync::CacheBuilder::new(500)
         .time_to_idle(Duration::from_secs(1800))
         .build();
 
+    let member_roles_cache = mini_moka::sync::CacheBuilder::new(10_000)
+        .time_to_live(Duration::from_secs(60 * 60 * 24))
+        .build();
+
     Database {
         starboard: Mutex::new(
             StarboardHandler::new(&database)
                 .await
                 .expect("should be setup correctly."),
         ),
+        guild_settings: guild_settings::load_guild_settings(&database)
+            .await
+            .expect("should be setup correctly."),
+        mod_log_routes: mod_log::load_mod_log_routes(&database)
+            .await
+            .expect("should be setup correctly."),
+        raid_configs: raid::load_raid_configs(&database)
+            .await
+            .expect("should be setup correctly."),
+        invite_rules: invite_rules::load_invite_rules(&database)
+            .await
+            .expect("should be setup correctly."),
+        filter_rules: filter_rules::load_filter_rules(&database)
+            .await
+            .expect("should be setup correctly."),
+        filter_words: filter_words::load_filter_words(&database)
+            .await
+            .expect("should be setup correctly."),
+        filter_lists: DashMap::new(),
+        filter_lists_generation: AtomicU64::new(0),
+        modmail_threads: modmail::load_modmail_threads(&database)
+            .await
+            .expect("should be setup correctly."),
+        highlights: highlights::load_highlights(&database)
+            .await
+            .expect("should be setup correctly."),
+        highlight_settings: highlights::load_highlight_settings(&database)
+            .await
+            .expect("should be setup correctly."),
+        voice_sessions: DashMap::new(),
+        presence_activities: DashMap::new(),
+        presence_statuses: DashMap::new(),
         db: database,
         users: cache,
         dm_activity: DashMap::new(),
+        member_roles: member_roles_cache,
         channels: DashMap::new(),
         guilds: DashMap::new(),
-        messages: DashMap::new(),
+        messages: message_cache,
         emotes: DashMap::new(),
         private_vc: private_vc_cache,
     }
@@ -75,20 +142,49 @@ pub enum EmoteUsageType {
     Reaction,
 }
 
+/// How a command was invoked.
+#[derive(Debug, Clone, Copy, sqlx::Type, PartialEq, Eq, Hash)]
+#[sqlx(type_name = "commandtype", rename_all = "lowercase")]
+pub enum CommandType {
+    Prefix,
+    Application,
+}
+
 pub struct Database {
     pub db: PgPool,
     users: mini_moka::sync::Cache<UserId, Arc<ApplicationUser>>,
     // TODO: simplify this store.
     guilds: DashMap<serenity::GuildId, i32>,
     channels: DashMap<serenity::GenericChannelId, (i32, Option<i32>)>,
-    messages: DashMap<serenity::MessageId, MessageData>,
+    messages: mini_moka::sync::Cache<serenity::MessageId, MessageData>,
     emotes: DashMap<serenity::ReactionType, i32>,
+    guild_settings: DashMap<serenity::GuildId, Arc<GuildSettings>>,
+    mod_log_routes: DashMap<(serenity::GuildId, mod_log::ModLogEvent), mod_log::ModLogTarget>,
+    raid_configs: DashMap<serenity::GuildId, raid::RaidConfig>,
+    invite_rules: DashMap<serenity::GuildId, invite_rules::InviteRules>,
+    filter_rules: DashMap<serenity::GuildId, filter_rules::FilterRules>,
+    /// Filter words by guild, `None` holds the words every guild uses.
+    filter_words: DashMap<Option<serenity::GuildId>, Vec<filter_words::FilterWord>>,
+    /// Compiled lists built from the filter words, dropped whenever the words change.
+    filter_lists: DashMap<Option<serenity::GuildId>, Arc<moth_filter::WordLists>>,
+    /// Bumped whenever the words change, lists built from older words aren't cached.
+    filter_lists_generation: AtomicU64,
+    /// Open modmail threads, keyed by the forum post.
+    modmail_threads: DashMap<serenity::GenericChannelId, modmail::ModmailThread>,
+    highlights: DashMap<serenity::GuildId, Vec<highlights::Highlight>>,
+    highlight_settings: DashMap<(serenity::GuildId, UserId), highlights::HighlightSettings>,
+    /// Voice sessions that are currently ongoing.
+    voice_sessions: DashMap<(serenity::GuildId, UserId), voice_sessions::OpenVoiceSession>,
+    presence_activities: DashMap<(serenity::GuildId, UserId), Vec<presence::OpenActivity>>,
+    presence_statuses: DashMap<(serenity::GuildId, UserId), presence::LastStatus>,
     // TODO: return privacy after i decide to make proper getters and setters
     pub private_vc: mini_moka::sync::Cache<ChannelId, Option<Arc<PrivateVc>>>,
     pub starboard: Mutex<starboard::StarboardHandler>,
     // TODO: try and keep private and rewrite them when i eventually redo my users and starboard part.
     /// Runtime caches for dm activity.
     pub(crate) dm_activity: DashMap<UserId, DmActivity>,
+    /// Roles seen in member updates for members missing from the cache, kept for a day.
+    member_roles: mini_moka::sync::Cache<(serenity::GuildId, UserId), Vec<RoleId>>,
 }
 
 pub struct Transaction<'a> {
@@ -285,27 +381,19 @@ impl Database {
         user_id: UserId,
     ) -> Result<MessageData, sqlx::Error> {
         if let Some(message) = self.messages.get(&message_id) {
-            return Ok(*message);
+            return Ok(message);
         }
 
         let (channel_id, guild_id) = self.get_channel(channel_id, guild_id).await?;
         let user_id = self.get_user(user_id).await?.id;
 
+        // the no-op update always returns the row, a select can miss one inserted concurrently.
         let row = query!(
             r#"
-            WITH input_rows(message_id, channel_id, user_id, guild_id) AS (
-                VALUES ($1::bigint, $2::int, $3::int, $4::int)
-            ),
-            ins AS (
-                INSERT INTO messages (message_id, channel_id, user_id, guild_id)
-                SELECT message_id, channel_id, user_id, guild_id FROM input_rows
-                ON CONFLICT (message_id) DO NOTHING
-                RETURNING id
-            )
-            SELECT id AS "id!" FROM ins
-            UNION ALL
-            SELECT m.id AS "id!" FROM messages m
-            JOIN input_rows i USING (message_id);
+            INSERT INTO messages (message_id, channel_id, user_id, guild_id)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (message_id) DO UPDATE SET message_id = EXCLUDED.message_id
+            RETURNING id
             "#,
             message_id.get() as i64,
             channel_id,
@@ -332,7 +420,7 @@ impl Database {
         message_id: MessageId,
     ) -> Result<MessageData, sqlx::Error> {
         if let Some(message) = self.messages.get(&message_id) {
-            return Ok(*message);
+            return Ok(message);
         }
 
         let message_data = sqlx::query_as!(
@@ -349,7 +437,23 @@ impl Database {
     }
 
     pub fn get_cached_message(&self, message_id: &MessageId) -> Option<MessageData> {
-        self.messages.get(message_id).map(|v| *v)
+        self.messages.get(message_id)
+    }
+
+    /// Gets who sent a message, if the message was ever stored.
+    pub async fn get_message_author(
+        &self,
+        message_id: MessageId,
+    ) -> Result<Option<UserId>, sqlx::Error> {
+        let row = query!(
+            "SELECT u.user_id FROM messages m JOIN users u ON m.user_id = u.id \
+             WHERE m.message_id = $1",
+            message_id.get() as i64
+        )
+        .fetch_optional(&self.db)
+        .await?;
+
+        Ok(row.map(|r| UserId::new(r.user_id as u64)))
     }
 
     /// Gets the guild from the database, or inserts it if it doesn't exist, returning the inner id value.
@@ -630,7 +734,9 @@ impl Database {
         })
     }
 
-    pub async fn get_all_private_vcs(&self) -> Result<Vec<(ChannelId, Arc<PrivateVc>)>, Error> {
+    pub async fn get_all_private_vcs(
+        &self,
+    ) -> Result<Vec<(GuildId, ChannelId, Arc<PrivateVc>)>, Error> {
         let records = sqlx::query!(
             r#"
             SELECT
@@ -640,10 +746,12 @@ impl Database {
                 COALESCE(alu.allowlist_users, ARRAY[]::BIGINT[]) AS allowlist_users,
                 COALESCE(tru.trusted_users, ARRAY[]::BIGINT[]) AS trusted_users,
                 COALESCE(dnu.denylist_users, ARRAY[]::BIGINT[]) AS denylist_users,
-                msg.message_id AS "message_id?"
+                msg.message_id AS "message_id?",
+                g.guild_id
             FROM private_vcs pv
             JOIN users owner ON pv.owner_id = owner.id
             JOIN channels ch ON pv.channel_id = ch.id
+            JOIN guilds g ON ch.guild_id = g.id
             LEFT JOIN messages msg ON pv.message_id = msg.id
 
             LEFT JOIN LATERAL (
@@ -674,6 +782,7 @@ impl Database {
         let mut result = Vec::with_capacity(records.len());
 
         for row in records {
+            let guild_id = GuildId::new(row.guild_id as u64);
             let channel_id = ChannelId::new(row.channel_id as u64);
             let owner_id = UserId::new(row.owner_id as u64);
 
@@ -714,7 +823,7 @@ impl Database {
             });
 
             self.private_vc.insert(channel_id, Some(private_vc.clone()));
-            result.push((channel_id, private_vc));
+            result.push((guild_id, channel_id, private_vc));
         }
 
         Ok(result)
@@ -786,7 +895,7 @@ impl Database {
         // Insert main private_vcs record with hydrated channel_id and owner_id
 
         let message_id = if let Some(message_id) = message_id {
-            self.get_message(message_id, channel_id.widen(), Some(GUILD), bot_id)
+            self.get_message(message_id, channel_id.widen(), guild_id, bot_id)
                 .await
                 .ok()
                 .map(|m| m.id)
@@ -0,0 +1,141 @@
+use dashmap::DashMap;
+use serenity::all::{GenericChannelId, GuildId};
+use sqlx::query;
+
+use crate::data::structs::Error;
+
+/// The kinds of events that can be routed to a mod log.
+#[derive(Debug, Clone, Copy, sqlx::Type, PartialEq, Eq, Hash)]
+#[sqlx(type_name = "modlogevent", rename_all = "snake_case")]
+pub enum ModLogEvent {
+    MessageEdit,
+    MessageDelete,
+    MemberJoin,
+    MemberLeave,
+    MemberUpdate,
+    RoleUpdate,
+    ChannelUpdate,
+}
+
+impl ModLogEvent {
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            ModLogEvent::MessageEdit => "Message edits",
+            ModLogEvent::MessageDelete => "Message deletes",
+            ModLogEvent::MemberJoin => "Member joins",
+            ModLogEvent::MemberLeave => "Member leaves",
+            ModLogEvent::MemberUpdate => "Member updates",
+            ModLogEvent::RoleUpdate => "Role updates",
+            ModLogEvent::ChannelUpdate => "Channel updates",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ModLogTarget {
+    Channel(GenericChannelId),
+    /// The full url of the webhook, including the token.
+    Webhook(String),
+}
+
+pub(super) async fn load_mod_log_routes(
+    db: &sqlx::PgPool,
+) -> Result<DashMap<(GuildId, ModLogEvent), ModLogTarget>, Error> {
+    let rows = query!(
+        r#"
+        SELECT g.guild_id, r.event AS "event: ModLogEvent", r.channel_id, r.webhook_url
+        FROM mod_log_routes r
+        JOIN guilds g ON r.guild_id = g.id
+        "#
+    )
+    .fetch_all(db)
+    .await?;
+
+    let map = DashMap::with_capacity(rows.len());
+    for row in rows {
+        let target = match (row.channel_id, row.webhook_url) {
+            (Some(channel_id), _) => {
+                ModLogTarget::Channel(GenericChannelId::new(channel_id as u64))
+            }
+            (None, Some(url)) => ModLogTarget::Webhook(url),
+            (None, None) => continue,
+        };
+
+        map.insert((GuildId::new(row.guild_id as u64), row.event), target);
+    }
+
+    Ok(map)
+}
+
+impl super::Database {
+    /// Gets where an event should be logged to in a guild, if anywhere.
+    #[must_use]
+    pub fn get_mod_log_target(
+        &self,
+        guild_id: GuildId,
+        event: ModLogEvent,
+    ) -> Option<ModLogTarget> {
+        self.mod_log_routes
+            .get(&(guild_id, event))
+            .map(|t| t.value().clone())
+    }
+
+    /// Gets every route configured for a guild.
+    #[must_use]
+    pub fn get_mod_log_targets(&self, guild_id: GuildId) -> Vec<(ModLogEvent, ModLogTarget)> {
+        self.mod_log_routes
+            .iter()
+            .filter(|entry| entry.key().0 == guild_id)
+            .map(|entry| (entry.key().1, entry.value().clone()))
+            .collect()
+    }
+
+    /// Sets or unsets where an event is logged to in a guild.
+    pub async fn set_mod_log_target(
+        &self,
+        guild_id: GuildId,
+        event: ModLogEvent,
+        target: Option<ModLogTarget>,
+    ) -> Result<(), Error> {
+        let id = self.get_guild(guild_id).await?;
+
+        let Some(target) = target else {
+            query!(
+                "DELETE FROM mod_log_routes WHERE guild_id = $1 AND event = $2",
+                id,
+                event as ModLogEvent
+            )
+            .execute(&self.db)
+            .await?;
+
+            self.mod_log_routes.remove(&(guild_id, event));
+            return Ok(());
+        };
+
+        let (channel_id, webhook_url) = match &target {
+            ModLogTarget::Channel(channel_id) => (Some(channel_id.get() as i64), None),
+            ModLogTarget::Webhook(url) => (None, Some(url.as_str())),
+        };
+
+        query!(
+            r#"
+            INSERT INTO mod_log_routes (guild_id, event, channel_id, webhook_url)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (guild_id, event) DO UPDATE SET
+                channel_id = EXCLUDED.channel_id,
+                webhook_url = EXCLUDED.webhook_url
+            "#,
+            id,
+            event as ModLogEvent,
+            channel_id,
+            webhook_url
+        )
+        .execute(&self.db)
+        .await?;
+
+        self.mod_log_routes.insert((guild_id, event), target);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,171 @@
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use serenity::all::{GenericChannelId, GuildId, UserId};
+use sqlx::query;
+
+use crate::data::structs::Error;
+
+/// An open modmail thread, a user only ever has one open at a time.
+#[derive(Debug, Clone, Copy)]
+pub struct ModmailThread {
+    pub guild_id: GuildId,
+    pub user_id: UserId,
+    /// The forum post staff reply in.
+    pub thread_id: GenericChannelId,
+    pub opened_at: DateTime<Utc>,
+}
+
+/// A thread that has been closed, along with everything said in it.
+#[derive(Debug, Clone)]
+pub struct ClosedModmailThread {
+    pub thread_id: GenericChannelId,
+    pub opened_at: DateTime<Utc>,
+    pub closed_at: DateTime<Utc>,
+    pub closed_by: Option<UserId>,
+    /// `None` if the thread was deleted before it was closed.
+    pub transcript: Option<String>,
+}
+
+pub(super) async fn load_modmail_threads(
+    db: &sqlx::PgPool,
+) -> Result<DashMap<GenericChannelId, ModmailThread>, Error> {
+    let rows = query!(
+        r#"
+        SELECT g.guild_id, u.user_id, t.thread_id, t.opened_at
+        FROM modmail_threads t
+        JOIN guilds g ON t.guild_id = g.id
+        JOIN users u ON t.user_id = u.id
+        WHERE t.closed_at IS NULL
+        "#
+    )
+    .fetch_all(db)
+    .await?;
+
+    let map = DashMap::with_capacity(rows.len());
+    for row in rows {
+        let thread = ModmailThread {
+            guild_id: GuildId::new(row.guild_id as u64),
+            user_id: UserId::new(row.user_id as u64),
+            thread_id: GenericChannelId::new(row.thread_id as u64),
+            opened_at: row.opened_at,
+        };
+
+        map.insert(thread.thread_id, thread);
+    }
+
+    Ok(map)
+}
+
+impl super::Database {
+    /// Gets the open modmail thread for a forum post.
+    #[must_use]
+    pub fn get_modmail_thread(&self, thread_id: GenericChannelId) -> Option<ModmailThread> {
+        self.modmail_threads.get(&thread_id).map(|t| *t.value())
+    }
+
+    /// Gets the open modmail thread of a user.
+    #[must_use]
+    pub fn get_user_modmail_thread(&self, user_id: UserId) -> Option<ModmailThread> {
+        self.modmail_threads
+            .iter()
+            .find(|t| t.user_id == user_id)
+            .map(|t| *t.value())
+    }
+
+    pub async fn open_modmail_thread(
+        &self,
+        guild_id: GuildId,
+        user_id: UserId,
+        thread_id: GenericChannelId,
+    ) -> Result<ModmailThread, Error> {
+        let id = self.get_guild(guild_id).await?;
+        let user = self.get_user(user_id).await?.id;
+
+        let row = query!(
+            "INSERT INTO modmail_threads (guild_id, user_id, thread_id) VALUES ($1, $2, $3) \
+             RETURNING opened_at",
+            id,
+            user,
+            thread_id.get() as i64
+        )
+        .fetch_one(&self.db)
+        .await?;
+
+        let thread = ModmailThread {
+            guild_id,
+            user_id,
+            thread_id,
+            opened_at: row.opened_at,
+        };
+        self.modmail_threads.insert(thread_id, thread);
+
+        Ok(thread)
+    }
+
+    /// Closes a thread and stores its transcript, returning the thread if it was open.
+    ///
+    /// Threads that were deleted have nobody that closed them and no transcript.
+    pub async fn close_modmail_thread(
+        &self,
+        thread_id: GenericChannelId,
+        closed_by: Option<UserId>,
+        transcript: Option<&str>,
+    ) -> Result<Option<ModmailThread>, Error> {
+        let Some((_, thread)) = self.modmail_threads.remove(&thread_id) else {
+            return Ok(None);
+        };
+
+        let closed_by = match closed_by {
+            Some(closed_by) => Some(self.get_user(closed_by).await?.id),
+            None => None,
+        };
+
+        query!(
+            "UPDATE modmail_threads SET closed_at = NOW(), closed_by = $2, transcript = $3 WHERE \
+             thread_id = $1 AND closed_at IS NULL",
+            thread_id.get() as i64,
+            closed_by,
+            transcript
+        )
+        .execute(&self.db)
+        .await?;
+
+        Ok(Some(thread))
+    }
+
+    /// Every closed thread a user has had in a guild, newest first.
+    pub async fn get_modmail_transcripts(
+        &self,
+        guild_id: GuildId,
+        user_id: UserId,
+    ) -> Result<Vec<ClosedModmailThread>, Error> {
+        let id = self.get_guild(guild_id).await?;
+        let user = self.get_user(user_id).await?.id;
+
+        let rows = query!(
+            r#"
+            SELECT t.thread_id, t.opened_at, t.closed_at AS "closed_at!", c.user_id AS "closed_by?",
+                   t.transcript
+            FROM modmail_threads t
+            LEFT JOIN users c ON t.closed_by = c.id
+            WHERE t.guild_id = $1 AND t.user_id = $2 AND t.closed_at IS NOT NULL
+            ORDER BY t.closed_at DESC
+            "#,
+            id,
+            user
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| ClosedModmailThread {
+                thread_id: GenericChannelId::new(row.thread_id as u64),
+                opened_at: row.opened_at,
+                closed_at: row.closed_at,
+                closed_by: row.closed_by.map(|u| UserId::new(u as u64)),
+                transcript: row.transcript,
+            })
+            .collect())
+    }
+}
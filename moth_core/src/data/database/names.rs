@@ -0,0 +1,125 @@
+use chrono::{DateTime, Utc};
+use serenity::all::{GuildId, UserId};
+use sqlx::query;
+
+use crate::data::structs::Error;
+
+/// What kind of name or avatar changed.
+#[derive(Debug, Clone, Copy, sqlx::Type, PartialEq, Eq, Hash)]
+#[sqlx(type_name = "namechangekind", rename_all = "snake_case")]
+pub enum NameChangeKind {
+    Username,
+    DisplayName,
+    Nickname,
+    Avatar,
+    GuildAvatar,
+}
+
+impl NameChangeKind {
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Username => "Username",
+            Self::DisplayName => "Display name",
+            Self::Nickname => "Nickname",
+            Self::Avatar => "Avatar",
+            Self::GuildAvatar => "Server avatar",
+        }
+    }
+
+    /// If the value belongs to a single guild rather than the user.
+    #[must_use]
+    pub fn is_guild_specific(self) -> bool {
+        matches!(self, Self::Nickname | Self::GuildAvatar)
+    }
+}
+
+/// A stored name or avatar change, avatars are stored as their hash.
+#[derive(Clone, Debug)]
+pub struct NameChange {
+    /// Only set for guild specific changes.
+    pub guild_id: Option<GuildId>,
+    pub kind: NameChangeKind,
+    pub old_value: Option<String>,
+    pub new_value: Option<String>,
+    pub changed_at: DateTime<Utc>,
+}
+
+impl super::Database {
+    /// Records a change, global changes are seen once for every mutual guild so they are only
+    /// stored when they differ from the last stored value.
+    pub async fn insert_name_change(
+        &self,
+        user_id: UserId,
+        guild_id: GuildId,
+        kind: NameChangeKind,
+        old_value: Option<&str>,
+        new_value: Option<&str>,
+    ) -> Result<(), Error> {
+        let user = self.get_user(user_id).await?.id;
+        let guild = if kind.is_guild_specific() {
+            Some(self.get_guild(guild_id).await?)
+        } else {
+            None
+        };
+
+        query!(
+            r#"
+            INSERT INTO name_history (user_id, guild_id, kind, old_value, new_value)
+            SELECT $1, $2, $3, $4, $5
+            WHERE NOT EXISTS (
+                SELECT 1 FROM (
+                    SELECT new_value FROM name_history
+                    WHERE user_id = $1 AND guild_id IS NOT DISTINCT FROM $2 AND kind = $3
+                    ORDER BY changed_at DESC
+                    LIMIT 1
+                ) latest
+                WHERE latest.new_value IS NOT DISTINCT FROM $5
+            )
+            "#,
+            user,
+            guild,
+            kind as NameChangeKind,
+            old_value,
+            new_value
+        )
+        .execute(&self.db)
+        .await?;
+
+        Ok(())
+    }
+
+    /// A user's name history, newest first, guild specific changes are limited to `guild_id`.
+    pub async fn get_name_history(
+        &self,
+        user_id: UserId,
+        guild_id: GuildId,
+    ) -> Result<Vec<NameChange>, Error> {
+        let user = self.get_user(user_id).await?.id;
+        let guild = self.get_guild(guild_id).await?;
+
+        let rows = query!(
+            r#"
+            SELECT n.kind AS "kind: NameChangeKind", n.old_value, n.new_value, n.changed_at
+            FROM name_history n
+            WHERE n.user_id = $1 AND (n.guild_id IS NULL OR n.guild_id = $2)
+            ORDER BY n.changed_at DESC
+            "#,
+            user,
+            guild
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| NameChange {
+                guild_id: row.kind.is_guild_specific().then_some(guild_id),
+                kind: row.kind,
+                old_value: row.old_value,
+                new_value: row.new_value,
+                changed_at: row.changed_at,
+            })
+            .collect())
+    }
+}
@@ -0,0 +1,441 @@
+use std::sync::{Arc, LazyLock};
+
+use chrono::{DateTime, TimeDelta, Utc};
+use dashmap::DashMap;
+use serenity::all::{GuildId, UserId};
+use sqlx::query;
+use tokio::sync::Mutex;
+
+use crate::data::structs::Error;
+
+/// Activities shorter than this aren't worth keeping.
+const MIN_ACTIVITY: TimeDelta = TimeDelta::minutes(2);
+/// An activity that restarts within this long of ending continues the previous row.
+const ACTIVITY_MERGE_WINDOW: TimeDelta = TimeDelta::minutes(5);
+/// Status changes this close together replace each other instead of adding a row.
+const STATUS_DEBOUNCE: TimeDelta = TimeDelta::minutes(2);
+
+/// Updates for a member read the open activities before writing them, so they take turns.
+static PRESENCE_UPDATES: LazyLock<DashMap<(GuildId, UserId), Arc<Mutex<()>>>> =
+    LazyLock::new(DashMap::new);
+
+#[derive(Clone, Debug)]
+pub(super) struct OpenActivity {
+    id: i64,
+    name: String,
+    started_at: DateTime<Utc>,
+}
+
+#[derive(Clone, Debug)]
+pub(super) struct LastStatus {
+    id: i64,
+    status: String,
+    changed_at: DateTime<Utc>,
+}
+
+/// An entry in a member's activity timeline.
+#[derive(Clone, Debug)]
+pub enum PresenceEvent {
+    Activity {
+        name: String,
+        started_at: DateTime<Utc>,
+        /// `None` while it's ongoing.
+        ended_at: Option<DateTime<Utc>>,
+    },
+    Status {
+        status: String,
+        changed_at: DateTime<Utc>,
+    },
+}
+
+impl PresenceEvent {
+    #[must_use]
+    pub fn timestamp(&self) -> DateTime<Utc> {
+        match self {
+            Self::Activity { started_at, .. } => *started_at,
+            Self::Status { changed_at, .. } => *changed_at,
+        }
+    }
+}
+
+/// A game and how much it was played.
+#[derive(Clone, Debug)]
+pub struct GameStats {
+    pub name: String,
+    pub players: i64,
+    pub total_secs: i64,
+}
+
+/// Closes activities left open by the last run at the time they were last seen.
+pub(super) async fn close_stale_activities(db: &sqlx::PgPool) -> Result<(), Error> {
+    query!("UPDATE presence_activities SET ended_at = last_seen_at WHERE ended_at IS NULL")
+        .execute(db)
+        .await?;
+
+    Ok(())
+}
+
+impl super::Database {
+    /// Records a presence update, `games` are the names of everything the member is playing.
+    pub async fn update_presence(
+        &self,
+        guild_id: GuildId,
+        user_id: UserId,
+        status: &str,
+        games: &[&str],
+    ) -> Result<(), Error> {
+        let lock = PRESENCE_UPDATES
+            .entry((guild_id, user_id))
+            .or_default()
+            .clone();
+        let result = {
+            let _guard = lock.lock().await;
+            self.update_presence_locked(guild_id, user_id, status, games)
+                .await
+        };
+        drop(lock);
+        PRESENCE_UPDATES.remove_if(&(guild_id, user_id), |_, lock| Arc::strong_count(lock) == 1);
+
+        result
+    }
+
+    async fn update_presence_locked(
+        &self,
+        guild_id: GuildId,
+        user_id: UserId,
+        status: &str,
+        games: &[&str],
+    ) -> Result<(), Error> {
+        let now = Utc::now();
+
+        let open = self
+            .presence_activities
+            .get(&(guild_id, user_id))
+            .map(|a| a.value().clone())
+            .unwrap_or_default();
+
+        for activity in &open {
+            if !games.contains(&activity.name.as_str()) {
+                self.end_activity(guild_id, user_id, activity, now).await?;
+            }
+        }
+
+        for game in games {
+            if !open.iter().any(|a| a.name == *game) {
+                self.start_activity(guild_id, user_id, game, now).await?;
+            }
+        }
+
+        self.update_status(guild_id, user_id, status, now).await
+    }
+
+    async fn start_activity(
+        &self,
+        guild_id: GuildId,
+        user_id: UserId,
+        name: &str,
+        now: DateTime<Utc>,
+    ) -> Result<(), Error> {
+        let id = self.get_guild(guild_id).await?;
+        let user = self.get_user(user_id).await?.id;
+
+        let reopened = query!(
+            r#"
+            UPDATE presence_activities SET ended_at = NULL, last_seen_at = $5
+            WHERE id = (
+                SELECT id FROM presence_activities
+                WHERE guild_id = $1 AND user_id = $2 AND name = $3 AND ended_at > $4
+                ORDER BY ended_at DESC
+                LIMIT 1
+            )
+            RETURNING id, started_at
+            "#,
+            id,
+            user,
+            name,
+            now - ACTIVITY_MERGE_WINDOW,
+            now
+        )
+        .fetch_optional(&self.db)
+        .await?;
+
+        let (activity_id, started_at) = if let Some(row) = reopened {
+            (row.id, row.started_at)
+        } else {
+            let row = query!(
+                r#"
+                INSERT INTO presence_activities (guild_id, user_id, name, started_at, last_seen_at)
+                VALUES ($1, $2, $3, $4, $4)
+                RETURNING id
+                "#,
+                id,
+                user,
+                name,
+                now
+            )
+            .fetch_one(&self.db)
+            .await?;
+            (row.id, now)
+        };
+
+        self.presence_activities
+            .entry((guild_id, user_id))
+            .or_default()
+            .push(OpenActivity {
+                id: activity_id,
+                name: name.to_owned(),
+                started_at,
+            });
+
+        Ok(())
+    }
+
+    async fn end_activity(
+        &self,
+        guild_id: GuildId,
+        user_id: UserId,
+        activity: &OpenActivity,
+        now: DateTime<Utc>,
+    ) -> Result<(), Error> {
+        if let Some(mut open) = self.presence_activities.get_mut(&(guild_id, user_id)) {
+            open.retain(|a| a.id != activity.id);
+        }
+        self.presence_activities
+            .remove_if(&(guild_id, user_id), |_, open| open.is_empty());
+
+        if now - activity.started_at < MIN_ACTIVITY {
+            query!("DELETE FROM presence_activities WHERE id = $1", activity.id)
+                .execute(&self.db)
+                .await?;
+        } else {
+            query!(
+                "UPDATE presence_activities SET ended_at = $2, last_seen_at = $2 WHERE id = $1",
+                activity.id,
+                now
+            )
+            .execute(&self.db)
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn update_status(
+        &self,
+        guild_id: GuildId,
+        user_id: UserId,
+        status: &str,
+        now: DateTime<Utc>,
+    ) -> Result<(), Error> {
+        let id = self.get_guild(guild_id).await?;
+        let user = self.get_user(user_id).await?.id;
+
+        let last = match self.presence_statuses.get(&(guild_id, user_id)) {
+            Some(last) => Some(last.value().clone()),
+            // first time seeing them since starting, the last stored status is still valid.
+            None => query!(
+                r#"
+                SELECT id, status, changed_at FROM presence_statuses
+                WHERE guild_id = $1 AND user_id = $2
+                ORDER BY changed_at DESC
+                LIMIT 1
+                "#,
+                id,
+                user
+            )
+            .fetch_optional(&self.db)
+            .await?
+            .map(|row| LastStatus {
+                id: row.id,
+                status: row.status,
+                changed_at: row.changed_at,
+            }),
+        };
+
+        let last = match last {
+            Some(last) if last.status == status => last,
+            Some(last) if now - last.changed_at < STATUS_DEBOUNCE => {
+                query!(
+                    "UPDATE presence_statuses SET status = $2 WHERE id = $1",
+                    last.id,
+                    status
+                )
+                .execute(&self.db)
+                .await?;
+
+                LastStatus {
+                    status: status.to_owned(),
+                    ..last
+                }
+            }
+            _ => {
+                let row = query!(
+                    r#"
+                    INSERT INTO presence_statuses (guild_id, user_id, status, changed_at)
+                    VALUES ($1, $2, $3, $4)
+                    RETURNING id
+                    "#,
+                    id,
+                    user,
+                    status,
+                    now
+                )
+                .fetch_one(&self.db)
+                .await?;
+
+                LastStatus {
+                    id: row.id,
+                    status: status.to_owned(),
+                    changed_at: now,
+                }
+            }
+        };
+
+        self.presence_statuses.insert((guild_id, user_id), last);
+
+        Ok(())
+    }
+
+    /// Marks every tracked activity as still ongoing.
+    pub async fn touch_presence_activities(&self) -> Result<(), Error> {
+        let ids: Vec<i64> = self
+            .presence_activities
+            .iter()
+            .flat_map(|open| open.value().iter().map(|a| a.id).collect::<Vec<_>>())
+            .collect();
+
+        if ids.is_empty() {
+            return Ok(());
+        }
+
+        query!(
+            "UPDATE presence_activities SET last_seen_at = NOW() WHERE id = ANY($1)",
+            &ids
+        )
+        .execute(&self.db)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Ends every open activity in a guild, for when it stops keeping presence history.
+    pub async fn end_guild_presence_activities(&self, guild_id: GuildId) -> Result<(), Error> {
+        let now = Utc::now();
+        let open: Vec<((GuildId, UserId), Vec<OpenActivity>)> = self
+            .presence_activities
+            .iter()
+            .filter(|entry| entry.key().0 == guild_id)
+            .map(|entry| (*entry.key(), entry.value().clone()))
+            .collect();
+
+        for ((guild_id, user_id), activities) in open {
+            for activity in &activities {
+                self.end_activity(guild_id, user_id, activity, now).await?;
+            }
+        }
+        self.presence_statuses.retain(|(g, _), _| *g != guild_id);
+
+        // anything left open by a previous run isn't tracked.
+        let id = self.get_guild(guild_id).await?;
+        query!(
+            "UPDATE presence_activities SET ended_at = last_seen_at \
+             WHERE guild_id = $1 AND ended_at IS NULL",
+            id
+        )
+        .execute(&self.db)
+        .await?;
+
+        Ok(())
+    }
+
+    /// The most played games since `since`, by total time played within the window.
+    pub async fn get_top_games(
+        &self,
+        guild_id: GuildId,
+        since: DateTime<Utc>,
+        limit: i64,
+    ) -> Result<Vec<GameStats>, Error> {
+        let id = self.get_guild(guild_id).await?;
+
+        let rows = query!(
+            r#"
+            SELECT
+                name,
+                COUNT(DISTINCT user_id) AS "players!",
+                SUM(EXTRACT(EPOCH FROM COALESCE(ended_at, NOW()) - GREATEST(started_at, $2)))::BIGINT
+                    AS "total_secs!"
+            FROM presence_activities
+            WHERE guild_id = $1 AND COALESCE(ended_at, NOW()) > $2
+            GROUP BY name
+            ORDER BY 3 DESC
+            LIMIT $3
+            "#,
+            id,
+            since,
+            limit
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| GameStats {
+                name: row.name,
+                players: row.players,
+                total_secs: row.total_secs,
+            })
+            .collect())
+    }
+
+    /// A member's activities and status changes since `since`, newest first.
+    pub async fn get_presence_timeline(
+        &self,
+        guild_id: GuildId,
+        user_id: UserId,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<PresenceEvent>, Error> {
+        let id = self.get_guild(guild_id).await?;
+        let user = self.get_user(user_id).await?.id;
+
+        let activities = query!(
+            r#"
+            SELECT name, started_at, ended_at FROM presence_activities
+            WHERE guild_id = $1 AND user_id = $2 AND COALESCE(ended_at, NOW()) > $3
+            "#,
+            id,
+            user,
+            since
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        let statuses = query!(
+            r#"
+            SELECT status, changed_at FROM presence_statuses
+            WHERE guild_id = $1 AND user_id = $2 AND changed_at > $3
+            "#,
+            id,
+            user,
+            since
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        let mut timeline = activities
+            .into_iter()
+            .map(|row| PresenceEvent::Activity {
+                name: row.name,
+                started_at: row.started_at,
+                ended_at: row.ended_at,
+            })
+            .chain(statuses.into_iter().map(|row| PresenceEvent::Status {
+                status: row.status,
+                changed_at: row.changed_at,
+            }))
+            .collect::<Vec<_>>();
+
+        timeline.sort_by_key(|e| std::cmp::Reverse(e.timestamp()));
+
+        Ok(timeline)
+    }
+}
@@ -3,14 +3,14 @@ use std::{sync::Arc, time::Duration};
 use serenity::all::{
     ChannelId, ComponentInteraction, Context, CreateAllowedMentions, CreateInputText,
     CreateInteractionResponse, CreateInteractionResponseMessage, CreateQuickModal, EditChannel,
-    InputTextStyle, ModalInteraction, PermissionOverwrite, PermissionOverwriteType, Permissions,
-    QuickModal, RoleId, UserId,
+    GuildId, InputTextStyle, ModalInteraction, PermissionOverwrite, PermissionOverwriteType,
+    Permissions, QuickModal, RoleId, UserId,
 };
 
 use crate::data::{
     database::{
         PrivateVc,
-        private_vcs::task::{GUILD, get_parent_permissions, message, vc_has_user},
+        private_vcs::task::{get_parent_permissions, message, vc_has_user},
     },
     structs::Data,
 };
@@ -46,6 +46,10 @@ pub async fn handle_interaction(ctx: &Context, interaction: &ComponentInteractio
         return;
     };
 
+    let Some(guild_id) = interaction.guild_id else {
+        return;
+    };
+
     let Some(private_vc) = ctx
         .data_ref::<Data>()
         .database
@@ -76,17 +80,22 @@ pub async fn handle_interaction(ctx: &Context, interaction: &ComponentInteractio
     }
 
     match kind {
-        Kind::Owner => owner(ctx, interaction, private_vc).await,
+        Kind::Owner => owner(ctx, guild_id, interaction, private_vc).await,
         Kind::Size => size(ctx, interaction).await,
-        Kind::Allowlist => allow(ctx, interaction, (*private_vc).clone()).await,
-        Kind::Denylist => deny(ctx, interaction, (*private_vc).clone()).await,
-        Kind::Disconnect => disconnect(ctx, interaction).await,
+        Kind::Allowlist => allow(ctx, guild_id, interaction, (*private_vc).clone()).await,
+        Kind::Denylist => deny(ctx, guild_id, interaction, (*private_vc).clone()).await,
+        Kind::Disconnect => disconnect(ctx, guild_id, interaction).await,
         Kind::Region => unreachable!(),
     }
 }
 
 // really should set permissions here to prevent lockout lmao
-async fn owner(ctx: &Context, interaction: &ComponentInteraction, private_vc: Arc<PrivateVc>) {
+async fn owner(
+    ctx: &Context,
+    guild_id: GuildId,
+    interaction: &ComponentInteraction,
+    private_vc: Arc<PrivateVc>,
+) {
     let user_id = match &interaction.data.kind {
         serenity::all::ComponentInteractionDataKind::UserSelect { values } => {
             values.first().copied()
@@ -99,8 +108,12 @@ async fn owner(ctx: &Context, interaction: &ComponentInteraction, private_vc: Ar
     };
 
     // technically a race condition when setting the owner but its so minimal...
-    let user_in_vc =
-        super::task::vc_has_user(ctx, interaction.channel_id.expect_channel(), user_id);
+    let user_in_vc = super::task::vc_has_user(
+        ctx,
+        guild_id,
+        interaction.channel_id.expect_channel(),
+        user_id,
+    );
 
     if !user_in_vc {
         let _ = interaction
@@ -125,7 +138,7 @@ async fn owner(ctx: &Context, interaction: &ComponentInteraction, private_vc: Ar
         .create_private_vc(
             interaction.channel_id.expect_channel(),
             private_vc.message_id,
-            Some(GUILD),
+            Some(guild_id),
             user_id,
             private_vc.allowlist_roles.clone(),
             private_vc.allowlist_users.clone(),
@@ -142,7 +155,7 @@ async fn owner(ctx: &Context, interaction: &ComponentInteraction, private_vc: Ar
     // new instance
     let Some(private_vc) = data
         .database
-        .get_private_vc(interaction.channel_id.expect_channel(), Some(GUILD))
+        .get_private_vc(interaction.channel_id.expect_channel(), Some(guild_id))
         .await
     else {
         return;
@@ -150,6 +163,7 @@ async fn owner(ctx: &Context, interaction: &ComponentInteraction, private_vc: Ar
 
     message(
         ctx,
+        guild_id,
         interaction.channel_id.expect_channel(),
         private_vc.message_id,
         &private_vc,
@@ -169,8 +183,9 @@ async fn owner(ctx: &Context, interaction: &ComponentInteraction, private_vc: Ar
         .await;
 
     let overwrites = build_permission_overwrites(
+        guild_id,
         &private_vc,
-        get_parent_permissions(ctx, &interaction.channel_id.expect_channel())
+        get_parent_permissions(ctx, guild_id, &interaction.channel_id.expect_channel())
             .unwrap_or_default()
             .as_ref(),
         &mut vec![],
@@ -246,9 +261,14 @@ async fn respond_text(ctx: &Context, interaction: &ModalInteraction, msg: &str)
         .await;
 }
 
-async fn allow(ctx: &Context, interaction: &ComponentInteraction, mut private_vc: PrivateVc) {
+async fn allow(
+    ctx: &Context,
+    guild_id: GuildId,
+    interaction: &ComponentInteraction,
+    mut private_vc: PrivateVc,
+) {
     let disallowed_roles_users = {
-        let Some(guild) = ctx.cache.guild(GUILD) else {
+        let Some(guild) = ctx.cache.guild(guild_id) else {
             return;
         };
 
@@ -324,6 +344,7 @@ async fn allow(ctx: &Context, interaction: &ComponentInteraction, mut private_vc
 
     update_permissions(
         ctx,
+        guild_id,
         interaction,
         stripped_users,
         stripped_roles,
@@ -333,9 +354,14 @@ async fn allow(ctx: &Context, interaction: &ComponentInteraction, mut private_vc
     .await;
 }
 
-async fn deny(ctx: &Context, interaction: &ComponentInteraction, mut private_vc: PrivateVc) {
+async fn deny(
+    ctx: &Context,
+    guild_id: GuildId,
+    interaction: &ComponentInteraction,
+    mut private_vc: PrivateVc,
+) {
     let disallowed_roles_users = {
-        let Some(guild) = ctx.cache.guild(GUILD) else {
+        let Some(guild) = ctx.cache.guild(guild_id) else {
             return;
         };
 
@@ -361,8 +387,10 @@ async fn deny(ctx: &Context, interaction: &ComponentInteraction, mut private_vc:
     let mut stripped_users = Vec::new();
     let mut denied_overwrites = Vec::new();
 
+    let settings = ctx.data_ref::<Data>().database.get_guild_settings(guild_id);
     for (member_id, partial_member) in &interaction.data.resolved.members {
-        let is_mod = MOD_ROLES
+        let is_mod = settings
+            .moderator_roles
             .iter()
             .any(|mod_role| partial_member.roles.contains(mod_role));
 
@@ -401,6 +429,7 @@ async fn deny(ctx: &Context, interaction: &ComponentInteraction, mut private_vc:
 
     update_permissions(
         ctx,
+        guild_id,
         interaction,
         stripped_users,
         vec![],
@@ -410,23 +439,11 @@ async fn deny(ctx: &Context, interaction: &ComponentInteraction, mut private_vc:
     .await;
 }
 
-// mod role users need to not be allowed to be denied.
-
-const MOD_ROLES: [RoleId; 5] = [
-    // community
-    RoleId::new(98459030455853056),
-    // voice
-    RoleId::new(723115326195367936),
-    // trial
-    RoleId::new(781213498998915123),
-    // moth
-    RoleId::new(1062803266636873781),
-    // mod bot
-    RoleId::new(150811709009821696),
-];
+// mod role users need to not be allowed to be denied, these are configured per guild.
 
 pub(super) async fn update_permissions(
     ctx: &Context,
+    guild_id: GuildId,
     interaction: &ComponentInteraction,
     mut stripped_users: Vec<UserId>,
     mut stripped_roles: Vec<RoleId>,
@@ -434,6 +451,7 @@ pub(super) async fn update_permissions(
     parent_permissions: &[PermissionOverwrite],
 ) {
     let permissions = build_permission_overwrites(
+        guild_id,
         &private_vc,
         parent_permissions,
         &mut stripped_users,
@@ -447,7 +465,7 @@ pub(super) async fn update_permissions(
         .create_private_vc(
             interaction.channel_id.expect_channel(),
             private_vc.message_id,
-            Some(GUILD),
+            Some(guild_id),
             private_vc.owner_id,
             private_vc.allowlist_roles.clone(),
             private_vc.allowlist_users.clone(),
@@ -464,6 +482,7 @@ pub(super) async fn update_permissions(
     let _ = tokio::join!(
         super::task::message(
             ctx,
+            guild_id,
             interaction.channel_id.expect_channel(),
             private_vc.message_id,
             &private_vc,
@@ -474,6 +493,7 @@ pub(super) async fn update_permissions(
 }
 
 pub(super) fn build_permission_overwrites(
+    guild_id: GuildId,
     private_vc: &PrivateVc,
     parent_permissions: &[PermissionOverwrite],
     stripped_users: &mut Vec<UserId>,
@@ -531,7 +551,7 @@ pub(super) fn build_permission_overwrites(
 
     if let Some(p) = permissions
         .iter_mut()
-        .find(|p| p.kind == PermissionOverwriteType::Role(GUILD.get().into()))
+        .find(|p| p.kind == PermissionOverwriteType::Role(guild_id.get().into()))
     {
         if private_vc.allowlist_roles.is_empty() && private_vc.allowlist_users.is_empty() {
             p.allow.insert(Permissions::CONNECT);
@@ -608,7 +628,7 @@ async fn send_update_message(
         .await;
 }
 
-async fn disconnect(ctx: &Context, interaction: &ComponentInteraction) {
+async fn disconnect(ctx: &Context, guild_id: GuildId, interaction: &ComponentInteraction) {
     let (user_id, partial_member) = interaction
         .data
         .resolved
@@ -617,7 +637,12 @@ async fn disconnect(ctx: &Context, interaction: &ComponentInteraction) {
         .next()
         .expect("select menu should contain at least 1 user");
 
-    if !vc_has_user(ctx, interaction.channel_id.expect_channel(), *user_id) {
+    if !vc_has_user(
+        ctx,
+        guild_id,
+        interaction.channel_id.expect_channel(),
+        *user_id,
+    ) {
         let _ = interaction
             .create_response(
                 &ctx.http,
@@ -631,7 +656,11 @@ async fn disconnect(ctx: &Context, interaction: &ComponentInteraction) {
         return;
     }
 
-    let is_mod = MOD_ROLES
+    let is_mod = ctx
+        .data_ref::<Data>()
+        .database
+        .get_guild_settings(guild_id)
+        .moderator_roles
         .iter()
         .any(|mod_role| partial_member.roles.contains(mod_role));
 
@@ -652,7 +681,11 @@ async fn disconnect(ctx: &Context, interaction: &ComponentInteraction) {
         return;
     }
 
-    if GUILD.disconnect_member(&ctx.http, *user_id).await.is_ok() {
+    if guild_id
+        .disconnect_member(&ctx.http, *user_id)
+        .await
+        .is_ok()
+    {
         let _ = interaction
             .create_response(
                 &ctx.http,
@@ -1,7 +1,7 @@
 use std::sync::Arc;
 
 use serenity::{
-    all::{ChannelId, Context, UserId},
+    all::{ChannelId, Context, GuildId, UserId},
     small_fixed_array::FixedString,
 };
 use tokio::sync::mpsc::UnboundedSender;
@@ -44,19 +44,27 @@ impl Sender {
         *self.0.lock().await = Some(tx);
     }
 
-    pub async fn join(&self, channel_id: ChannelId, user_id: UserId, username: FixedString<u8>) {
+    pub async fn join(
+        &self,
+        guild_id: GuildId,
+        channel_id: ChannelId,
+        user_id: UserId,
+        username: FixedString<u8>,
+    ) {
         let lock = self.0.lock().await;
 
         lock.as_ref().map(|s: &UnboundedSender<HandlerCommand>| {
-            s.send(HandlerCommand::JoinSpecial((channel_id, user_id, username)))
+            s.send(HandlerCommand::JoinSpecial((
+                guild_id, channel_id, user_id, username,
+            )))
         });
     }
 
-    pub async fn leave(&self, channel_id: ChannelId, user_id: UserId) {
+    pub async fn leave(&self, guild_id: GuildId, channel_id: ChannelId, user_id: UserId) {
         let lock = self.0.lock().await;
 
         lock.as_ref().map(|s: &UnboundedSender<HandlerCommand>| {
-            s.send(HandlerCommand::LeaveVc((channel_id, user_id)))
+            s.send(HandlerCommand::LeaveVc((guild_id, channel_id, user_id)))
         });
     }
 
@@ -68,8 +76,8 @@ impl Sender {
 
 enum HandlerCommand {
     /// Should be triggered when a user either joins the creation channel, or a private vc.
-    JoinSpecial((ChannelId, UserId, FixedString<u8>)),
-    LeaveVc((ChannelId, UserId)),
+    JoinSpecial((GuildId, ChannelId, UserId, FixedString<u8>)),
+    LeaveVc((GuildId, ChannelId, UserId)),
     Shutdown,
 }
 
@@ -32,16 +32,13 @@ use crate::data::{
     structs::Data,
 };
 
-pub const VC_CHANNEL: ChannelId = ChannelId::new(1399817426723668039);
-pub const GUILD: GuildId = GuildId::new(98226572468690944);
-
 // cooldown system setup when?
 
 #[derive(Hash, Eq, PartialEq, Clone, Debug)]
 enum QueueType {
-    Cooldown((UserId, FixedString<u8>)),
-    Leave(ChannelId),
-    OwnerLeave((ChannelId, UserId)),
+    Cooldown((GuildId, UserId, FixedString<u8>)),
+    Leave((GuildId, ChannelId)),
+    OwnerLeave((GuildId, ChannelId, UserId)),
 }
 
 pub(super) async fn start(
@@ -55,9 +52,9 @@ pub(super) async fn start(
     // Populate on task startup to clearup old channels.
     let data = ctx.data_ref::<Data>();
     if let Ok(private_vcs) = data.database.get_all_private_vcs().await {
-        for (id, _) in private_vcs {
-            let key = delay_queue.insert(QueueType::Leave(id), Duration::from_secs(5));
-            keys.insert(QueueType::Leave(id), key);
+        for (guild_id, id, _) in private_vcs {
+            let key = delay_queue.insert(QueueType::Leave((guild_id, id)), Duration::from_secs(5));
+            keys.insert(QueueType::Leave((guild_id, id)), key);
         }
     }
 
@@ -80,43 +77,53 @@ pub(super) async fn start(
 }
 
 async fn handle_expired(ctx: &Context, queue_type: QueueType) {
-    async fn maybe_del_vc(ctx: &Context, id: ChannelId) {
-        if id == VC_CHANNEL {
+    async fn maybe_del_vc(ctx: &Context, guild_id: GuildId, id: ChannelId) {
+        let data = ctx.data_ref::<Data>();
+        let vc_channel = data
+            .database
+            .get_guild_settings(guild_id)
+            .private_vc_channel;
+
+        if vc_channel == Some(id) {
             return; // DO NOT
         }
 
         // TODO: determine permission failure from not existing
-        if !vc_has_people(ctx, id)
+        if !vc_has_people(ctx, guild_id, id)
             && ctx
                 .http
                 .delete_channel(id.widen(), Some("Private VC no longer active."))
                 .await
                 .is_ok()
         {
-            let _ = ctx
-                .data_ref::<Data>()
-                .database
-                .delete_private_vc(id, GUILD)
-                .await;
+            let _ = data.database.delete_private_vc(id, guild_id).await;
         }
     }
 
     match queue_type {
-        QueueType::Cooldown((user_id, username)) => {
-            if vc_has_user(ctx, VC_CHANNEL, user_id) {
-                create_channel(ctx, user_id, username).await;
+        QueueType::Cooldown((guild_id, user_id, username)) => {
+            let settings = ctx.data_ref::<Data>().database.get_guild_settings(guild_id);
+
+            if let Some(vc_channel) = settings.private_vc_channel
+                && vc_has_user(ctx, guild_id, vc_channel, user_id)
+            {
+                create_channel(ctx, guild_id, vc_channel, user_id, username).await;
             }
         }
-        QueueType::Leave(channel_id) => maybe_del_vc(ctx, channel_id).await,
-        QueueType::OwnerLeave((channel_id, user_id)) => {
-            if !vc_has_user(ctx, channel_id, user_id) {
+        QueueType::Leave((guild_id, channel_id)) => maybe_del_vc(ctx, guild_id, channel_id).await,
+        QueueType::OwnerLeave((guild_id, channel_id, user_id)) => {
+            if !vc_has_user(ctx, guild_id, channel_id, user_id) {
                 let data = ctx.data_ref::<Data>();
 
-                let Some(vc) = data.database.get_private_vc(channel_id, Some(GUILD)).await else {
+                let Some(vc) = data
+                    .database
+                    .get_private_vc(channel_id, Some(guild_id))
+                    .await
+                else {
                     return;
                 };
 
-                let users = get_vc_users(ctx, channel_id);
+                let users = get_vc_users(ctx, guild_id, channel_id);
                 let chosen_owner = {
                     let mut rng = rand::rng();
                     let random_index = rng.next_u32() as usize % users.len();
@@ -129,7 +136,7 @@ async fn handle_expired(ctx: &Context, queue_type: QueueType) {
                     .create_private_vc(
                         channel_id,
                         vc.message_id,
-                        Some(GUILD),
+                        Some(guild_id),
                         chosen_owner,
                         vc.allowlist_roles.clone(),
                         vc.allowlist_users.clone(),
@@ -144,17 +151,21 @@ async fn handle_expired(ctx: &Context, queue_type: QueueType) {
                 }
 
                 // get new state
-                let Some(new_vc) = data.database.get_private_vc(channel_id, Some(GUILD)).await
+                let Some(new_vc) = data
+                    .database
+                    .get_private_vc(channel_id, Some(guild_id))
+                    .await
                 else {
                     return;
                 };
 
                 // TODO: rerun if they aren't in the VC by this point, its a race condition but very rare.
-                message(ctx, channel_id, vc.message_id, &new_vc).await;
+                message(ctx, guild_id, channel_id, vc.message_id, &new_vc).await;
 
                 let overwrites = build_permission_overwrites(
+                    guild_id,
                     &new_vc,
-                    get_parent_permissions(ctx, &channel_id)
+                    get_parent_permissions(ctx, guild_id, &channel_id)
                         .unwrap_or_default()
                         .as_ref(),
                     &mut vec![],
@@ -170,9 +181,10 @@ async fn handle_expired(ctx: &Context, queue_type: QueueType) {
 #[must_use]
 pub fn get_parent_permissions(
     ctx: &Context,
+    guild_id: GuildId,
     channel_id: &ChannelId,
 ) -> Option<Vec<PermissionOverwrite>> {
-    let guild = ctx.cache.guild(GUILD)?;
+    let guild = ctx.cache.guild(guild_id)?;
     let channel = guild.channels.get(channel_id)?;
     let parent_id = channel.parent_id?;
     let parent_channel = guild.channels.get(&parent_id)?;
@@ -193,11 +205,12 @@ async fn handle_command(
     let database = &data.database;
 
     match cmd {
-        HandlerCommand::JoinSpecial((channel_id, user_id, username)) => {
+        HandlerCommand::JoinSpecial((guild_id, channel_id, user_id, username)) => {
             // first we need to figure out the origin, *we know* its either special or a private VC already.
-            if let Some(vc) = database.get_private_vc(channel_id, Some(GUILD)).await {
+            if let Some(vc) = database.get_private_vc(channel_id, Some(guild_id)).await {
                 if vc.owner_id == user_id
-                    && let Some(key) = keys.get(&QueueType::OwnerLeave((channel_id, user_id)))
+                    && let Some(key) =
+                        keys.get(&QueueType::OwnerLeave((guild_id, channel_id, user_id)))
                 {
                     delay_queue.remove(key);
                 }
@@ -206,7 +219,7 @@ async fn handle_command(
             }
 
             // probably isn't possible to reach here given the circumstances, but we very much should check anyway.
-            if channel_id != VC_CHANNEL {
+            if database.get_guild_settings(guild_id).private_vc_channel != Some(channel_id) {
                 return true;
             }
 
@@ -217,7 +230,7 @@ async fn handle_command(
                 if elapsed < COOLDOWN {
                     let remaining = COOLDOWN - elapsed;
                     // TODO: technically a bad idea to put the username inside the keymap but later me problem.
-                    let value = QueueType::Cooldown((user_id, username));
+                    let value = QueueType::Cooldown((guild_id, user_id, username));
 
                     let key = delay_queue.insert(value.clone(), remaining);
                     keys.insert(value, key);
@@ -227,23 +240,23 @@ async fn handle_command(
             }
 
             join_times.insert(user_id, Instant::now());
-            create_channel(ctx, user_id, username).await;
+            create_channel(ctx, guild_id, channel_id, user_id, username).await;
         }
-        HandlerCommand::LeaveVc((channel_id, user_id)) => {
+        HandlerCommand::LeaveVc((guild_id, channel_id, user_id)) => {
             // TODO: figure out if owner, then insert right type
-            if !vc_has_people(ctx, channel_id) {
-                let key = delay_queue.insert(QueueType::Leave(channel_id), COOLDOWN);
-                keys.insert(QueueType::Leave(channel_id), key);
+            if !vc_has_people(ctx, guild_id, channel_id) {
+                let key = delay_queue.insert(QueueType::Leave((guild_id, channel_id)), COOLDOWN);
+                keys.insert(QueueType::Leave((guild_id, channel_id)), key);
             }
 
-            if let Some(vc) = database.get_private_vc(channel_id, Some(GUILD)).await
+            if let Some(vc) = database.get_private_vc(channel_id, Some(guild_id)).await
                 && vc.owner_id == user_id
             {
                 let key = delay_queue.insert(
-                    QueueType::OwnerLeave((channel_id, user_id)),
+                    QueueType::OwnerLeave((guild_id, channel_id, user_id)),
                     Duration::from_secs(300),
                 );
-                keys.insert(QueueType::OwnerLeave((channel_id, user_id)), key);
+                keys.insert(QueueType::OwnerLeave((guild_id, channel_id, user_id)), key);
             }
         }
         HandlerCommand::Shutdown => return false,
@@ -252,8 +265,8 @@ async fn handle_command(
     true
 }
 
-fn get_vc_users(ctx: &Context, channel_id: ChannelId) -> Vec<UserId> {
-    let Some(guild) = ctx.cache.guild(GUILD) else {
+fn get_vc_users(ctx: &Context, guild_id: GuildId, channel_id: ChannelId) -> Vec<UserId> {
+    let Some(guild) = ctx.cache.guild(guild_id) else {
         return vec![];
     };
 
@@ -265,8 +278,8 @@ fn get_vc_users(ctx: &Context, channel_id: ChannelId) -> Vec<UserId> {
         .collect()
 }
 
-fn vc_has_people(ctx: &Context, channel_id: ChannelId) -> bool {
-    let Some(guild) = ctx.cache.guild(GUILD) else {
+fn vc_has_people(ctx: &Context, guild_id: GuildId, channel_id: ChannelId) -> bool {
+    let Some(guild) = ctx.cache.guild(guild_id) else {
         return true;
     };
 
@@ -276,8 +289,13 @@ fn vc_has_people(ctx: &Context, channel_id: ChannelId) -> bool {
         .any(|v| v.channel_id == Some(channel_id))
 }
 
-pub(super) fn vc_has_user(ctx: &Context, channel_id: ChannelId, user_id: UserId) -> bool {
-    let Some(guild) = ctx.cache.guild(GUILD) else {
+pub(super) fn vc_has_user(
+    ctx: &Context,
+    guild_id: GuildId,
+    channel_id: ChannelId,
+    user_id: UserId,
+) -> bool {
+    let Some(guild) = ctx.cache.guild(guild_id) else {
         return true;
     };
 
@@ -287,11 +305,17 @@ pub(super) fn vc_has_user(ctx: &Context, channel_id: ChannelId, user_id: UserId)
         .any(|v| v.channel_id == Some(channel_id) && v.user_id == user_id)
 }
 
-async fn create_channel(ctx: &Context, user_id: UserId, username: FixedString<u8>) {
+async fn create_channel(
+    ctx: &Context,
+    guild_id: GuildId,
+    vc_channel: ChannelId,
+    user_id: UserId,
+    username: FixedString<u8>,
+) {
     let data = ctx.data_ref::<Data>();
 
-    let Some((position, mut overwrites, category_id)) = ctx.cache.guild(GUILD).and_then(|g| {
-        g.channels.get(&VC_CHANNEL).and_then(|c| {
+    let Some((position, mut overwrites, category_id)) = ctx.cache.guild(guild_id).and_then(|g| {
+        g.channels.get(&vc_channel).and_then(|c| {
             let position = c.position;
             let parent_id = c.parent_id?;
             let overwrites = g.channels.get(&parent_id)?.permission_overwrites.clone();
@@ -303,7 +327,7 @@ async fn create_channel(ctx: &Context, user_id: UserId, username: FixedString<u8
 
     let Some(overwrite_index) = overwrites
         .iter()
-        .position(|o| o.kind == PermissionOverwriteType::Role(GUILD.get().into()))
+        .position(|o| o.kind == PermissionOverwriteType::Role(guild_id.get().into()))
     else {
         return;
     };
@@ -332,14 +356,14 @@ async fn create_channel(ctx: &Context, user_id: UserId, username: FixedString<u8
         .position(position + 1)
         .user_limit(5.into());
 
-    if let Ok(channel) = GUILD.create_channel(&ctx.http, builder).await {
+    if let Ok(channel) = guild_id.create_channel(&ctx.http, builder).await {
         #[expect(unused_braces)]
         let _ = data
             .database
             .create_private_vc(
                 channel.id,
                 None,
-                Some(GUILD),
+                Some(guild_id),
                 user_id,
                 vec![],
                 vec![],
@@ -349,10 +373,14 @@ async fn create_channel(ctx: &Context, user_id: UserId, username: FixedString<u8
             )
             .await;
 
-        let _ = GUILD.move_member(&ctx.http, user_id, channel.id).await;
+        let _ = guild_id.move_member(&ctx.http, user_id, channel.id).await;
 
-        if let Some(msg) = data.database.get_private_vc(channel.id, Some(GUILD)).await {
-            message(ctx, channel.id, None, &msg).await;
+        if let Some(msg) = data
+            .database
+            .get_private_vc(channel.id, Some(guild_id))
+            .await
+        {
+            message(ctx, guild_id, channel.id, None, &msg).await;
         }
     }
 }
@@ -360,6 +388,7 @@ async fn create_channel(ctx: &Context, user_id: UserId, username: FixedString<u8
 #[expect(clippy::too_many_lines)] // will split out later
 pub(super) async fn message(
     ctx: &Context,
+    guild_id: GuildId,
     channel_id: ChannelId,
     message_id: Option<MessageId>,
     private_vc: &PrivateVc,
@@ -571,7 +600,7 @@ pub(super) async fn message(
             let _ = ctx
                 .data_ref::<Data>()
                 .database
-                .set_vc_message_id(channel_id, m_id, GUILD, { ctx.cache.current_user().id })
+                .set_vc_message_id(channel_id, m_id, guild_id, { ctx.cache.current_user().id })
                 .await;
         }
     } else {
@@ -582,7 +611,7 @@ pub(super) async fn message(
         let _ = ctx
             .data_ref::<Data>()
             .database
-            .set_vc_message_id(channel_id, m_id, GUILD, { ctx.cache.current_user().id })
+            .set_vc_message_id(channel_id, m_id, guild_id, { ctx.cache.current_user().id })
             .await;
     }
 }
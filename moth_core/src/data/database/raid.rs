@@ -0,0 +1,316 @@
+use std::time::Duration;
+
+use chrono::{DateTime, TimeDelta, Utc};
+use dashmap::DashMap;
+use serenity::all::{GuildId, UserId};
+use sqlx::query;
+
+use crate::data::structs::Error;
+
+/// Joins older than this are never looked at again.
+const JOIN_HISTORY: TimeDelta = TimeDelta::days(1);
+
+#[derive(Debug, Clone, Copy)]
+pub struct RaidConfig {
+    /// Accounts created within this long of each other are treated as a cluster.
+    pub age_window: Duration,
+    /// How many similar joins it takes for a signal to trip.
+    pub cluster_size: u16,
+    /// How far back joins are counted for the join rate and name/avatar signals.
+    pub join_window: Duration,
+    /// How many joins within the join window trips the join rate signal.
+    pub join_rate: u16,
+    pub raise_verification: bool,
+    pub pause_invites: bool,
+    pub timeout_cluster: bool,
+    pub timeout_for: Duration,
+}
+
+impl Default for RaidConfig {
+    fn default() -> Self {
+        Self {
+            age_window: Duration::from_secs(60 * 60 * 24),
+            cluster_size: 3,
+            join_window: Duration::from_secs(60),
+            join_rate: 10,
+            raise_verification: false,
+            pause_invites: false,
+            timeout_cluster: false,
+            timeout_for: Duration::from_secs(60 * 60),
+        }
+    }
+}
+
+impl RaidConfig {
+    /// If any automatic response is enabled.
+    #[must_use]
+    pub fn has_responses(&self) -> bool {
+        self.raise_verification || self.pause_invites || self.timeout_cluster
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RaidJoin {
+    pub user_id: UserId,
+    pub username: String,
+    pub default_avatar: bool,
+    pub joined_at: DateTime<Utc>,
+}
+
+/// Everything the automatic responses changed.
+#[derive(Debug, Clone)]
+pub struct RaidLockdown {
+    pub started_at: DateTime<Utc>,
+    /// The level before it was raised, `None` if it was left alone.
+    pub previous_verification_level: Option<u8>,
+    pub invites_paused: bool,
+    pub timed_out_users: Vec<UserId>,
+}
+
+pub(super) async fn load_raid_configs(
+    db: &sqlx::PgPool,
+) -> Result<DashMap<GuildId, RaidConfig>, Error> {
+    let rows = query!(
+        r#"
+        SELECT g.guild_id, r.age_window_secs, r.cluster_size, r.join_window_secs, r.join_rate,
+               r.raise_verification, r.pause_invites, r.timeout_cluster, r.timeout_mins
+        FROM raid_config r
+        JOIN guilds g ON r.guild_id = g.id
+        "#
+    )
+    .fetch_all(db)
+    .await?;
+
+    let map = DashMap::with_capacity(rows.len());
+    for row in rows {
+        let config = RaidConfig {
+            age_window: Duration::from_secs(row.age_window_secs as u64),
+            cluster_size: row.cluster_size as u16,
+            join_window: Duration::from_secs(row.join_window_secs as u64),
+            join_rate: row.join_rate as u16,
+            raise_verification: row.raise_verification,
+            pause_invites: row.pause_invites,
+            timeout_cluster: row.timeout_cluster,
+            timeout_for: Duration::from_secs(row.timeout_mins as u64 * 60),
+        };
+
+        map.insert(GuildId::new(row.guild_id as u64), config);
+    }
+
+    Ok(map)
+}
+
+impl super::Database {
+    /// Gets the raid config for a guild, unconfigured guilds get the defaults.
+    #[must_use]
+    pub fn get_raid_config(&self, guild_id: GuildId) -> RaidConfig {
+        self.raid_configs
+            .get(&guild_id)
+            .map(|c| *c.value())
+            .unwrap_or_default()
+    }
+
+    pub async fn set_raid_config(
+        &self,
+        guild_id: GuildId,
+        config: RaidConfig,
+    ) -> Result<(), Error> {
+        let id = self.get_guild(guild_id).await?;
+
+        query!(
+            r#"
+            INSERT INTO raid_config (guild_id, age_window_secs, cluster_size, join_window_secs,
+                                     join_rate, raise_verification, pause_invites,
+                                     timeout_cluster, timeout_mins)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            ON CONFLICT (guild_id) DO UPDATE SET
+                age_window_secs = EXCLUDED.age_window_secs,
+                cluster_size = EXCLUDED.cluster_size,
+                join_window_secs = EXCLUDED.join_window_secs,
+                join_rate = EXCLUDED.join_rate,
+                raise_verification = EXCLUDED.raise_verification,
+                pause_invites = EXCLUDED.pause_invites,
+                timeout_cluster = EXCLUDED.timeout_cluster,
+                timeout_mins = EXCLUDED.timeout_mins
+            "#,
+            id,
+            config.age_window.as_secs() as i32,
+            config.cluster_size as i16,
+            config.join_window.as_secs() as i32,
+            config.join_rate as i16,
+            config.raise_verification,
+            config.pause_invites,
+            config.timeout_cluster,
+            (config.timeout_for.as_secs() / 60) as i32
+        )
+        .execute(&self.db)
+        .await?;
+
+        self.raid_configs.insert(guild_id, config);
+
+        Ok(())
+    }
+
+    /// Every join within the last day without recording one, oldest first.
+    pub async fn get_raid_joins(&self, guild_id: GuildId) -> Result<Vec<RaidJoin>, Error> {
+        let id = self.get_guild(guild_id).await?;
+        let cutoff = Utc::now() - JOIN_HISTORY;
+
+        let rows = query!(
+            r#"
+            SELECT u.user_id, r.username, r.default_avatar, r.joined_at
+            FROM raid_joins r
+            JOIN users u ON r.user_id = u.id
+            WHERE r.guild_id = $1 AND r.joined_at >= $2
+            ORDER BY r.joined_at
+            "#,
+            id,
+            cutoff
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|r| RaidJoin {
+                user_id: UserId::new(r.user_id as u64),
+                username: r.username,
+                default_avatar: r.default_avatar,
+                joined_at: r.joined_at,
+            })
+            .collect())
+    }
+
+    /// Records a join and returns every join within the last day, including this one, oldest
+    /// first.
+    pub async fn insert_raid_join(
+        &self,
+        guild_id: GuildId,
+        join: &RaidJoin,
+    ) -> Result<Vec<RaidJoin>, Error> {
+        let id = self.get_guild(guild_id).await?;
+        let user_id = self.get_user(join.user_id).await?.id;
+        let cutoff = Utc::now() - JOIN_HISTORY;
+
+        let mut transaction = self.db.begin().await?;
+
+        query!(
+            "INSERT INTO raid_joins (guild_id, user_id, username, default_avatar, joined_at) \
+             VALUES ($1, $2, $3, $4, $5)",
+            id,
+            user_id,
+            join.username,
+            join.default_avatar,
+            join.joined_at
+        )
+        .execute(&mut *transaction)
+        .await?;
+
+        // nothing reads past the history, so there's no point keeping it.
+        query!(
+            "DELETE FROM raid_joins WHERE guild_id = $1 AND joined_at < $2",
+            id,
+            cutoff
+        )
+        .execute(&mut *transaction)
+        .await?;
+
+        let rows = query!(
+            r#"
+            SELECT u.user_id, r.username, r.default_avatar, r.joined_at
+            FROM raid_joins r
+            JOIN users u ON r.user_id = u.id
+            WHERE r.guild_id = $1
+            ORDER BY r.joined_at
+            "#,
+            id
+        )
+        .fetch_all(&mut *transaction)
+        .await?;
+
+        transaction.commit().await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|r| RaidJoin {
+                user_id: UserId::new(r.user_id as u64),
+                username: r.username,
+                default_avatar: r.default_avatar,
+                joined_at: r.joined_at,
+            })
+            .collect())
+    }
+
+    pub async fn get_raid_lockdown(
+        &self,
+        guild_id: GuildId,
+    ) -> Result<Option<RaidLockdown>, Error> {
+        let id = self.get_guild(guild_id).await?;
+
+        let row = query!(
+            "SELECT started_at, previous_verification_level, invites_paused, timed_out_users FROM \
+             raid_lockdowns WHERE guild_id = $1",
+            id
+        )
+        .fetch_optional(&self.db)
+        .await?;
+
+        Ok(row.map(|r| RaidLockdown {
+            started_at: r.started_at,
+            previous_verification_level: r.previous_verification_level.map(|l| l as u8),
+            invites_paused: r.invites_paused,
+            timed_out_users: r
+                .timed_out_users
+                .into_iter()
+                .map(|u| UserId::new(u as u64))
+                .collect(),
+        }))
+    }
+
+    /// Stores a lockdown, replacing the existing one.
+    pub async fn set_raid_lockdown(
+        &self,
+        guild_id: GuildId,
+        lockdown: &RaidLockdown,
+    ) -> Result<(), Error> {
+        let id = self.get_guild(guild_id).await?;
+        let timed_out_users = lockdown
+            .timed_out_users
+            .iter()
+            .map(|u| u.get() as i64)
+            .collect::<Vec<_>>();
+
+        query!(
+            r#"
+            INSERT INTO raid_lockdowns (guild_id, started_at, previous_verification_level,
+                                        invites_paused, timed_out_users)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (guild_id) DO UPDATE SET
+                started_at = EXCLUDED.started_at,
+                previous_verification_level = EXCLUDED.previous_verification_level,
+                invites_paused = EXCLUDED.invites_paused,
+                timed_out_users = EXCLUDED.timed_out_users
+            "#,
+            id,
+            lockdown.started_at,
+            lockdown.previous_verification_level.map(i16::from),
+            lockdown.invites_paused,
+            &timed_out_users
+        )
+        .execute(&self.db)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Removes the lockdown of a guild, once everything it changed has been undone.
+    pub async fn delete_raid_lockdown(&self, guild_id: GuildId) -> Result<(), Error> {
+        let id = self.get_guild(guild_id).await?;
+
+        query!("DELETE FROM raid_lockdowns WHERE guild_id = $1", id)
+            .execute(&self.db)
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,451 @@
+use std::{sync::Arc, time::Duration};
+
+use chrono::{DateTime, TimeDelta, Utc};
+use sqlx::query;
+use tokio::sync::Mutex;
+
+use super::Database;
+use crate::data::structs::Error;
+
+/// Milliseconds between the unix epoch and the first second of 2015, which snowflakes count from.
+const DISCORD_EPOCH: i64 = 1_420_070_400_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RetentionTable {
+    EmoteUsage,
+    StickerUsage,
+    ExecutedCommands,
+    Messages,
+    PresenceActivities,
+    PresenceStatuses,
+}
+
+impl RetentionTable {
+    pub const ALL: [RetentionTable; 6] = [
+        RetentionTable::EmoteUsage,
+        RetentionTable::StickerUsage,
+        RetentionTable::ExecutedCommands,
+        RetentionTable::Messages,
+        RetentionTable::PresenceActivities,
+        RetentionTable::PresenceStatuses,
+    ];
+
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            RetentionTable::EmoteUsage => "emote_usage",
+            RetentionTable::StickerUsage => "sticker_usage",
+            RetentionTable::ExecutedCommands => "executed_commands",
+            RetentionTable::Messages => "messages",
+            RetentionTable::PresenceActivities => "presence_activities",
+            RetentionTable::PresenceStatuses => "presence_statuses",
+        }
+    }
+
+    /// Presence history is only kept as long as members were told, even without retention on.
+    #[must_use]
+    pub fn is_presence(self) -> bool {
+        matches!(
+            self,
+            RetentionTable::PresenceActivities | RetentionTable::PresenceStatuses
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct RetentionPolicy {
+    pub table: RetentionTable,
+    /// How long raw rows are kept for.
+    pub keep_for: TimeDelta,
+    /// If pruned rows are added to the daily aggregate of the table first, messages and presence history have none.
+    pub rollup: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct RetentionConfig {
+    /// How often every policy is applied.
+    pub interval: Duration,
+    /// Rows deleted per statement, small batches keep the hot tables from being locked for long.
+    pub batch_size: i64,
+    /// Pause between batches so pruning doesn't starve everything else of the pool.
+    pub batch_delay: Duration,
+    /// Tables without a policy are never pruned.
+    pub policies: Vec<RetentionPolicy>,
+}
+
+impl Default for RetentionConfig {
+    fn default() -> Self {
+        let policy = |table: RetentionTable, days| RetentionPolicy {
+            table,
+            keep_for: TimeDelta::days(days),
+            rollup: !table.is_presence(),
+        };
+
+        Self {
+            interval: Duration::from_secs(60 * 60 * 24),
+            batch_size: 5000,
+            batch_delay: Duration::from_millis(250),
+            policies: vec![
+                policy(RetentionTable::EmoteUsage, 180),
+                policy(RetentionTable::StickerUsage, 180),
+                policy(RetentionTable::ExecutedCommands, 90),
+                policy(RetentionTable::Messages, 180),
+                policy(RetentionTable::PresenceActivities, 90),
+                policy(RetentionTable::PresenceStatuses, 90),
+            ],
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RetentionReport {
+    pub table_name: String,
+    pub cutoff: DateTime<Utc>,
+    pub rows_pruned: i64,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: DateTime<Utc>,
+}
+
+pub struct RetentionHandler {
+    config: RetentionConfig,
+    /// Held for the duration of a run so scheduled and manual runs never overlap.
+    running: Arc<Mutex<()>>,
+}
+
+impl RetentionHandler {
+    #[must_use]
+    pub fn new(config: RetentionConfig) -> Self {
+        Self {
+            config,
+            running: Arc::new(Mutex::new(())),
+        }
+    }
+
+    #[must_use]
+    pub fn config(&self) -> &RetentionConfig {
+        &self.config
+    }
+
+    /// Starts the background task, will run regardless of if an existing task is running (will not be dropped)
+    pub fn start_background_task(&self, database: Arc<Database>) {
+        self.spawn(self.config.clone(), database);
+    }
+
+    /// Starts a background task that only applies the presence policies, for when retention is
+    /// otherwise off.
+    pub fn start_presence_task(&self, database: Arc<Database>) {
+        let mut config = self.config.clone();
+        config.policies.retain(|p| p.table.is_presence());
+        self.spawn(config, database);
+    }
+
+    fn spawn(&self, config: RetentionConfig, database: Arc<Database>) {
+        let running = Arc::clone(&self.running);
+
+        tokio::spawn(async move {
+            // the first tick is delayed so pruning doesn't compete with startup.
+            let start = tokio::time::Instant::now() + Duration::from_secs(60 * 10);
+            let mut interval = tokio::time::interval_at(start, config.interval);
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+
+            loop {
+                interval.tick().await;
+
+                let _guard = running.lock().await;
+                if let Err(e) = run(&database, &config).await {
+                    println!("Retention run failed: {e}");
+                }
+            }
+        });
+    }
+
+    /// Applies every policy immediately, fails if a run is already in progress.
+    pub async fn run_now(&self, database: &Database) -> Result<Vec<RetentionReport>, Error> {
+        let Ok(_guard) = self.running.try_lock() else {
+            return Err("A retention run is already in progress.".into());
+        };
+
+        run(database, &self.config).await
+    }
+}
+
+async fn run(database: &Database, config: &RetentionConfig) -> Result<Vec<RetentionReport>, Error> {
+    // messages can only be pruned once nothing references them, so they always go last.
+    let mut policies = config.policies.clone();
+    policies.sort_by_key(|p| p.table == RetentionTable::Messages);
+
+    let mut reports = Vec::with_capacity(policies.len());
+    for policy in policies {
+        let started_at = Utc::now();
+        let cutoff = started_at - policy.keep_for;
+
+        let mut rows_pruned = 0;
+        loop {
+            let pruned = database
+                .prune_batch(policy, cutoff, config.batch_size)
+                .await?;
+            rows_pruned += pruned;
+
+            if pruned < config.batch_size {
+                break;
+            }
+
+            tokio::time::sleep(config.batch_delay).await;
+        }
+
+        // cached ids could point at rows that no longer exist.
+        if policy.table == RetentionTable::Messages && rows_pruned != 0 {
+            database.messages.invalidate_all();
+        }
+
+        let report = RetentionReport {
+            table_name: policy.table.name().to_owned(),
+            cutoff,
+            rows_pruned,
+            started_at,
+            finished_at: Utc::now(),
+        };
+        database.insert_retention_report(&report).await?;
+        reports.push(report);
+    }
+
+    Ok(reports)
+}
+
+impl Database {
+    /// Deletes up to `batch_size` rows older than `cutoff`, returning how many were deleted.
+    ///
+    /// Rows locked by another transaction are skipped and picked up by a later batch.
+    async fn prune_batch(
+        &self,
+        policy: RetentionPolicy,
+        cutoff: DateTime<Utc>,
+        batch_size: i64,
+    ) -> Result<i64, Error> {
+        let pruned = match policy.table {
+            RetentionTable::EmoteUsage => {
+                query!(
+                    r#"
+                    WITH pruned AS (
+                        DELETE FROM emote_usage
+                        WHERE id IN (
+                            SELECT id FROM emote_usage
+                            WHERE used_at < $1
+                            LIMIT $2
+                            FOR UPDATE SKIP LOCKED
+                        )
+                        RETURNING guild_id, emote_id, user_id, usage_type, used_at
+                    ),
+                    rollup AS (
+                        INSERT INTO emote_usage_daily (day, guild_id, emote_id, user_id,
+                                                       usage_type, uses)
+                        SELECT (used_at AT TIME ZONE 'UTC')::date, guild_id, emote_id, user_id,
+                               usage_type, COUNT(*)
+                        FROM pruned
+                        WHERE $3
+                        GROUP BY 1, 2, 3, 4, 5
+                        ON CONFLICT (day, guild_id, emote_id, user_id, usage_type)
+                        DO UPDATE SET uses = emote_usage_daily.uses + EXCLUDED.uses
+                    )
+                    SELECT COUNT(*) AS "count!" FROM pruned
+                    "#,
+                    cutoff,
+                    batch_size,
+                    policy.rollup
+                )
+                .fetch_one(&self.db)
+                .await?
+                .count
+            }
+            RetentionTable::StickerUsage => {
+                query!(
+                    r#"
+                    WITH pruned AS (
+                        DELETE FROM sticker_usage
+                        WHERE id IN (
+                            SELECT id FROM sticker_usage
+                            WHERE used_at < $1
+                            LIMIT $2
+                            FOR UPDATE SKIP LOCKED
+                        )
+                        RETURNING guild_id, sticker_id, user_id, used_at
+                    ),
+                    rollup AS (
+                        INSERT INTO sticker_usage_daily (day, guild_id, sticker_id, user_id, uses)
+                        SELECT (used_at AT TIME ZONE 'UTC')::date, guild_id, sticker_id, user_id,
+                               COUNT(*)
+                        FROM pruned
+                        WHERE $3
+                        GROUP BY 1, 2, 3, 4
+                        ON CONFLICT (day, guild_id, sticker_id, user_id)
+                        DO UPDATE SET uses = sticker_usage_daily.uses + EXCLUDED.uses
+                    )
+                    SELECT COUNT(*) AS "count!" FROM pruned
+                    "#,
+                    cutoff,
+                    batch_size,
+                    policy.rollup
+                )
+                .fetch_one(&self.db)
+                .await?
+                .count
+            }
+            RetentionTable::ExecutedCommands => {
+                query!(
+                    r#"
+                    WITH pruned AS (
+                        DELETE FROM executed_commands
+                        WHERE id IN (
+                            SELECT id FROM executed_commands
+                            WHERE executed_at < $1
+                            LIMIT $2
+                            FOR UPDATE SKIP LOCKED
+                        )
+                        RETURNING guild_id, user_id, command, executed_at, executed_successfully
+                    ),
+                    rollup AS (
+                        INSERT INTO executed_commands_daily (day, guild_id, user_id, command,
+                                                             uses, failures)
+                        SELECT (executed_at AT TIME ZONE 'UTC')::date, guild_id, user_id, command,
+                               COUNT(*), COUNT(*) FILTER (WHERE NOT executed_successfully)
+                        FROM pruned
+                        WHERE $3
+                        GROUP BY 1, 2, 3, 4
+                        ON CONFLICT (day, guild_id, user_id, command)
+                        DO UPDATE SET uses = executed_commands_daily.uses + EXCLUDED.uses,
+                                      failures = executed_commands_daily.failures
+                                                 + EXCLUDED.failures
+                    )
+                    SELECT COUNT(*) AS "count!" FROM pruned
+                    "#,
+                    cutoff,
+                    batch_size,
+                    policy.rollup
+                )
+                .fetch_one(&self.db)
+                .await?
+                .count
+            }
+            RetentionTable::Messages => {
+                // messages have no timestamp column, but the snowflake has the creation time.
+                let max_message_id = (cutoff.timestamp_millis() - DISCORD_EPOCH).max(0) << 22;
+
+                query!(
+                    r#"
+                    WITH pruned AS (
+                        DELETE FROM messages
+                        WHERE id IN (
+                            SELECT m.id FROM messages m
+                            WHERE m.message_id < $1
+                            AND NOT EXISTS (SELECT 1 FROM emote_usage eu WHERE eu.message_id = m.id)
+                            AND NOT EXISTS (
+                                SELECT 1 FROM sticker_usage su WHERE su.message_id = m.id
+                            )
+                            AND NOT EXISTS (
+                                SELECT 1 FROM starboard s
+                                WHERE m.id IN (s.message_id, s.starboard_message_id,
+                                               s.reply_message_id)
+                            )
+                            AND NOT EXISTS (SELECT 1 FROM private_vcs p WHERE p.message_id = m.id)
+                            LIMIT $2
+                            FOR UPDATE OF m SKIP LOCKED
+                        )
+                        RETURNING id
+                    )
+                    SELECT COUNT(*) AS "count!" FROM pruned
+                    "#,
+                    max_message_id,
+                    batch_size
+                )
+                .fetch_one(&self.db)
+                .await?
+                .count
+            }
+            RetentionTable::PresenceActivities => {
+                // open activities are still going, so only ones that ended are pruned.
+                query!(
+                    r#"
+                    WITH pruned AS (
+                        DELETE FROM presence_activities
+                        WHERE id IN (
+                            SELECT id FROM presence_activities
+                            WHERE ended_at < $1
+                            LIMIT $2
+                            FOR UPDATE SKIP LOCKED
+                        )
+                        RETURNING id
+                    )
+                    SELECT COUNT(*) AS "count!" FROM pruned
+                    "#,
+                    cutoff,
+                    batch_size
+                )
+                .fetch_one(&self.db)
+                .await?
+                .count
+            }
+            RetentionTable::PresenceStatuses => {
+                query!(
+                    r#"
+                    WITH pruned AS (
+                        DELETE FROM presence_statuses
+                        WHERE id IN (
+                            SELECT id FROM presence_statuses
+                            WHERE changed_at < $1
+                            LIMIT $2
+                            FOR UPDATE SKIP LOCKED
+                        )
+                        RETURNING id
+                    )
+                    SELECT COUNT(*) AS "count!" FROM pruned
+                    "#,
+                    cutoff,
+                    batch_size
+                )
+                .fetch_one(&self.db)
+                .await?
+                .count
+            }
+        };
+
+        Ok(pruned)
+    }
+
+    async fn insert_retention_report(&self, report: &RetentionReport) -> Result<(), Error> {
+        query!(
+            "INSERT INTO retention_runs (table_name, cutoff, rows_pruned, started_at, finished_at) \
+             VALUES ($1, $2, $3, $4, $5)",
+            report.table_name,
+            report.cutoff,
+            report.rows_pruned,
+            report.started_at,
+            report.finished_at
+        )
+        .execute(&self.db)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Gets the most recent retention reports, newest first.
+    pub async fn get_retention_reports(&self, limit: i64) -> Result<Vec<RetentionReport>, Error> {
+        let rows = query!(
+            "SELECT table_name, cutoff, rows_pruned, started_at, finished_at FROM retention_runs \
+             ORDER BY finished_at DESC LIMIT $1",
+            limit
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|r| RetentionReport {
+                table_name: r.table_name,
+                cutoff: r.cutoff,
+                rows_pruned: r.rows_pruned,
+                started_at: r.started_at,
+                finished_at: r.finished_at,
+            })
+            .collect())
+    }
+}
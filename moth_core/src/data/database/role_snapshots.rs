@@ -0,0 +1,91 @@
+use chrono::{DateTime, Utc};
+use serenity::all::{GuildId, RoleId, UserId};
+use sqlx::query;
+
+use crate::data::structs::Error;
+
+#[derive(Clone, Debug)]
+pub struct RoleSnapshot {
+    pub roles: Vec<RoleId>,
+    pub taken_at: Option<DateTime<Utc>>,
+}
+
+impl super::Database {
+    /// Stores the roles a member had, usually called when they leave the guild.
+    pub async fn insert_role_snapshot(
+        &self,
+        guild_id: GuildId,
+        user_id: UserId,
+        roles: &[RoleId],
+    ) -> Result<(), Error> {
+        let guild_id = self.get_guild(guild_id).await?;
+        let user_id = self.get_user(user_id).await?.id;
+        let roles = roles.iter().map(|r| r.get() as i64).collect::<Vec<_>>();
+
+        query!(
+            "INSERT INTO role_snapshots (user_id, guild_id, roles, snapshot_taken) VALUES ($1, \
+             $2, $3, $4)",
+            user_id,
+            guild_id,
+            &roles,
+            Utc::now(),
+        )
+        .execute(&self.db)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Gets the most recent role snapshot of a member.
+    pub async fn get_latest_role_snapshot(
+        &self,
+        guild_id: GuildId,
+        user_id: UserId,
+    ) -> Result<Option<RoleSnapshot>, Error> {
+        let guild_id = self.get_guild(guild_id).await?;
+        let user_id = self.get_user(user_id).await?.id;
+
+        let row = query!(
+            r#"
+            SELECT roles, snapshot_taken
+            FROM role_snapshots
+            WHERE user_id = $1 AND guild_id = $2
+            ORDER BY snapshot_taken DESC NULLS LAST, id DESC
+            LIMIT 1
+            "#,
+            user_id,
+            guild_id,
+        )
+        .fetch_optional(&self.db)
+        .await?;
+
+        Ok(row.map(|row| RoleSnapshot {
+            roles: row
+                .roles
+                .unwrap_or_default()
+                .into_iter()
+                .map(|r| RoleId::new(r as u64))
+                .collect(),
+            taken_at: row.snapshot_taken,
+        }))
+    }
+
+    /// Remembers the roles from a member update that the cache didn't know about, so they can
+    /// still be snapshot if the member leaves.
+    pub fn cache_member_roles(&self, guild_id: GuildId, user_id: UserId, roles: Vec<RoleId>) {
+        self.member_roles.insert((guild_id, user_id), roles);
+    }
+
+    /// Takes the roles stored by [`Self::cache_member_roles`].
+    #[must_use]
+    pub fn take_cached_member_roles(
+        &self,
+        guild_id: GuildId,
+        user_id: UserId,
+    ) -> Option<Vec<RoleId>> {
+        let key = (guild_id, user_id);
+        let roles = self.member_roles.get(&key);
+        self.member_roles.invalidate(&key);
+        roles
+    }
+}
@@ -0,0 +1,26 @@
+/// What happens to a message that breaks a guild's rules, each action includes the ones before
+/// it.
+#[derive(Debug, Clone, Copy, sqlx::Type, PartialEq, Eq, PartialOrd, Ord)]
+#[sqlx(type_name = "ruleaction", rename_all = "lowercase")]
+pub enum RuleAction {
+    /// Only posted to the log or report channel.
+    Report,
+    Delete,
+    /// Deleted and the author is told why.
+    Warn,
+    /// Deleted, warned and timed out.
+    Timeout,
+}
+
+impl RuleAction {
+    /// What was done to the message, to finish a sentence about it.
+    #[must_use]
+    pub fn taken(self) -> &'static str {
+        match self {
+            Self::Report => "reported",
+            Self::Delete => "deleted",
+            Self::Warn => "deleted and the author warned",
+            Self::Timeout => "deleted and the author timed out",
+        }
+    }
+}
@@ -0,0 +1,116 @@
+use chrono::{DateTime, Utc};
+use serenity::all::{ChannelId, GuildId, UserId};
+use sqlx::query;
+
+use crate::data::structs::Error;
+
+/// A stored change of a voice channel status.
+#[derive(Clone, Debug)]
+pub struct VcStatusChange {
+    pub channel_id: ChannelId,
+    /// `None` when the audit log didn't show who changed it.
+    pub user_id: Option<UserId>,
+    pub old_status: Option<String>,
+    pub new_status: Option<String>,
+    /// If the new status matched the blacklist.
+    pub blacklisted: bool,
+    pub changed_at: DateTime<Utc>,
+}
+
+/// Filters for searching status history, every filter is optional.
+#[derive(Clone, Debug, Default)]
+pub struct VcStatusSearch {
+    pub user: Option<UserId>,
+    pub channel: Option<ChannelId>,
+    /// Matched case insensitively against the old and new status.
+    pub text: Option<String>,
+    pub blacklisted_only: bool,
+}
+
+impl super::Database {
+    pub async fn insert_vc_status_change(
+        &self,
+        guild_id: GuildId,
+        change: &VcStatusChange,
+    ) -> Result<(), Error> {
+        let guild_id = self.get_guild(guild_id).await?;
+        let user_id = match change.user_id {
+            Some(user_id) => Some(self.get_user(user_id).await?.id),
+            None => None,
+        };
+
+        query!(
+            r#"
+            INSERT INTO vc_status_history (guild_id, channel_id, user_id, old_status, new_status,
+                                           blacklisted, changed_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            "#,
+            guild_id,
+            change.channel_id.get() as i64,
+            user_id,
+            change.old_status.as_deref(),
+            change.new_status.as_deref(),
+            change.blacklisted,
+            change.changed_at,
+        )
+        .execute(&self.db)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Searches the status history of a guild, newest first.
+    pub async fn search_vc_status_history(
+        &self,
+        guild_id: GuildId,
+        search: &VcStatusSearch,
+        limit: i64,
+    ) -> Result<Vec<VcStatusChange>, Error> {
+        let guild_id = self.get_guild(guild_id).await?;
+
+        // the text is user input, so LIKE wildcards in it are matched literally.
+        let text = search.text.as_deref().map(|t| {
+            let escaped = t
+                .replace('\\', "\\\\")
+                .replace('%', "\\%")
+                .replace('_', "\\_");
+            format!("%{escaped}%")
+        });
+
+        let rows = query!(
+            r#"
+            SELECT h.channel_id, u.user_id AS "user_id?", h.old_status, h.new_status,
+                   h.blacklisted, h.changed_at
+            FROM vc_status_history h
+            LEFT JOIN users u ON h.user_id = u.id
+            WHERE h.guild_id = $1
+                AND ($2::bigint IS NULL OR u.user_id = $2)
+                AND ($3::bigint IS NULL OR h.channel_id = $3)
+                AND ($4::text IS NULL OR h.old_status ILIKE $4 OR h.new_status ILIKE $4)
+                AND (NOT $5 OR h.blacklisted)
+            ORDER BY h.changed_at DESC
+            LIMIT $6
+            "#,
+            guild_id,
+            search.user.map(|u| u.get() as i64),
+            search.channel.map(|c| c.get() as i64),
+            text,
+            search.blacklisted_only,
+            limit,
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|r| VcStatusChange {
+                channel_id: ChannelId::new(r.channel_id as u64),
+                user_id: r.user_id.map(|u| UserId::new(u as u64)),
+                old_status: r.old_status,
+                new_status: r.new_status,
+                blacklisted: r.blacklisted,
+                changed_at: r.changed_at,
+            })
+            .collect())
+    }
+}
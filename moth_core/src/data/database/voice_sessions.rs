@@ -0,0 +1,438 @@
+use chrono::{DateTime, Utc};
+use serenity::all::{ChannelId, GuildId, UserId};
+use sqlx::query;
+
+use crate::data::structs::Error;
+
+/// A session that is currently being tracked, mute and deafen time is only written out when the
+/// session ends.
+#[derive(Clone, Debug)]
+pub struct OpenVoiceSession {
+    id: i64,
+    pub channel_id: ChannelId,
+    pub started_at: DateTime<Utc>,
+    muted_since: Option<DateTime<Utc>>,
+    deafened_since: Option<DateTime<Utc>>,
+    muted_secs: i64,
+    deafened_secs: i64,
+}
+
+impl OpenVoiceSession {
+    fn set_state(&mut self, now: DateTime<Utc>, muted: bool, deafened: bool) {
+        match (self.muted_since, muted) {
+            (None, true) => self.muted_since = Some(now),
+            (Some(since), false) => {
+                self.muted_secs += (now - since).num_seconds();
+                self.muted_since = None;
+            }
+            _ => {}
+        }
+
+        match (self.deafened_since, deafened) {
+            (None, true) => self.deafened_since = Some(now),
+            (Some(since), false) => {
+                self.deafened_secs += (now - since).num_seconds();
+                self.deafened_since = None;
+            }
+            _ => {}
+        }
+    }
+}
+
+/// A member's voice state as seen in the cache, used to recover sessions.
+#[derive(Clone, Copy, Debug)]
+pub struct VoiceSnapshot {
+    pub user_id: UserId,
+    pub channel_id: ChannelId,
+    pub muted: bool,
+    pub deafened: bool,
+}
+
+/// Time spent in voice, in seconds.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct VoiceTotals {
+    pub sessions: i64,
+    pub total_secs: i64,
+    pub muted_secs: i64,
+    pub deafened_secs: i64,
+}
+
+impl super::Database {
+    /// Starts a session, ending the previous one first if the member already had one open.
+    pub async fn start_voice_session(
+        &self,
+        guild_id: GuildId,
+        user_id: UserId,
+        channel_id: ChannelId,
+        muted: bool,
+        deafened: bool,
+    ) -> Result<(), Error> {
+        let now = Utc::now();
+        self.end_voice_session(guild_id, user_id).await?;
+
+        let id = self.get_guild(guild_id).await?;
+        let user = self.get_user(user_id).await?.id;
+
+        let row = query!(
+            r#"
+            INSERT INTO voice_sessions (guild_id, user_id, channel_id, started_at, last_seen_at)
+            VALUES ($1, $2, $3, $4, $4)
+            RETURNING id
+            "#,
+            id,
+            user,
+            channel_id.get() as i64,
+            now
+        )
+        .fetch_one(&self.db)
+        .await?;
+
+        self.voice_sessions.insert(
+            (guild_id, user_id),
+            OpenVoiceSession {
+                id: row.id,
+                channel_id,
+                started_at: now,
+                muted_since: muted.then_some(now),
+                deafened_since: deafened.then_some(now),
+                muted_secs: 0,
+                deafened_secs: 0,
+            },
+        );
+
+        Ok(())
+    }
+
+    /// The channel of the member's open session, if they have one.
+    #[must_use]
+    pub fn get_voice_session_channel(
+        &self,
+        guild_id: GuildId,
+        user_id: UserId,
+    ) -> Option<ChannelId> {
+        self.voice_sessions
+            .get(&(guild_id, user_id))
+            .map(|s| s.channel_id)
+    }
+
+    /// Updates the mute and deafen state of an open session.
+    pub fn update_voice_session(
+        &self,
+        guild_id: GuildId,
+        user_id: UserId,
+        muted: bool,
+        deafened: bool,
+    ) {
+        if let Some(mut session) = self.voice_sessions.get_mut(&(guild_id, user_id)) {
+            session.set_state(Utc::now(), muted, deafened);
+        }
+    }
+
+    /// Ends the member's open session, does nothing if they don't have one.
+    pub async fn end_voice_session(&self, guild_id: GuildId, user_id: UserId) -> Result<(), Error> {
+        let Some(mut session) = self
+            .voice_sessions
+            .get(&(guild_id, user_id))
+            .map(|s| s.clone())
+        else {
+            return Ok(());
+        };
+
+        let now = Utc::now();
+        session.set_state(now, false, false);
+
+        query!(
+            "UPDATE voice_sessions SET ended_at = $2, last_seen_at = $2, muted_secs = $3, \
+             deafened_secs = $4 WHERE id = $1",
+            session.id,
+            now,
+            session.muted_secs,
+            session.deafened_secs
+        )
+        .execute(&self.db)
+        .await?;
+
+        // only forgotten once the row is closed, the next session couldn't be opened otherwise.
+        self.voice_sessions.remove(&(guild_id, user_id));
+
+        Ok(())
+    }
+
+    /// Ends every session in a guild, used when the bot is removed from it.
+    pub async fn end_guild_voice_sessions(&self, guild_id: GuildId) -> Result<(), Error> {
+        let users = self
+            .voice_sessions
+            .iter()
+            .filter(|s| s.key().0 == guild_id)
+            .map(|s| s.key().1)
+            .collect::<Vec<_>>();
+
+        for user_id in users {
+            self.end_voice_session(guild_id, user_id).await?;
+        }
+
+        // sessions that were never recovered aren't tracked.
+        let id = self.get_guild(guild_id).await?;
+        query!(
+            "UPDATE voice_sessions SET ended_at = last_seen_at WHERE guild_id = $1 AND ended_at IS \
+             NULL",
+            id
+        )
+        .execute(&self.db)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Closes the sessions left open in guilds the bot isn't in anymore, which would otherwise
+    /// never be recovered.
+    pub async fn end_stale_voice_sessions(&self, guild_ids: &[GuildId]) -> Result<(), Error> {
+        let guild_ids = guild_ids.iter().map(|g| g.get() as i64).collect::<Vec<_>>();
+
+        query!(
+            r#"
+            UPDATE voice_sessions s SET ended_at = s.last_seen_at
+            FROM guilds g
+            WHERE s.guild_id = g.id AND s.ended_at IS NULL AND NOT (g.guild_id = ANY($1))
+            "#,
+            &guild_ids
+        )
+        .execute(&self.db)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Marks every tracked session as still ongoing, sessions of guilds that are unavailable keep
+    /// the time they were last seen.
+    pub async fn touch_voice_sessions(&self) -> Result<(), Error> {
+        let ids = self.voice_sessions.iter().map(|s| s.id).collect::<Vec<_>>();
+
+        query!(
+            "UPDATE voice_sessions SET last_seen_at = NOW() WHERE id = ANY($1)",
+            &ids
+        )
+        .execute(&self.db)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Reconciles the sessions of a guild with its current voice states, called whenever a guild
+    /// becomes available.
+    ///
+    /// Sessions left open by a restart are resumed when the member is still in the same channel
+    /// and otherwise closed when they were last seen, members in voice without a session get a
+    /// new one.
+    pub async fn recover_voice_sessions(
+        &self,
+        guild_id: GuildId,
+        states: &[VoiceSnapshot],
+    ) -> Result<(), Error> {
+        let now = Utc::now();
+        let id = self.get_guild(guild_id).await?;
+
+        let rows = query!(
+            r#"
+            SELECT s.id, u.user_id, s.channel_id, s.started_at, s.muted_secs, s.deafened_secs
+            FROM voice_sessions s
+            JOIN users u ON s.user_id = u.id
+            WHERE s.guild_id = $1 AND s.ended_at IS NULL
+            "#,
+            id
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        for row in rows {
+            let user_id = UserId::new(row.user_id as u64);
+            let channel_id = ChannelId::new(row.channel_id as u64);
+            let state = states.iter().find(|s| s.user_id == user_id);
+
+            if self.voice_sessions.contains_key(&(guild_id, user_id)) {
+                // tracked live, so anything that changed did so while the guild was unavailable.
+                if state.is_none_or(|s| s.channel_id != channel_id) {
+                    self.end_voice_session(guild_id, user_id).await?;
+                }
+                continue;
+            }
+
+            if let Some(state) = state
+                && state.channel_id == channel_id
+            {
+                self.voice_sessions.insert(
+                    (guild_id, user_id),
+                    OpenVoiceSession {
+                        id: row.id,
+                        channel_id,
+                        started_at: row.started_at,
+                        muted_since: state.muted.then_some(now),
+                        deafened_since: state.deafened.then_some(now),
+                        muted_secs: row.muted_secs,
+                        deafened_secs: row.deafened_secs,
+                    },
+                );
+                continue;
+            }
+
+            query!(
+                "UPDATE voice_sessions SET ended_at = last_seen_at WHERE id = $1",
+                row.id
+            )
+            .execute(&self.db)
+            .await?;
+        }
+
+        for state in states {
+            if self
+                .get_voice_session_channel(guild_id, state.user_id)
+                .is_none()
+            {
+                self.start_voice_session(
+                    guild_id,
+                    state.user_id,
+                    state.channel_id,
+                    state.muted,
+                    state.deafened,
+                )
+                .await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// A member's voice time per channel since `since`, most used channel first.
+    ///
+    /// Session length is clipped to the window, mute and deafen time is counted for every
+    /// session overlapping it.
+    pub async fn get_user_voice_stats(
+        &self,
+        guild_id: GuildId,
+        user_id: UserId,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<(ChannelId, VoiceTotals)>, Error> {
+        let id = self.get_guild(guild_id).await?;
+        let user = self.get_user(user_id).await?.id;
+
+        let rows = query!(
+            r#"
+            SELECT
+                channel_id,
+                COUNT(*) AS "sessions!",
+                SUM(EXTRACT(EPOCH FROM COALESCE(ended_at, NOW()) - GREATEST(started_at, $3)))::BIGINT
+                    AS "total_secs!",
+                SUM(muted_secs)::BIGINT AS "muted_secs!",
+                SUM(deafened_secs)::BIGINT AS "deafened_secs!"
+            FROM voice_sessions
+            WHERE guild_id = $1 AND user_id = $2 AND COALESCE(ended_at, NOW()) > $3
+            GROUP BY channel_id
+            ORDER BY 3 DESC
+            "#,
+            id,
+            user,
+            since
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| {
+                (
+                    ChannelId::new(row.channel_id as u64),
+                    VoiceTotals {
+                        sessions: row.sessions,
+                        total_secs: row.total_secs,
+                        muted_secs: row.muted_secs,
+                        deafened_secs: row.deafened_secs,
+                    },
+                )
+            })
+            .collect())
+    }
+
+    /// Usage of a channel since `since`, along with the members that used it the most.
+    pub async fn get_channel_voice_stats(
+        &self,
+        guild_id: GuildId,
+        channel_id: ChannelId,
+        since: DateTime<Utc>,
+        limit: i64,
+    ) -> Result<(VoiceTotals, Vec<(UserId, i64)>), Error> {
+        let id = self.get_guild(guild_id).await?;
+
+        let rows = query!(
+            r#"
+            SELECT
+                u.user_id,
+                COUNT(*) AS "sessions!",
+                SUM(EXTRACT(EPOCH FROM COALESCE(s.ended_at, NOW()) - GREATEST(s.started_at, $3)))::BIGINT
+                    AS "total_secs!",
+                SUM(s.muted_secs)::BIGINT AS "muted_secs!",
+                SUM(s.deafened_secs)::BIGINT AS "deafened_secs!"
+            FROM voice_sessions s
+            JOIN users u ON s.user_id = u.id
+            WHERE s.guild_id = $1 AND s.channel_id = $2 AND COALESCE(s.ended_at, NOW()) > $3
+            GROUP BY u.user_id
+            ORDER BY 3 DESC
+            "#,
+            id,
+            channel_id.get() as i64,
+            since
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        let mut totals = VoiceTotals::default();
+        for row in &rows {
+            totals.sessions += row.sessions;
+            totals.total_secs += row.total_secs;
+            totals.muted_secs += row.muted_secs;
+            totals.deafened_secs += row.deafened_secs;
+        }
+
+        let top = rows
+            .into_iter()
+            .take(limit as usize)
+            .map(|row| (UserId::new(row.user_id as u64), row.total_secs))
+            .collect();
+
+        Ok((totals, top))
+    }
+
+    /// The members with the most voice time since `since`.
+    pub async fn get_voice_leaderboard(
+        &self,
+        guild_id: GuildId,
+        since: DateTime<Utc>,
+        limit: i64,
+    ) -> Result<Vec<(UserId, i64)>, Error> {
+        let id = self.get_guild(guild_id).await?;
+
+        let rows = query!(
+            r#"
+            SELECT
+                u.user_id,
+                SUM(EXTRACT(EPOCH FROM COALESCE(s.ended_at, NOW()) - GREATEST(s.started_at, $2)))::BIGINT
+                    AS "total_secs!"
+            FROM voice_sessions s
+            JOIN users u ON s.user_id = u.id
+            WHERE s.guild_id = $1 AND COALESCE(s.ended_at, NOW()) > $2
+            GROUP BY u.user_id
+            ORDER BY 2 DESC
+            LIMIT $3
+            "#,
+            id,
+            since,
+            limit
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| (UserId::new(row.user_id as u64), row.total_secs))
+            .collect())
+    }
+}
@@ -1,10 +1,10 @@
 use chrono::TimeZone;
 use dashmap::DashMap;
-use parking_lot::{Mutex, RwLock};
+use parking_lot::RwLock;
 use rosu_v2::Osu;
 use serenity::all::{ChannelId, Member, RoleId, SecretString};
 use std::{
-    collections::{HashMap, HashSet, VecDeque},
+    collections::{HashMap, HashSet},
     sync::Arc,
     time::Instant,
 };
@@ -14,7 +14,9 @@ use lumi::serenity_prelude::{GenericChannelId, GuildId, MessageId, UserId};
 use std::sync::atomic::AtomicBool;
 
 use crate::{
-    data::database::{private_vcs::PrivateVcHandler, reactions::EmoteProcessor},
+    data::database::{
+        private_vcs::PrivateVcHandler, reactions::EmoteProcessor, retention::RetentionHandler,
+    },
     standby::AuthenticationStandby,
     verification::{sender::VerificationSender, task},
 };
@@ -43,12 +45,12 @@ pub struct Data {
     pub ocr_engine: crate::ocr::OcrEngine,
     /// ugh
     pub new_join_vc: DashMap<UserId, Fuck>,
-    /// ugh 2
-    pub osu_game_joins: Mutex<VecDeque<UserId>>,
     pub web: WebServer,
     pub auto_pooped: dashmap::DashSet<UserId>,
     pub emote_processor: EmoteProcessor,
     pub private_vc: PrivateVcHandler,
+    /// Prunes old usage data on a schedule.
+    pub retention: RetentionHandler,
 }
 
 pub struct WebServer {
@@ -171,15 +173,19 @@ pub struct Decay {
 
 impl AntiDeleteCache {
     /// Check if all values should be decayed and if so, decay them.
-    pub fn decay_proc(&self) {
+    pub fn decay_proc(&self, database: &crate::data::database::Database) {
         let now = Instant::now();
         let mut to_remove = vec![];
         for mut entry in self.val.iter_mut() {
+            let decay_after = database
+                .get_guild_settings(*entry.key())
+                .mass_delete_thresholds()
+                .decay_after;
+
             let guild = entry.value_mut();
-            let elapsed = now.duration_since(guild.last_update).as_secs();
-            // time without messages deleted to decay, hardcoded currently.
-            if elapsed > 5 {
-                guild.val -= 1;
+            // time without messages deleted to decay.
+            if now.duration_since(guild.last_update) > decay_after {
+                guild.val = guild.val.saturating_sub(1);
             }
             if guild.val == 0 {
                 to_remove.push(*entry.key());
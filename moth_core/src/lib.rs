@@ -14,5 +14,6 @@ pub mod config;
 pub mod data;
 pub mod emojis;
 pub mod ocr;
+pub mod prefix;
 pub mod standby;
 pub mod verification;
@@ -0,0 +1,174 @@
+use std::{sync::Arc, time::Duration};
+
+use leptess::LepTess;
+use tokio::sync::{Mutex, mpsc, oneshot};
+
+mod preprocess;
+
+/// Images larger than this shouldn't be sent for OCR, tesseract gets slow and discord screenshots
+/// are never this big anyway.
+pub const MAX_IMAGE_SIZE: u32 = 8 * 1024 * 1024;
+
+struct OcrRequest {
+    image_data: Vec<u8>,
+    response: oneshot::Sender<Result<String, OCRError>>,
+}
+
+pub struct OcrEngine {
+    sender: mpsc::Sender<OcrRequest>,
+    timeout: Duration,
+}
+
+#[derive(Debug, Clone)]
+pub struct OcrConfig {
+    /// Amount of tesseract workers, each worker processes a single image at a time.
+    pub workers: usize,
+    /// How long a request waits for a result, including time spent queued.
+    pub timeout: Duration,
+    /// Tesseract languages, multiple languages are joined with `+`, e.g. `eng+jpn`.
+    pub languages: String,
+}
+
+impl Default for OcrConfig {
+    fn default() -> Self {
+        Self {
+            workers: 2,
+            timeout: Duration::from_secs(15),
+            languages: "eng".to_owned(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum OCRError {
+    UTFError(std::str::Utf8Error),
+    PixError(leptess::leptonica::PixError),
+    ImageError(image::ImageError),
+    Timeout(Duration),
+    WorkerMissing,
+    InitError(leptess::tesseract::TessInitError),
+}
+
+impl std::fmt::Display for OCRError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match *self {
+            OCRError::UTFError(ref e) => write!(f, "{e}"),
+            OCRError::PixError(ref e) => write!(f, "{e}"),
+            OCRError::ImageError(ref e) => write!(f, "{e}"),
+            OCRError::Timeout(duration) => {
+                write!(f, "OCR request timed out after {}s.", duration.as_secs())
+            }
+            OCRError::WorkerMissing => write!(f, "Worker died, cannot process OCR request."),
+            OCRError::InitError(ref e) => write!(f, "Failed to initialize tesseract: {e:?}"),
+        }
+    }
+}
+
+impl std::error::Error for OCRError {}
+
+impl OcrEngine {
+    /// Creates a new OCR engine with a pool of workers, failing if tesseract can't be initialized
+    /// with the configured languages.
+    pub fn new(config: OcrConfig) -> Result<Self, OCRError> {
+        let workers = config.workers.max(1);
+        let (tx, rx) = mpsc::channel(workers * 5);
+        let rx = Arc::new(Mutex::new(rx));
+        let (ready_tx, ready_rx) = std::sync::mpsc::channel();
+
+        for id in 0..workers {
+            let rx = rx.clone();
+            let ready_tx = ready_tx.clone();
+            let languages = config.languages.clone();
+
+            // tesseract blocks, so every worker gets its own thread instead of a task.
+            std::thread::Builder::new()
+                .name(format!("ocr-worker-{id}"))
+                .spawn(move || match LepTess::new(None, &languages) {
+                    Ok(tess) => {
+                        let _ = ready_tx.send(Ok(()));
+                        worker(tess, &rx);
+                    }
+                    Err(e) => {
+                        let _ = ready_tx.send(Err(OCRError::InitError(e)));
+                    }
+                })
+                .expect("Failed to spawn OCR worker");
+        }
+
+        // workers that failed to start exit straight away, which closes the queue once they all
+        // have.
+        for _ in 0..workers {
+            ready_rx.recv().map_err(|_| OCRError::WorkerMissing)??;
+        }
+
+        Ok(OcrEngine {
+            sender: tx,
+            timeout: config.timeout,
+        })
+    }
+
+    /// Sends an image to be processed asynchronously and awaits the result.
+    pub async fn process(&self, image_data: Vec<u8>) -> Result<String, OCRError> {
+        let (response_tx, response_rx) = oneshot::channel();
+
+        let request = OcrRequest {
+            image_data,
+            response: response_tx,
+        };
+
+        let result = tokio::time::timeout(self.timeout, async {
+            if self.sender.send(request).await.is_err() {
+                return Err(OCRError::WorkerMissing);
+            }
+
+            match response_rx.await {
+                Ok(result) => result,
+                Err(_) => Err(OCRError::WorkerMissing),
+            }
+        })
+        .await;
+
+        result.unwrap_or(Err(OCRError::Timeout(self.timeout)))
+    }
+}
+
+fn worker(mut tess: LepTess, rx: &Mutex<mpsc::Receiver<OcrRequest>>) {
+    loop {
+        // the lock is only held while waiting, so other workers can pick up the next request.
+        let Some(OcrRequest {
+            image_data,
+            response,
+        }) = rx.blocking_lock().blocking_recv()
+        else {
+            break;
+        };
+
+        // the request already timed out while queued.
+        if response.is_closed() {
+            continue;
+        }
+
+        let image_data = match preprocess::prepare(&image_data) {
+            Ok(image_data) => image_data,
+            Err(e) => {
+                let _ = response.send(Err(e));
+                continue;
+            }
+        };
+
+        // tesseract can't be interrupted once it starts, so this is the last chance to skip
+        // requests that timed out during preprocessing.
+        if response.is_closed() {
+            continue;
+        }
+
+        let ocr_text = match tess.set_image_from_mem(&image_data) {
+            Ok(()) => tess.get_utf8_text().map_err(OCRError::UTFError),
+            Err(e) => Err(OCRError::PixError(e)),
+        };
+
+        let _ = response.send(ocr_text);
+    }
+
+    println!("OCR Worker shutting down...");
+}
@@ -0,0 +1,201 @@
+use std::io::Cursor;
+
+use image::{GrayImage, ImageFormat, ImageReader, Limits, imageops::FilterType};
+
+use super::OCRError;
+
+/// Images narrower than this are upscaled, tesseract struggles with the small text in cropped
+/// discord screenshots.
+const MIN_WIDTH: u32 = 1000;
+/// Upscaling past this doesn't help and only makes tesseract slower.
+const MAX_SCALE: u32 = 4;
+/// Images are never decoded or upscaled past this many pixels, which keeps each worker's memory
+/// use bounded.
+const MAX_PIXELS: u64 = 16_000_000;
+
+/// Prepares an image for tesseract, converting it into a black on white image.
+pub(super) fn prepare(image_data: &[u8]) -> Result<Vec<u8>, OCRError> {
+    let mut limits = Limits::default();
+    limits.max_alloc = Some(MAX_PIXELS * 4);
+
+    let mut reader = ImageReader::new(Cursor::new(image_data))
+        .with_guessed_format()
+        .map_err(|e| OCRError::ImageError(e.into()))?;
+    reader.limits(limits);
+
+    let image = reader.decode().map_err(OCRError::ImageError)?;
+    let mut gray = image.into_luma8();
+
+    if gray.width() < MIN_WIDTH && gray.width() != 0 {
+        let pixels = u64::from(gray.width()) * u64::from(gray.height());
+        let mut scale = MIN_WIDTH.div_ceil(gray.width()).min(MAX_SCALE);
+        while scale > 1 && pixels * u64::from(scale * scale) > MAX_PIXELS {
+            scale -= 1;
+        }
+        gray = image::imageops::resize(
+            &gray,
+            gray.width() * scale,
+            gray.height() * scale,
+            FilterType::CatmullRom,
+        );
+    }
+
+    threshold(&mut gray);
+
+    let mut output = Cursor::new(Vec::new());
+    gray.write_to(&mut output, ImageFormat::Png)
+        .map_err(OCRError::ImageError)?;
+
+    Ok(output.into_inner())
+}
+
+/// Binarises the image with otsu's method, inverting it when the background is dark as most
+/// screenshots use discord's dark theme.
+fn threshold(image: &mut GrayImage) {
+    let mut histogram = [0_u64; 256];
+    for pixel in image.pixels() {
+        histogram[pixel.0[0] as usize] += 1;
+    }
+
+    let total = u64::from(image.width()) * u64::from(image.height());
+    if total == 0 {
+        return;
+    }
+
+    let level = otsu_level(&histogram, total);
+
+    // more than half the image being below the threshold means it's likely a dark background.
+    let dark_pixels: u64 = histogram[..=level as usize].iter().sum();
+    let invert = dark_pixels * 2 > total;
+
+    for pixel in image.pixels_mut() {
+        let is_light = pixel.0[0] > level;
+        pixel.0[0] = if is_light != invert { 255 } else { 0 };
+    }
+}
+
+#[expect(clippy::cast_precision_loss)]
+fn otsu_level(histogram: &[u64; 256], total: u64) -> u8 {
+    let sum: f64 = histogram
+        .iter()
+        .enumerate()
+        .map(|(i, &count)| i as f64 * count as f64)
+        .sum();
+
+    let mut background_sum = 0.0;
+    let mut background_weight = 0;
+    let mut best_variance = 0.0;
+    let mut level = 0;
+
+    for (i, &count) in histogram.iter().enumerate() {
+        background_weight += count;
+        if background_weight == 0 {
+            continue;
+        }
+
+        let foreground_weight = total - background_weight;
+        if foreground_weight == 0 {
+            break;
+        }
+
+        background_sum += i as f64 * count as f64;
+
+        let background_mean = background_sum / background_weight as f64;
+        let foreground_mean = (sum - background_sum) / foreground_weight as f64;
+        let variance = background_weight as f64
+            * foreground_weight as f64
+            * (background_mean - foreground_mean).powi(2);
+
+        if variance > best_variance {
+            best_variance = variance;
+            level = i;
+        }
+    }
+
+    level as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn histogram(image: &GrayImage) -> [u64; 256] {
+        let mut histogram = [0_u64; 256];
+        for pixel in image.pixels() {
+            histogram[pixel.0[0] as usize] += 1;
+        }
+        histogram
+    }
+
+    /// A `background` image with a `foreground` block in the middle, like text on a screenshot.
+    fn text_on(background: u8, foreground: u8) -> GrayImage {
+        GrayImage::from_fn(10, 10, |x, y| {
+            let inside = (3..7).contains(&x) && (3..7).contains(&y);
+            image::Luma([if inside { foreground } else { background }])
+        })
+    }
+
+    #[test]
+    fn otsu_splits_bimodal_histogram() {
+        let mut histogram = [0_u64; 256];
+        histogram[20] = 100;
+        histogram[200] = 50;
+
+        let level = otsu_level(&histogram, 150);
+        assert!((20..200).contains(&level), "level was {level}");
+    }
+
+    #[test]
+    fn otsu_splits_spread_out_peaks() {
+        let mut histogram = [0_u64; 256];
+        histogram[10..30].fill(10);
+        histogram[180..220].fill(5);
+
+        let level = otsu_level(&histogram, 400);
+        assert!((29..180).contains(&level), "level was {level}");
+    }
+
+    #[test]
+    fn otsu_uniform_histogram_has_nothing_to_split() {
+        let mut histogram = [0_u64; 256];
+        histogram[128] = 100;
+
+        assert_eq!(otsu_level(&histogram, 100), 0);
+    }
+
+    #[test]
+    fn threshold_uniform_image_is_blank() {
+        let mut image = GrayImage::from_pixel(8, 8, image::Luma([128]));
+        threshold(&mut image);
+
+        assert!(image.pixels().all(|p| p.0[0] == 255));
+    }
+
+    #[test]
+    fn threshold_light_theme_keeps_dark_text() {
+        let mut image = text_on(230, 20);
+        threshold(&mut image);
+
+        assert_eq!(image.get_pixel(0, 0).0[0], 255);
+        assert_eq!(image.get_pixel(5, 5).0[0], 0);
+        assert_eq!(histogram(&image)[0], 16);
+    }
+
+    #[test]
+    fn threshold_dark_theme_is_inverted() {
+        let mut image = text_on(30, 220);
+        threshold(&mut image);
+
+        assert_eq!(image.get_pixel(0, 0).0[0], 255);
+        assert_eq!(image.get_pixel(5, 5).0[0], 0);
+        assert_eq!(histogram(&image)[0], 16);
+    }
+
+    #[test]
+    fn threshold_empty_image() {
+        let mut image = GrayImage::new(0, 0);
+        threshold(&mut image);
+
+        assert_eq!(image.len(), 0);
+    }
+}
@@ -0,0 +1,27 @@
+use std::{collections::HashSet, sync::OnceLock};
+
+/// Prefixes commands are invoked with, `m!` comes before `m` so it isn't split as `m` and `!`.
+pub const PREFIXES: [&str; 3] = ["-", "m!", "m"];
+
+/// The names and aliases of every prefix command, set once the framework is built.
+static COMMAND_NAMES: OnceLock<HashSet<String>> = OnceLock::new();
+
+/// Stores the names commands can be invoked with, only the first call does anything.
+pub fn set_command_names(names: impl IntoIterator<Item = String>) {
+    let _ = COMMAND_NAMES.set(names.into_iter().map(|n| n.to_lowercase()).collect());
+}
+
+/// If the content invokes a command, `m` alone is too common a start to go off the prefix.
+#[must_use]
+pub fn invokes_command(content: &str) -> bool {
+    let Some(names) = COMMAND_NAMES.get() else {
+        return false;
+    };
+
+    PREFIXES.iter().any(|prefix| {
+        content
+            .strip_prefix(prefix)
+            .and_then(|rest| rest.split_whitespace().next())
+            .is_some_and(|word| names.contains(&word.to_lowercase()))
+    })
+}
@@ -13,7 +13,7 @@ use axum::{
     routing::get,
 };
 use chrono::Utc;
-use roles::{LOG_CHANNEL, MetadataType, UserMapHolder, maybe_update};
+use roles::{MetadataType, UserMapHolder, maybe_update, verification_guild};
 use rosu_v2::{Osu, prelude::GameMode};
 use sender::VerificationCommand;
 use serenity::{
@@ -263,15 +263,17 @@ async fn handle_expired_entry(
                 .everyone(false)
                 .all_roles(false);
 
-            let _ = LOG_CHANNEL.send_message(
-                &ctx.http,
-                CreateMessage::new()
-                    .content(format!(
-                        "❌ Could not update <@{u}>'s roles due to error: (https://osu.ppy.sh/users/{})",
-                        metadata.osu_id
-                    ))
-                    .allowed_mentions(mentions),
-            ).await;
+            if let Some((_, Some(log_channel))) = verification_guild(data) {
+                let _ = log_channel.send_message(
+                    &ctx.http,
+                    CreateMessage::new()
+                        .content(format!(
+                            "❌ Could not update <@{u}>'s roles due to error: (https://osu.ppy.sh/users/{})",
+                            metadata.osu_id
+                        ))
+                        .allowed_mentions(mentions),
+                ).await;
+            }
 
             let _ = data.database.inactive_user(u).await;
         }
@@ -8,7 +8,7 @@ use serenity::all::{
     MessageFlags, RoleId, UserId,
 };
 
-use crate::data::structs::Data;
+use crate::data::{database::GuildSettings, structs::Data};
 
 struct RoleRange {
     min_rank: u32,
@@ -16,8 +16,16 @@ struct RoleRange {
     role_id: RoleId,
 }
 
-const GUILD_ID: GuildId = GuildId::new(98226572468690944);
-pub const LOG_CHANNEL: GenericChannelId = GenericChannelId::new(776522946872344586);
+/// Gets the guild verification is enabled in alongside its log channel.
+///
+/// The rank roles are specific to a single guild, so only the first guild with verification
+/// enabled is used.
+#[must_use]
+pub fn verification_guild(data: &Data) -> Option<(GuildId, Option<GenericChannelId>)> {
+    data.database
+        .find_guild_settings(GuildSettings::verification)
+        .map(|(guild_id, settings)| (guild_id, settings.verification_log_channel))
+}
 
 #[rustfmt::skip]
 const OSU_RANGES: &[RoleRange] = &[
@@ -116,6 +124,15 @@ const SPECIAL_MAPPING: &[(u32, RoleId)] = &[
     // ALM is 16, but not yet supported as not needed.
 ];
 
+/// Every role verification hands out, rank, group and mapper roles.
+pub fn verification_roles() -> impl Iterator<Item = RoleId> {
+    ALL_RANGES
+        .iter()
+        .flat_map(|ranges| ranges.iter().map(|range| range.role_id))
+        .chain(SPECIAL_MAPPING.iter().map(|(_, role_id)| *role_id))
+        .chain(UserMapHolder::all_roles())
+}
+
 #[expect(clippy::type_complexity)]
 const ALL_MAPPER_ROLES: &[(fn(&UserMapHolder) -> bool, RoleId)] = &[
     // Ranked roles
@@ -222,7 +239,12 @@ pub async fn update_roles(
         return true;
     }
 
-    let Ok(member) = ctx.http.get_member(GUILD_ID, user_id).await else {
+    let Some((guild_id, log_channel)) = verification_guild(&ctx.data::<Data>()) else {
+        println!("verification is not enabled in any guild, failing...");
+        return false;
+    };
+
+    let Ok(member) = ctx.http.get_member(guild_id, user_id).await else {
         println!("could not fetch member, failing...");
         return false;
     };
@@ -287,7 +309,7 @@ pub async fn update_roles(
         return true;
     }
 
-    if GUILD_ID
+    if guild_id
         .edit_member(
             &ctx.http,
             user_id,
@@ -313,6 +335,10 @@ pub async fn update_roles(
         )
         .await;
 
+    let Some(log_channel) = log_channel else {
+        return true;
+    };
+
     let embed = CreateEmbed::new()
         .author(
             CreateEmbedAuthor::new(user.username.as_str())
@@ -325,7 +351,7 @@ pub async fn update_roles(
     for role in new_special {
         let embed = embed.clone().field("Role", format!("<@&{role}>"), true);
 
-        let _ = LOG_CHANNEL
+        let _ = log_channel
             .send_message(
                 &ctx.http,
                 CreateMessage::new()
@@ -349,7 +375,7 @@ pub async fn update_roles(
     for role in removed_special {
         let embed = embed.clone().field("Role", format!("<@&{role}>"), true);
 
-        let _ = LOG_CHANNEL
+        let _ = log_channel
             .send_message(
                 &ctx.http,
                 CreateMessage::new()
@@ -489,7 +515,11 @@ async fn handle_maps(
 }
 
 async fn kill_roles(ctx: &serenity::all::Context, user_id: UserId) {
-    let Ok(mut member) = ctx.http.get_member(GUILD_ID, user_id).await else {
+    let Some((guild_id, _)) = verification_guild(&ctx.data::<Data>()) else {
+        return;
+    };
+
+    let Ok(mut member) = ctx.http.get_member(guild_id, user_id).await else {
         return;
     };
 
@@ -4,14 +4,19 @@ use crate::helper::{
     sort_order_to_string,
 };
 
-use crate::{Data, Error};
+use crate::{
+    Data, Error,
+    handlers::{messages::modmail, mod_log},
+};
 
+use chrono::Utc;
 use lumi::serenity_prelude::audit_log::Action::VoiceChannelStatus;
 use lumi::serenity_prelude::{
     self as serenity, ChannelFlags, ChannelId, ChannelType, CreateEmbed, ForumEmoji, GuildChannel,
     GuildId, GuildThread, PartialGuildThread, UserId, VoiceChannelStatusAction,
 };
 use moth_ansi::{BLUE, HI_BLUE, RESET};
+use moth_core::data::database::{mod_log::ModLogEvent, vc_status::VcStatusChange};
 
 use std::fmt::Write;
 use std::sync::Arc;
@@ -277,6 +282,26 @@ pub async fn channel_update(
     diff = diff.trim_end_matches('\n').to_string();
     if !diff.is_empty() {
         println!("{BLUE}[{guild_name}] #{channel_name} was updated! ({kind}){RESET}\n{diff}");
+
+        let guild_id = new.base.guild_id;
+        if mod_log::enabled(&data, guild_id, ModLogEvent::ChannelUpdate)
+            && !mod_log::is_ignored(&data, None, Some(new.base.id.widen()))
+        {
+            let embed = CreateEmbed::new()
+                .title(format!("Channel updated ({kind})"))
+                .description(format!(
+                    "<#{}>\n{}",
+                    new.base.id,
+                    mod_log::ansi_block(&diff)
+                ))
+                .footer(serenity::CreateEmbedFooter::new(format!(
+                    "Channel ID: {}",
+                    new.base.id
+                )))
+                .colour(serenity::Colour::BLUE);
+
+            mod_log::send(ctx, &data, guild_id, ModLogEvent::ChannelUpdate, embed).await;
+        }
     }
     Ok(())
 }
@@ -413,6 +438,10 @@ pub async fn thread_delete(
     let mut kind = String::new();
     let guild_name = get_guild_name_override(ctx, &data, Some(guild_id));
 
+    if let Err(e) = modmail::thread_deleted(ctx, &data, thread.id.widen()).await {
+        println!("Failed to close the modmail thread of {}: {e}", thread.id);
+    }
+
     if let Some(full_thread) = full_thread_data {
         channel_name = full_thread.base.name.to_string();
         kind = channel_type_to_string(full_thread.base.kind);
@@ -440,53 +469,21 @@ pub async fn voice_channel_status_update(
     guild_id: &GuildId,
     data: Arc<Data>,
 ) -> Result<(), Error> {
-    let vcstatus = {
-        let config = data.config.read();
-        config.vcstatus.clone()
-    };
-    if vcstatus.action {
-        let old_field: Option<String>;
-        let new_field: Option<String>;
-        match (old, status.clone()) {
-            (None, None) => {
-                old_field = None;
-                new_field = None;
-                add(ctx, id, guild_id, old_field, new_field, status, &data).await?;
-            }
-            (Some(old), Some(status)) => {
-                old_field = Some(old.to_string());
-                new_field = Some(status.clone());
-                if old_field != new_field {
-                    add(
-                        ctx,
-                        id,
-                        guild_id,
-                        old_field,
-                        new_field,
-                        &Some(status),
-                        &data,
-                    )
-                    .await?;
-                }
-            }
-            (None, Some(status)) => {
-                old_field = None;
-                new_field = Some(status.clone());
-                add(
-                    ctx,
-                    id,
-                    guild_id,
-                    old_field,
-                    new_field,
-                    &Some(status),
-                    &data,
-                )
-                .await?;
-            }
-            _ => {}
-        }
+    // discord sends updates for statuses that didn't change.
+    if old.is_some() && old == status {
+        return Ok(());
     }
-    Ok(())
+
+    add(
+        ctx,
+        id,
+        guild_id,
+        old.clone(),
+        status.clone(),
+        status,
+        &data,
+    )
+    .await
 }
 
 pub async fn add(
@@ -498,38 +495,48 @@ pub async fn add(
     status: &Option<String>,
     data: &Data,
 ) -> Result<(), Error> {
+    let changed_at = Utc::now();
+
+    // cleared statuses have their own action, without the status.
+    let action = if status.is_some() {
+        VoiceChannelStatusAction::StatusUpdate
+    } else {
+        VoiceChannelStatusAction::StatusDelete
+    };
+
     tokio::time::sleep(Duration::from_secs(2)).await;
     let logs = guild_id
         .audit_logs(
             &ctx.http,
-            Some(VoiceChannelStatus(VoiceChannelStatusAction::StatusUpdate)),
+            Some(VoiceChannelStatus(action)),
             None,
             None,
             Some(nonmax::NonMaxU8::new(5).unwrap()),
         )
-        .await?;
+        .await;
     let mut user_id: Option<UserId> = None;
 
-    for log in &logs.entries {
-        if log.options.is_none() {
-            continue;
+    // the change is still worth storing without who made it.
+    let entries: &[_] = match &logs {
+        Ok(logs) => &logs.entries,
+        Err(e) => {
+            println!("Failed to get who changed the status of {id}: {e}");
+            &[]
         }
+    };
 
-        let options = &log.options.as_ref().unwrap();
+    for log in entries {
+        let Some(options) = &log.options else {
+            continue;
+        };
 
-        if let Some(str) = &options.status
-            && str == status.as_deref().unwrap_or_default()
-            && options.channel_id == Some(id.widen())
+        if options.status.as_deref() == status.as_deref() && options.channel_id == Some(id.widen())
         {
-            user_id = Some(log.user_id.unwrap());
+            user_id = log.user_id;
             break;
         }
     }
 
-    let Some(user_id) = user_id else {
-        return Ok(());
-    };
-
     let vcstatus = {
         let config = data.config.read();
         config.vcstatus.clone()
@@ -542,6 +549,37 @@ pub async fn add(
         false
     };
 
+    let change = VcStatusChange {
+        channel_id: *id,
+        user_id,
+        old_status: old_field.clone(),
+        new_status: new_field.clone(),
+        blacklisted,
+        changed_at,
+    };
+    if let Err(e) = data
+        .database
+        .insert_vc_status_change(*guild_id, &change)
+        .await
+    {
+        println!("Failed to store the status change in {id}: {e}");
+    }
+
+    if blacklisted
+        && vcstatus.clear_blacklisted
+        && let Err(e) = clear_status(data, *id).await
+    {
+        println!("Failed to clear the blacklisted status in {id}: {e}");
+    }
+
+    let Some(user_id) = user_id else {
+        return Ok(());
+    };
+
+    if !vcstatus.action {
+        return Ok(());
+    }
+
     post_messages(
         ctx,
         data,
@@ -556,6 +594,25 @@ pub async fn add(
     Ok(())
 }
 
+/// Serenity has no support for setting voice channel statuses, so this goes through the api
+/// directly.
+async fn clear_status(data: &Data, channel_id: ChannelId) -> Result<(), Error> {
+    let token = std::env::var("MOTH_TOKEN")?;
+
+    data.reqwest
+        .put(format!(
+            "https://discord.com/api/v10/channels/{channel_id}/voice-status"
+        ))
+        .header("Authorization", format!("Bot {token}"))
+        .header("X-Audit-Log-Reason", "Blacklisted word in status")
+        .json(&serde_json::json!({ "status": "" }))
+        .send()
+        .await?
+        .error_for_status()?;
+
+    Ok(())
+}
+
 async fn check_blacklisted(msg: &str, patterns: &[regex::Regex]) -> bool {
     patterns.iter().any(|pattern| pattern.is_match(msg))
 }
@@ -1,23 +1,26 @@
 use std::{collections::HashSet, sync::Arc};
 
 mod member_roles;
+pub mod raid;
+mod role_persistence;
 pub(crate) mod roles;
 
 use std::fmt::Write;
 
 use crate::{
     Data, Error,
+    handlers::mod_log,
     helper::{get_channel_name, get_guild_name_override, get_user},
 };
 use lumi::serenity_prelude::{
-    self as serenity, AuditLogEntry, AutoModAction, ChannelId, CreateEmbedAuthor, Guild, GuildId,
-    Member, User,
+    self as serenity, AuditLogEntry, AutoModAction, ChannelId, Colour, CreateEmbed,
+    CreateEmbedAuthor, CreateEmbedFooter, Guild, GuildId, Member, UnavailableGuild, User,
 };
 
 use moth_ansi::{RESET, YELLOW};
 
-use ::serenity::all::{CreateMessage, GenericChannelId, UserId};
-use moth_core::data::structs::Fuck;
+use ::serenity::all::GenericChannelId;
+use moth_core::data::{database::mod_log::ModLogEvent, structs::Fuck};
 use serenity::model::guild::audit_log::Action;
 
 pub async fn guild_create(
@@ -33,9 +36,22 @@ pub async fn guild_create(
             ctx.cache.guilds().len()
         );
     }
+
+    crate::handlers::voice::sessions::recover(ctx, guild).await?;
+
     Ok(())
 }
 
+pub async fn guild_delete(data: Arc<Data>, guild: &UnavailableGuild) -> Result<(), Error> {
+    // outages are followed by a guild create, which recovers the sessions.
+    if guild.unavailable {
+        return Ok(());
+    }
+
+    println!("{YELLOW}Left guild (ID:{})!{RESET}", guild.id);
+    data.database.end_guild_voice_sessions(guild.id).await
+}
+
 pub async fn guild_member_addition(
     ctx: &serenity::Context,
     data: Arc<Data>,
@@ -54,6 +70,9 @@ pub async fn guild_member_addition(
         },
     );
 
+    role_persistence::restore(ctx, &data, new_member).await;
+    raid::check_join(ctx, &data, new_member).await;
+
     let guild_name = get_guild_name_override(ctx, &data, Some(guild_id));
 
     println!(
@@ -62,73 +81,36 @@ pub async fn guild_member_addition(
         new_member.user.tag(),
         joined_user_id
     );
-    Ok(())
-}
-
-pub async fn join_time(ctx: &serenity::Context, data: &Data, user_id: UserId) {
-    let mut groups = Vec::new();
 
+    if mod_log::enabled(&data, guild_id, ModLogEvent::MemberJoin)
+        && !mod_log::is_ignored(&data, Some(joined_user_id), None)
     {
-        let mut lock = data.osu_game_joins.lock();
-
-        if !lock.contains(&user_id) {
-            lock.push_back(user_id);
-        }
-
-        while lock.len() > 500 {
-            lock.pop_front();
-        }
-
-        let mut users_sorted = lock.iter().copied().collect::<Vec<_>>();
-        users_sorted.sort_by_key(|uid| uid.created_at().timestamp());
-
-        let timestamps: Vec<_> = users_sorted
-            .iter()
-            .map(|uid| uid.created_at().timestamp())
-            .collect();
-
-        let mut start = 0;
-        for end in 0..users_sorted.len() {
-            while timestamps[end] - timestamps[start] > 86400 {
-                start += 1;
-            }
-
-            if end - start + 1 > 1 {
-                groups.push(users_sorted[start..=end].to_vec());
-            }
-        }
+        let embed = CreateEmbed::new()
+            .author(CreateEmbedAuthor::new(new_member.user.tag()).icon_url(new_member.user.face()))
+            .title("Member joined")
+            .description(format!(
+                "<@{joined_user_id}>\n**Account created:** <t:{}:R>",
+                joined_user_id.created_at().timestamp()
+            ))
+            .thumbnail(new_member.user.face())
+            .footer(CreateEmbedFooter::new(format!("User ID: {joined_user_id}")))
+            .colour(Colour::DARK_GREEN);
+
+        mod_log::send(ctx, &data, guild_id, ModLogEvent::MemberJoin, embed).await;
     }
 
-    let relevant_groups: Vec<_> = groups
-        .into_iter()
-        .filter(|group| group.contains(&user_id))
-        .collect();
-
-    for group in relevant_groups {
-        let mut string = String::new();
-
-        for uid in group {
-            write!(string, "<@{uid}> ").unwrap();
-        }
-
-        let _ = GenericChannelId::new(158484765136125952)
-            .send_message(
-                &ctx.http,
-                CreateMessage::new().content(format!(
-                    "<@158567567487795200> group detected with join date within 1 day of each \
-                     other: {string}(possible raid, please watch vcs!)"
-                )),
-            )
-            .await;
-    }
+    Ok(())
 }
 
 pub async fn guild_member_removal(
     ctx: &serenity::Context,
     guild_id: &GuildId,
     user: &User,
+    member_data_if_available: Option<&Member>,
     data: Arc<Data>,
 ) -> Result<(), Error> {
+    role_persistence::snapshot(&data, *guild_id, user.id, member_data_if_available).await;
+
     let guild_name = get_guild_name_override(ctx, &data, Some(*guild_id));
 
     println!(
@@ -138,6 +120,36 @@ pub async fn guild_member_removal(
         user.id
     );
 
+    if mod_log::enabled(&data, *guild_id, ModLogEvent::MemberLeave)
+        && !mod_log::is_ignored(&data, Some(user.id), None)
+    {
+        let mut description = format!("<@{}>", user.id);
+        if let Some(member) = member_data_if_available {
+            if let Some(joined_at) = member.joined_at {
+                write!(description, "\n**Joined:** <t:{}:R>", joined_at.timestamp()).unwrap();
+            }
+
+            if !member.roles.is_empty() {
+                let roles = member
+                    .roles
+                    .iter()
+                    .map(|r| format!("<@&{r}>"))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                write!(description, "\n**Roles:** {roles}").unwrap();
+            }
+        }
+
+        let embed = CreateEmbed::new()
+            .author(CreateEmbedAuthor::new(user.tag()).icon_url(user.face()))
+            .title("Member left")
+            .description(description)
+            .footer(CreateEmbedFooter::new(format!("User ID: {}", user.id)))
+            .colour(Colour::DARK_RED);
+
+        mod_log::send(ctx, &data, *guild_id, ModLogEvent::MemberLeave, embed).await;
+    }
+
     Ok(())
 }
 
@@ -148,7 +160,13 @@ pub async fn guild_audit_log_entry_create(
 ) -> Result<(), Error> {
     member_roles::handle(ctx, entry, *guild_id).await;
 
-    if *guild_id != 98226572468690944 {
+    let data = ctx.data_ref::<Data>();
+    if let Err(e) = data.database.insert_audit_log_entry(*guild_id, entry).await {
+        println!("Failed to store audit log entry {}: {e}", entry.id);
+    }
+
+    let settings = data.database.get_guild_settings(*guild_id);
+    if !settings.automod_alerts() {
         return Ok(());
     }
 
@@ -172,16 +190,20 @@ pub async fn guild_audit_log_entry_create(
         (user.tag(), user.face())
     };
 
-    let (check_contents, culprit_channel_id): (Option<u64>, Option<ChannelId>) =
+    // the flagged status is posted in whatever channel the rule alerts in.
+    let (check_contents, culprit_channel_id): (Option<GenericChannelId>, Option<ChannelId>) =
         if let Some(options) = &entry.options {
             (
-                match &options.auto_moderation_rule_name {
-                    Some(rule_name) => match rule_name.as_str() {
-                        "Bad Words ❌ [BLOCKED]" => Some(697738506944118814),
-                        _ => None,
-                    },
-                    None => None,
-                },
+                options
+                    .auto_moderation_rule_name
+                    .as_ref()
+                    .filter(|name| {
+                        settings
+                            .automod_rule
+                            .as_ref()
+                            .is_none_or(|rule| rule.as_str() == name.as_str())
+                    })
+                    .and(settings.automod_channel),
                 options.channel_id.map(GenericChannelId::expect_channel), // culprit.
             )
         } else {
@@ -194,11 +216,11 @@ pub async fn guild_audit_log_entry_create(
 
         let mut status = format!(
             "Unknown (check #{})",
-            get_channel_name(ctx, Some(*guild_id), GenericChannelId::new(id)).await
+            get_channel_name(ctx, Some(*guild_id), id).await
         )
         .to_string();
 
-        if let Some(msgs) = ctx.cache.channel_messages(id.into()) {
+        if let Some(msgs) = ctx.cache.channel_messages(id) {
             for msg in msgs
                 .iter()
                 .rev()
@@ -220,7 +242,7 @@ pub async fn guild_audit_log_entry_create(
         let footer = serenity::CreateEmbedFooter::new(format!(
             "User ID: {} • Please check status manually in #{}",
             entry.user_id.unwrap(),
-            get_channel_name(ctx, Some(*guild_id), GenericChannelId::new(id)).await
+            get_channel_name(ctx, Some(*guild_id), id).await
         ));
         let mut embed = serenity::CreateEmbed::default()
             .author(CreateEmbedAuthor::new(author_title).icon_url(avatar_url))
@@ -234,13 +256,12 @@ pub async fn guild_audit_log_entry_create(
         let builder = serenity::CreateMessage::default()
             .embed(embed)
             .content(format!("<@{}>", entry.user_id.unwrap()));
-        // this is gg/osu only, so i won't enable configurable stuff for this.
-        GenericChannelId::new(158484765136125952)
-            .send_message(&ctx.http, builder.clone())
-            .await?;
-        GenericChannelId::new(1163544192866336808)
-            .send_message(&ctx.http, builder)
-            .await?;
+        for channel_id in [settings.staff_channel, settings.vc_status_alert_channel]
+            .into_iter()
+            .flatten()
+        {
+            channel_id.send_message(&ctx.http, builder.clone()).await?;
+        }
     }
     Ok(())
 }
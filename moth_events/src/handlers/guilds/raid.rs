@@ -0,0 +1,519 @@
+use std::{
+    collections::HashSet,
+    fmt::Write,
+    sync::{Arc, LazyLock},
+    time::{Duration, Instant},
+};
+
+use chrono::{TimeDelta, Utc};
+use dashmap::DashMap;
+use lumi::serenity_prelude::{
+    self as serenity, Colour, CreateAllowedMentions, CreateEmbed, CreateEmbedFooter, CreateMessage,
+    EditGuild, EditMember, GuildId, Member, Timestamp, UserId, VerificationLevel,
+};
+use moth_core::data::database::raid::{RaidConfig, RaidJoin, RaidLockdown};
+use tokio::sync::Mutex;
+
+use crate::{Data, Error};
+
+/// Automatic responses need at least this many signals, a single signal only alerts.
+const LOCKDOWN_SIGNALS: usize = 2;
+/// Discord won't pause invites for longer than a day.
+const INVITE_PAUSE: TimeDelta = TimeDelta::hours(24);
+/// A raid trips a signal on every join, so alerts for a guild are held back for a while.
+const ALERT_COOLDOWN: Duration = Duration::from_secs(60 * 5);
+const AUDIT_LOG_REASON: &str = "Raid detected";
+
+static LAST_ALERT: LazyLock<DashMap<GuildId, Instant>> = LazyLock::new(DashMap::new);
+/// Every join of a raid reads and writes the lockdown, so they take turns per guild.
+static LOCKDOWNS: LazyLock<DashMap<GuildId, Arc<Mutex<()>>>> = LazyLock::new(DashMap::new);
+
+enum Signal {
+    AccountAge(Vec<UserId>),
+    JoinRate(Vec<UserId>),
+    DefaultAvatars(Vec<UserId>),
+    SimilarNames(Vec<UserId>),
+}
+
+impl Signal {
+    fn users(&self) -> &[UserId] {
+        match self {
+            Signal::AccountAge(users)
+            | Signal::JoinRate(users)
+            | Signal::DefaultAvatars(users)
+            | Signal::SimilarNames(users) => users,
+        }
+    }
+
+    fn describe(&self, config: &RaidConfig) -> String {
+        let count = self.users().len();
+        match self {
+            Signal::AccountAge(_) => format!(
+                "{count} accounts created within {}h of each other",
+                config.age_window.as_secs() / 3600
+            ),
+            Signal::JoinRate(_) => format!(
+                "{count} joins in the last {}s",
+                config.join_window.as_secs()
+            ),
+            Signal::DefaultAvatars(_) => format!("{count} recent joins with default avatars"),
+            Signal::SimilarNames(_) => format!("{count} recent joins with similar usernames"),
+        }
+    }
+}
+
+/// Records the join and checks it against the recent joins of the guild, alerting staff and
+/// locking the guild down when configured to.
+pub async fn check_join(ctx: &serenity::Context, data: &Data, member: &Member) {
+    let guild_id = member.guild_id;
+    let settings = data.database.get_guild_settings(guild_id);
+    if !settings.raid_alerts() || member.user.bot() {
+        return;
+    }
+
+    let join = new_join(member);
+
+    let joins = match data.database.insert_raid_join(guild_id, &join).await {
+        Ok(joins) => joins,
+        Err(e) => {
+            println!(
+                "Failed to record join of {} in {guild_id}: {e}",
+                join.user_id
+            );
+            return;
+        }
+    };
+
+    let config = data.database.get_raid_config(guild_id);
+    let signals = detect(&config, &join, &joins);
+    if signals.is_empty() {
+        return;
+    }
+
+    let cluster = cluster(&signals);
+
+    let lock = LOCKDOWNS.entry(guild_id).or_default().clone();
+    let lockdown = {
+        let _guard = lock.lock().await;
+        respond(
+            ctx,
+            data,
+            guild_id,
+            &config,
+            &signals,
+            &cluster,
+            join.user_id,
+        )
+        .await
+    };
+    drop(lock);
+    LOCKDOWNS.remove_if(&guild_id, |_, lock| Arc::strong_count(lock) == 1);
+
+    let Ok(lockdown) = lockdown else {
+        return;
+    };
+
+    let on_cooldown = LAST_ALERT
+        .get(&guild_id)
+        .is_some_and(|last| last.elapsed() < ALERT_COOLDOWN);
+    if on_cooldown && lockdown.is_none() {
+        return;
+    }
+    LAST_ALERT.insert(guild_id, Instant::now());
+
+    let Some(alert_channel) = settings.staff_channel else {
+        return;
+    };
+
+    let mut description = String::new();
+    for signal in &signals {
+        writeln!(description, "- {}", signal.describe(&config)).unwrap();
+    }
+
+    let users = cluster
+        .iter()
+        .map(|u| format!("<@{u}>"))
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    let mut embed = CreateEmbed::new()
+        .title("Possible raid detected")
+        .description(description)
+        .field(
+            "Users",
+            crate::handlers::mod_log::field_value(&users),
+            false,
+        )
+        .footer(CreateEmbedFooter::new(
+            "Use raid all-clear to undo any automatic responses.",
+        ))
+        .colour(Colour::RED);
+
+    if let Some(lockdown) = &lockdown {
+        embed = embed.field("Automatic responses", describe_lockdown(lockdown), false);
+    }
+
+    let moderators = settings
+        .moderator_roles
+        .iter()
+        .map(|r| format!("<@&{r}>"))
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    let builder = CreateMessage::new()
+        .content(moderators)
+        .embed(embed)
+        .allowed_mentions(CreateAllowedMentions::new().roles(settings.moderator_roles.clone()));
+    let _ = alert_channel.send_message(&ctx.http, builder).await;
+}
+
+/// Checks a member as if they had just joined, without recording the join or responding to it.
+/// Returns the signals the join would trip.
+pub async fn dry_run(data: &Data, member: &Member) -> Result<Vec<String>, Error> {
+    let join = new_join(member);
+    let mut joins = data.database.get_raid_joins(member.guild_id).await?;
+    joins.push(join.clone());
+
+    let config = data.database.get_raid_config(member.guild_id);
+    Ok(detect(&config, &join, &joins)
+        .iter()
+        .map(|s| s.describe(&config))
+        .collect())
+}
+
+fn new_join(member: &Member) -> RaidJoin {
+    RaidJoin {
+        user_id: member.user.id,
+        username: member.user.name.to_string(),
+        default_avatar: member.user.avatar.is_none(),
+        joined_at: Utc::now(),
+    }
+}
+
+/// Locks the guild down if it isn't already, otherwise times out the new member along with the
+/// rest of the raid. `Err` when the join was handled by an existing lockdown and shouldn't alert.
+async fn respond(
+    ctx: &serenity::Context,
+    data: &Data,
+    guild_id: GuildId,
+    config: &RaidConfig,
+    signals: &[Signal],
+    cluster: &[UserId],
+    user_id: UserId,
+) -> Result<Option<RaidLockdown>, ()> {
+    let lockdown = match data.database.get_raid_lockdown(guild_id).await {
+        Ok(lockdown) => lockdown,
+        Err(e) => {
+            println!("Failed to get raid lockdown of {guild_id}: {e}");
+            return Err(());
+        }
+    };
+
+    // already locked down, anyone else from the raid just joins the timed out cluster.
+    if let Some(mut lockdown) = lockdown {
+        if config.timeout_cluster
+            && !lockdown.timed_out_users.contains(&user_id)
+            && timeout(ctx, guild_id, user_id, config.timeout_for).await
+        {
+            lockdown.timed_out_users.push(user_id);
+            if let Err(e) = data.database.set_raid_lockdown(guild_id, &lockdown).await {
+                println!("Failed to update raid lockdown of {guild_id}: {e}");
+            }
+        }
+        return Err(());
+    }
+
+    if signals.len() >= LOCKDOWN_SIGNALS && config.has_responses() {
+        Ok(Some(lock_down(ctx, data, guild_id, config, cluster).await))
+    } else {
+        Ok(None)
+    }
+}
+
+fn detect(config: &RaidConfig, join: &RaidJoin, joins: &[RaidJoin]) -> Vec<Signal> {
+    let mut signals = Vec::new();
+    let cluster_size = usize::from(config.cluster_size);
+
+    let created_at = join.user_id.created_at().unix_timestamp();
+    let age_window = config.age_window.as_secs() as i64;
+    let same_age =
+        unique(joins.iter().filter(|j| {
+            (j.user_id.created_at().unix_timestamp() - created_at).abs() <= age_window
+        }));
+    if same_age.len() >= cluster_size {
+        signals.push(Signal::AccountAge(same_age));
+    }
+
+    let since = join.joined_at - TimeDelta::from_std(config.join_window).unwrap_or_default();
+    let recent = joins
+        .iter()
+        .filter(|j| j.joined_at >= since)
+        .collect::<Vec<_>>();
+
+    let join_rate = unique(recent.iter().copied());
+    if join_rate.len() >= usize::from(config.join_rate) {
+        signals.push(Signal::JoinRate(join_rate));
+    }
+
+    if join.default_avatar {
+        let default_avatars = unique(recent.iter().copied().filter(|j| j.default_avatar));
+        if default_avatars.len() >= cluster_size {
+            signals.push(Signal::DefaultAvatars(default_avatars));
+        }
+    }
+
+    let similar_names = unique(
+        recent
+            .iter()
+            .copied()
+            .filter(|j| similar_names(&j.username, &join.username)),
+    );
+    if similar_names.len() >= cluster_size {
+        signals.push(Signal::SimilarNames(similar_names));
+    }
+
+    signals
+}
+
+/// Every user in the signals, members that rejoined are only counted once.
+fn unique<'a>(joins: impl Iterator<Item = &'a RaidJoin>) -> Vec<UserId> {
+    let mut seen = HashSet::new();
+    joins
+        .map(|j| j.user_id)
+        .filter(|u| seen.insert(*u))
+        .collect()
+}
+
+fn cluster(signals: &[Signal]) -> Vec<UserId> {
+    let mut seen = HashSet::new();
+    signals
+        .iter()
+        .flat_map(Signal::users)
+        .copied()
+        .filter(|u| seen.insert(*u))
+        .collect()
+}
+
+/// Raid accounts tend to share a base name with a different number or separator on the end.
+fn similar_names(a: &str, b: &str) -> bool {
+    let normalise = |name: &str| {
+        name.chars()
+            .filter(char::is_ascii_alphabetic)
+            .map(|c| c.to_ascii_lowercase())
+            .collect::<Vec<_>>()
+    };
+
+    let (a, b) = (normalise(a), normalise(b));
+    if a.len() < 4 || b.len() < 4 {
+        return false;
+    }
+
+    a == b || (a.len().min(b.len()) >= 6 && edit_distance(&a, &b) <= 2)
+}
+
+fn edit_distance(a: &[char], b: &[char]) -> usize {
+    let mut previous = (0..=b.len()).collect::<Vec<_>>();
+    let mut current = vec![0; b.len() + 1];
+
+    for (i, ca) in a.iter().enumerate() {
+        current[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+
+    previous[b.len()]
+}
+
+async fn lock_down(
+    ctx: &serenity::Context,
+    data: &Data,
+    guild_id: GuildId,
+    config: &RaidConfig,
+    cluster: &[UserId],
+) -> RaidLockdown {
+    let mut lockdown = RaidLockdown {
+        started_at: Utc::now(),
+        previous_verification_level: None,
+        invites_paused: false,
+        timed_out_users: Vec::new(),
+    };
+
+    if config.raise_verification {
+        let current = ctx.cache.guild(guild_id).map(|g| g.verification_level);
+
+        if let Some(current) = current
+            && u8::from(current) < u8::from(VerificationLevel::High)
+        {
+            let builder = EditGuild::new()
+                .verification_level(VerificationLevel::High)
+                .audit_log_reason(AUDIT_LOG_REASON);
+
+            match guild_id.edit(&ctx.http, builder).await {
+                Ok(_) => lockdown.previous_verification_level = Some(u8::from(current)),
+                Err(e) => println!("Failed to raise verification level of {guild_id}: {e}"),
+            }
+        }
+    }
+
+    if config.pause_invites {
+        match pause_invites(data, guild_id, true).await {
+            Ok(()) => lockdown.invites_paused = true,
+            Err(e) => println!("Failed to pause invites of {guild_id}: {e}"),
+        }
+    }
+
+    if config.timeout_cluster {
+        for user_id in cluster {
+            if timeout(ctx, guild_id, *user_id, config.timeout_for).await {
+                lockdown.timed_out_users.push(*user_id);
+            }
+        }
+    }
+
+    if let Err(e) = data.database.set_raid_lockdown(guild_id, &lockdown).await {
+        println!("Failed to store raid lockdown of {guild_id}: {e}");
+    }
+
+    lockdown
+}
+
+/// Undoes everything the automatic responses changed, returning what was undone.
+pub async fn lift_lockdown(
+    ctx: &serenity::Context,
+    data: &Data,
+    guild_id: GuildId,
+) -> Result<Option<RaidLockdown>, Error> {
+    let lock = LOCKDOWNS.entry(guild_id).or_default().clone();
+    let result = {
+        let _guard = lock.lock().await;
+        lift_lockdown_locked(ctx, data, guild_id).await
+    };
+    drop(lock);
+    LOCKDOWNS.remove_if(&guild_id, |_, lock| Arc::strong_count(lock) == 1);
+
+    result
+}
+
+async fn lift_lockdown_locked(
+    ctx: &serenity::Context,
+    data: &Data,
+    guild_id: GuildId,
+) -> Result<Option<RaidLockdown>, Error> {
+    let Some(lockdown) = data.database.get_raid_lockdown(guild_id).await? else {
+        return Ok(None);
+    };
+
+    // whatever couldn't be undone stays stored so the next all clear can try again.
+    let mut remaining = lockdown.clone();
+    if let Err(e) = undo_lockdown(ctx, data, guild_id, &mut remaining).await {
+        data.database
+            .set_raid_lockdown(guild_id, &remaining)
+            .await?;
+        return Err(e);
+    }
+
+    data.database.delete_raid_lockdown(guild_id).await?;
+
+    Ok(Some(lockdown))
+}
+
+async fn undo_lockdown(
+    ctx: &serenity::Context,
+    data: &Data,
+    guild_id: GuildId,
+    lockdown: &mut RaidLockdown,
+) -> Result<(), Error> {
+    if let Some(level) = lockdown.previous_verification_level {
+        let builder = EditGuild::new()
+            .verification_level(VerificationLevel::from(level))
+            .audit_log_reason("Raid all clear");
+        guild_id.edit(&ctx.http, builder).await?;
+        lockdown.previous_verification_level = None;
+    }
+
+    if lockdown.invites_paused {
+        pause_invites(data, guild_id, false).await?;
+        lockdown.invites_paused = false;
+    }
+
+    for user_id in std::mem::take(&mut lockdown.timed_out_users) {
+        let builder = EditMember::new()
+            .enable_communication()
+            .audit_log_reason("Raid all clear");
+        // they could've left or been banned since.
+        let _ = guild_id.edit_member(&ctx.http, user_id, builder).await;
+    }
+
+    Ok(())
+}
+
+#[must_use]
+pub fn describe_lockdown(lockdown: &RaidLockdown) -> String {
+    let mut description = String::new();
+
+    if lockdown.previous_verification_level.is_some() {
+        writeln!(description, "Raised the verification level").unwrap();
+    }
+    if lockdown.invites_paused {
+        writeln!(description, "Paused invites").unwrap();
+    }
+    if !lockdown.timed_out_users.is_empty() {
+        writeln!(
+            description,
+            "Timed out {} members",
+            lockdown.timed_out_users.len()
+        )
+        .unwrap();
+    }
+
+    if description.is_empty() {
+        description.push_str("None succeeded");
+    }
+
+    description
+}
+
+async fn timeout(
+    ctx: &serenity::Context,
+    guild_id: GuildId,
+    user_id: UserId,
+    duration: Duration,
+) -> bool {
+    let until = Utc::now() + TimeDelta::from_std(duration).unwrap_or_default();
+    let Ok(until) = Timestamp::from_unix_timestamp(until.timestamp()) else {
+        return false;
+    };
+
+    let builder = EditMember::new()
+        .disable_communication_until(until)
+        .audit_log_reason(AUDIT_LOG_REASON);
+
+    match guild_id.edit_member(&ctx.http, user_id, builder).await {
+        Ok(_) => true,
+        Err(e) => {
+            println!("Failed to time out {user_id} in {guild_id}: {e}");
+            false
+        }
+    }
+}
+
+/// Serenity has no support for incident actions, so this goes through the api directly.
+async fn pause_invites(data: &Data, guild_id: GuildId, paused: bool) -> Result<(), Error> {
+    let token = std::env::var("MOTH_TOKEN")?;
+    let until = paused.then(|| (Utc::now() + INVITE_PAUSE).to_rfc3339());
+
+    data.reqwest
+        .put(format!(
+            "https://discord.com/api/v10/guilds/{guild_id}/incident-actions"
+        ))
+        .header("Authorization", format!("Bot {token}"))
+        .json(&serde_json::json!({ "invites_disabled_until": until }))
+        .send()
+        .await?
+        .error_for_status()?;
+
+    Ok(())
+}
@@ -0,0 +1,91 @@
+use lumi::serenity_prelude::{self as serenity, GuildId, Member, RoleId, UserId};
+use moth_ansi::{MAGENTA, RESET};
+
+use crate::Data;
+
+/// Snapshots the roles of a member that just left.
+pub(super) async fn snapshot(
+    data: &Data,
+    guild_id: GuildId,
+    user_id: UserId,
+    member: Option<&Member>,
+) {
+    let cached = data.database.take_cached_member_roles(guild_id, user_id);
+    if !data
+        .database
+        .get_guild_settings(guild_id)
+        .role_persistence()
+    {
+        return;
+    }
+
+    let Some(roles) = member.map(|m| m.roles.to_vec()).or(cached) else {
+        return;
+    };
+
+    if roles.is_empty() {
+        return;
+    }
+
+    if let Err(e) = data
+        .database
+        .insert_role_snapshot(guild_id, user_id, &roles)
+        .await
+    {
+        println!("Failed to snapshot roles of {user_id} in {guild_id}: {e}");
+    }
+}
+
+/// Gives a rejoining member back any persistable roles from their last snapshot.
+pub(super) async fn restore(ctx: &serenity::Context, data: &Data, member: &Member) {
+    let guild_id = member.guild_id;
+    let settings = data.database.get_guild_settings(guild_id);
+    if !settings.role_persistence() {
+        return;
+    }
+
+    let snapshot = match data
+        .database
+        .get_latest_role_snapshot(guild_id, member.user.id)
+        .await
+    {
+        Ok(Some(snapshot)) => snapshot,
+        Ok(None) => return,
+        Err(e) => {
+            println!("Failed to get role snapshot of {}: {e}", member.user.id);
+            return;
+        }
+    };
+
+    // rank roles from an account that has since been unlinked shouldn't come back.
+    let linked = settings.verification()
+        && data
+            .database
+            .get_osu_user_id(member.user.id)
+            .await
+            .is_some();
+
+    let roles: Vec<RoleId> = snapshot
+        .roles
+        .into_iter()
+        .filter(|r| settings.is_persistable(*r, linked) && !member.roles.contains(r))
+        .collect();
+
+    for role_id in roles {
+        if let Err(e) = ctx
+            .http
+            .add_member_role(
+                guild_id,
+                member.user.id,
+                role_id,
+                Some("Restoring persisted role on rejoin."),
+            )
+            .await
+        {
+            println!(
+                "{MAGENTA}Failed to restore role {role_id} to {}: {e}{RESET}",
+                member.user.id
+            );
+        }
+    }
+}
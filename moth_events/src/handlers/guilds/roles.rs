@@ -1,6 +1,9 @@
-use crate::{helper::get_guild_name_override, Error};
+use crate::{Data, Error, handlers::mod_log, helper::get_guild_name_override};
 use moth_ansi::{HI_GREEN, MAGENTA, RED, RESET};
-use serenity::all::{Context, GuildId, Permissions, Role, RoleId};
+use moth_core::data::database::mod_log::ModLogEvent;
+use serenity::all::{
+    Colour, Context, CreateEmbed, CreateEmbedFooter, GuildId, Permissions, Role, RoleId,
+};
 
 use std::fmt::Write;
 
@@ -56,10 +59,11 @@ pub(crate) async fn role_update(
         return Ok(());
     };
 
-    let mut string = format!(
+    let header = format!(
         "{MAGENTA}[{guild_name}] A role {} (ID:{}) was updated!{RESET}",
         role.name, role.id
     );
+    let mut string = String::new();
 
     if old_role.name != role.name {
         writeln!(string, "\nname: {} -> {}", old_role.name, role.name).unwrap();
@@ -168,8 +172,22 @@ pub(crate) async fn role_update(
     }
 
     if modified {
-        string.strip_suffix('\n').unwrap_or(&string);
-        println!("{string}");
+        println!("{header}{string}");
+
+        let data = ctx.data::<Data>();
+        if mod_log::enabled(&data, role.guild_id, ModLogEvent::RoleUpdate) {
+            let embed = CreateEmbed::new()
+                .title("Role updated")
+                .description(format!(
+                    "<@&{}>\n{}",
+                    role.id,
+                    mod_log::ansi_block(string.trim())
+                ))
+                .footer(CreateEmbedFooter::new(format!("Role ID: {}", role.id)))
+                .colour(Colour::PURPLE);
+
+            mod_log::send(ctx, &data, role.guild_id, ModLogEvent::RoleUpdate, embed).await;
+        }
     }
 
     Ok(())
@@ -1,8 +1,26 @@
 use crate::Data;
+use dashmap::{DashMap, mapref::entry::Entry};
 use lumi::serenity_prelude as serenity;
 use moth_core::data::structs::{Decay, InnerCache};
-use serenity::{GenericChannelId, GetMessages, GuildId, MessageId, UserId};
-use std::{collections::HashMap, sync::Arc, time::Instant};
+use serenity::audit_log::{Action, MessageAction};
+use serenity::{GenericChannelId, GetMessages, GuildId, MessageId, Timestamp, UserId};
+use std::{
+    collections::HashMap,
+    sync::{Arc, LazyLock},
+    time::{Duration, Instant},
+};
+
+/// Discord merges repeated deletes by the same moderator into one entry, so the entry can be a
+/// few minutes older than the delete it covers.
+const AUDIT_LOG_WINDOW_SECS: i64 = 60 * 5;
+/// Heat a bulk delete adds, a purge counts as a single burst, not one delete per message.
+const BULK_DELETE_HEAT: u16 = 2;
+/// Every delete past the check heat would look the deleter up, a burst in a channel only needs
+/// one.
+const LOOKUP_COOLDOWN: Duration = Duration::from_secs(30);
+
+static LAST_LOOKUP: LazyLock<DashMap<(GuildId, GenericChannelId), Instant>> =
+    LazyLock::new(DashMap::new);
 
 async fn fetch(
     ctx: &serenity::Context,
@@ -46,6 +64,11 @@ pub async fn anti_delete(
     guild_id: GuildId,
     deleted_message_id: MessageId,
 ) -> Option<UserId> {
+    let thresholds = data
+        .database
+        .get_guild_settings(guild_id)
+        .mass_delete_thresholds();
+
     // increase value.
     {
         let Some(mut value) = data.anti_delete_cache.val.get_mut(&guild_id) else {
@@ -58,32 +81,121 @@ pub async fn anti_delete(
             );
             return None;
         };
-        if value.val > 0 && value.val < 5 {
+        if value.val < thresholds.max_heat {
             value.val += 1;
-            // low heat = no check.
-            if value.val < 3 {
-                return None;
-            }
         }
-    }
-    let last_deleted = {
-        let Some(mut value) = data.anti_delete_cache.map.get_mut(&guild_id) else {
-            fetch(ctx, channel_id, guild_id, deleted_message_id, data, false).await;
+        value.last_update = Instant::now();
+        // low heat = no check.
+        if value.val < thresholds.check_heat {
             return None;
-        };
-        let last_deleted = value.last_deleted_msg;
-        value.last_deleted_msg = deleted_message_id;
-        for (m, u) in &value.value().msg_user_cache {
-            if *m == deleted_message_id {
-                return Some(*u);
-            }
         }
-        last_deleted
-    };
-    if last_deleted < deleted_message_id {
-        fetch(ctx, channel_id, guild_id, deleted_message_id, data, true).await;
-    } else {
-        fetch(ctx, channel_id, guild_id, deleted_message_id, data, false).await;
     }
+    let last_deleted = data
+        .anti_delete_cache
+        .map
+        .get_mut(&guild_id)
+        .map(|mut value| {
+            let last_deleted = value.last_deleted_msg;
+            value.last_deleted_msg = deleted_message_id;
+            (
+                last_deleted,
+                value.msg_user_cache.get(&deleted_message_id).copied(),
+            )
+        });
+    if let Some((_, Some(user_id))) = last_deleted {
+        return Some(user_id);
+    }
+
+    // anything stored in the database has its author, which saves fetching from http.
+    if let Ok(Some(user_id)) = data.database.get_message_author(deleted_message_id).await {
+        return Some(user_id);
+    }
+
+    let fetch_newer =
+        last_deleted.is_some_and(|(last_deleted, _)| last_deleted < deleted_message_id);
+    fetch(
+        ctx,
+        channel_id,
+        guild_id,
+        deleted_message_id,
+        data,
+        fetch_newer,
+    )
+    .await;
     None
 }
+
+/// Bulk deletes need `MANAGE_MESSAGES` so they aren't attributed to anyone, but they still heat
+/// up the guild so deletes right after a purge are checked.
+pub fn anti_delete_bulk(data: &Arc<Data>, guild_id: GuildId, deleted_message_ids: &[MessageId]) {
+    {
+        let max_heat = data
+            .database
+            .get_guild_settings(guild_id)
+            .mass_delete_thresholds()
+            .max_heat;
+
+        let mut value = data.anti_delete_cache.val.entry(guild_id).or_insert(Decay {
+            val: 0,
+            last_update: Instant::now(),
+        });
+        value.val = (value.val + BULK_DELETE_HEAT).min(max_heat.max(1));
+        value.last_update = Instant::now();
+    }
+
+    // the messages are gone, so they can't be the next delete.
+    if let Some(mut value) = data.anti_delete_cache.map.get_mut(&guild_id) {
+        for id in deleted_message_ids {
+            value.msg_user_cache.remove(id);
+        }
+        if let Some(newest) = deleted_message_ids.iter().max() {
+            value.last_deleted_msg = *newest;
+        }
+    }
+}
+
+/// If a delete in the channel should look up its deleter, returns false while a lookup for the
+/// channel is on cooldown.
+pub fn should_find_deleter(guild_id: GuildId, channel_id: GenericChannelId) -> bool {
+    match LAST_LOOKUP.entry((guild_id, channel_id)) {
+        Entry::Occupied(last) if last.get().elapsed() < LOOKUP_COOLDOWN => false,
+        entry => {
+            entry.insert(Instant::now());
+            true
+        }
+    }
+}
+
+/// Finds who deleted a message from `author` through the audit log, deleting your own messages
+/// doesn't create an entry so `None` means they most likely deleted it themselves.
+pub async fn find_deleter(
+    ctx: &serenity::Context,
+    guild_id: GuildId,
+    channel_id: GenericChannelId,
+    author: UserId,
+) -> Option<UserId> {
+    // the entry isn't always there by the time the delete event is.
+    tokio::time::sleep(Duration::from_secs(2)).await;
+    let logs = guild_id
+        .audit_logs(
+            &ctx.http,
+            Some(Action::Message(MessageAction::Delete)),
+            None,
+            None,
+            Some(nonmax::NonMaxU8::new(10).unwrap()),
+        )
+        .await
+        .ok()?;
+
+    let now = Timestamp::now().unix_timestamp();
+    logs.entries.iter().find_map(|entry| {
+        let options = entry.options.as_ref()?;
+        let recent = now - entry.id.created_at().unix_timestamp() <= AUDIT_LOG_WINDOW_SECS;
+
+        (recent
+            && options.channel_id == Some(channel_id)
+            && entry.target_id.is_some_and(|t| t.get() == author.get()))
+        .then_some(entry.user_id)
+        .flatten()
+    })
+}
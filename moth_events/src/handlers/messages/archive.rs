@@ -0,0 +1,94 @@
+use chrono::Utc;
+use lumi::serenity_prelude::{self as serenity, GenericChannelId, GuildId, Message, MessageId};
+
+use crate::{Data, Error};
+
+fn is_archived(
+    ctx: &serenity::Context,
+    data: &Data,
+    guild_id: Option<GuildId>,
+    channel_id: GenericChannelId,
+) -> bool {
+    let Some(guild_id) = guild_id else {
+        return false;
+    };
+
+    let settings = data.database.get_guild_settings(guild_id);
+    if settings.is_archived(channel_id) {
+        return true;
+    }
+
+    // threads follow whatever their parent channel is set to.
+    let parent_id = ctx.cache.guild(guild_id).and_then(|guild| {
+        guild
+            .threads
+            .iter()
+            .find(|t| t.id == channel_id.expect_thread())
+            .map(|t| t.parent_id.widen())
+    });
+
+    parent_id.is_some_and(|p| settings.is_archived(p))
+}
+
+/// Stores the content a message was sent with if its channel is archived.
+pub(super) async fn archive_message(
+    ctx: &serenity::Context,
+    data: &Data,
+    msg: &Message,
+) -> Result<(), Error> {
+    if !is_archived(ctx, data, msg.guild_id, msg.channel_id) {
+        return Ok(());
+    }
+
+    data.database
+        .insert_message_revision(msg, *msg.timestamp, true)
+        .await
+}
+
+/// Stores the new content of an edited message if its channel is archived.
+pub(super) async fn archive_edit(
+    ctx: &serenity::Context,
+    data: &Data,
+    msg: &Message,
+) -> Result<(), Error> {
+    if !is_archived(ctx, data, msg.guild_id, msg.channel_id) {
+        return Ok(());
+    }
+
+    let edited_at = msg.edited_timestamp.map_or_else(Utc::now, |t| *t);
+    data.database
+        .insert_message_revision(msg, edited_at, false)
+        .await
+}
+
+pub(super) async fn archive_delete(
+    ctx: &serenity::Context,
+    data: &Data,
+    guild_id: Option<GuildId>,
+    channel_id: GenericChannelId,
+    message_id: MessageId,
+) -> Result<(), Error> {
+    if !is_archived(ctx, data, guild_id, channel_id) {
+        return Ok(());
+    }
+
+    data.database
+        .mark_message_deleted(message_id, Utc::now())
+        .await
+}
+
+pub(super) async fn archive_bulk_delete(
+    ctx: &serenity::Context,
+    data: &Data,
+    guild_id: Option<GuildId>,
+    channel_id: GenericChannelId,
+    message_ids: &[MessageId],
+) -> Result<(), Error> {
+    if !is_archived(ctx, data, guild_id, channel_id) {
+        return Ok(());
+    }
+
+    data.database
+        .mark_messages_deleted(message_ids, Utc::now())
+        .await
+}
@@ -0,0 +1,74 @@
+use std::time::Duration;
+
+use chrono::{TimeDelta, Utc};
+use lumi::serenity_prelude::{
+    self as serenity, CreateAllowedMentions, CreateMessage, EditMember, GuildId, Message, Timestamp,
+};
+use moth_core::data::database::rule_action::RuleAction;
+
+use crate::Error;
+
+/// How the author of a message is told why it was removed.
+pub(super) enum Warning {
+    /// Sent to the author, members with closed dms are still punished.
+    Dm(String),
+    /// Posted in the channel the message was in, mentioning the author.
+    Channel(String),
+}
+
+/// Takes an action against a message and its author, `reason` ends up in the audit log.
+pub(super) async fn enforce(
+    ctx: &serenity::Context,
+    msg: &Message,
+    guild_id: GuildId,
+    action: RuleAction,
+    reason: &str,
+    warning: Warning,
+    timeout_for: Duration,
+) -> Result<(), Error> {
+    // every step is attempted even if an earlier one failed, a message that was already deleted
+    // shouldn't let its author off.
+    if action >= RuleAction::Delete
+        && let Err(e) = msg.delete(&ctx.http, Some(reason)).await
+    {
+        println!("Failed to delete message in {guild_id}: {e}");
+    }
+
+    if action >= RuleAction::Warn {
+        let result = match warning {
+            Warning::Dm(content) => msg
+                .author
+                .id
+                .dm(&ctx.http, CreateMessage::new().content(content))
+                .await
+                .map(|_| ()),
+            Warning::Channel(content) => {
+                let builder = CreateMessage::new()
+                    .content(format!("<@{}>, {content}", msg.author.id))
+                    .allowed_mentions(CreateAllowedMentions::new().users(vec![msg.author.id]));
+                msg.channel_id
+                    .send_message(&ctx.http, builder)
+                    .await
+                    .map(|_| ())
+            }
+        };
+
+        if let Err(e) = result {
+            println!("Failed to warn {} in {guild_id}: {e}", msg.author.id);
+        }
+    }
+
+    if action >= RuleAction::Timeout {
+        let until = Utc::now() + TimeDelta::from_std(timeout_for).unwrap_or_default();
+        let until = Timestamp::from_unix_timestamp(until.timestamp())?;
+
+        let builder = EditMember::new()
+            .disable_communication_until(until)
+            .audit_log_reason(reason);
+        guild_id
+            .edit_member(&ctx.http, msg.author.id, builder)
+            .await?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,160 @@
+use std::sync::Arc;
+
+use lumi::serenity_prelude::{
+    self as serenity, Colour, CreateEmbed, CreateEmbedAuthor, CreateEmbedFooter, CreateMessage,
+    Message,
+};
+use moth_core::data::{
+    database::{filter_rules::FilterRules, rule_action::RuleAction},
+    structs::Data,
+};
+use moth_filter::{Severity, Verdict};
+
+use super::enforce::{Warning, enforce};
+use crate::Error;
+use crate::handlers::mod_log;
+
+/// Acts on content the filter catches in guilds with filter rules, `content` is the message or
+/// the text of an image in it. Returns if anything was done.
+pub(super) async fn moderate_content(
+    ctx: &serenity::Context,
+    data: &Arc<Data>,
+    msg: &Message,
+    content: &str,
+    verdict: Verdict,
+    from_image: bool,
+) -> Result<bool, Error> {
+    let Some(guild_id) = msg.guild_id else {
+        return Ok(false);
+    };
+
+    if msg.author.bot() || content.is_empty() {
+        return Ok(false);
+    }
+
+    let roles = msg
+        .member
+        .as_ref()
+        .map(|m| m.roles.to_vec())
+        .unwrap_or_default();
+    let Some(rules) = data
+        .database
+        .get_filter_rules(guild_id)
+        .filter(|r| !r.is_exempt(&roles, msg.channel_id))
+    else {
+        return Ok(false);
+    };
+
+    let Some((reason, action)) = rule_action(&rules, verdict) else {
+        return Ok(false);
+    };
+
+    // reports are only there for staff to look at, so they don't count against anyone.
+    let strikes = if action > RuleAction::Report {
+        Some(
+            data.database
+                .add_filter_strike(guild_id, msg.author.id, msg.id, rules.strike_window)
+                .await?,
+        )
+    } else {
+        None
+    };
+
+    let (action, timeout_for) = match strikes.and_then(|s| rules.escalation_for(s)) {
+        Some(step) if step.action >= action => (step.action, step.timeout_for),
+        _ => (action, rules.timeout_for),
+    };
+
+    let warning = Warning::Dm(format!(
+        "Your message in {} was removed for breaking the rules:\n>>> {}",
+        ctx.cache
+            .guild(guild_id)
+            .map_or_else(|| "the server".to_owned(), |g| g.name.to_string()),
+        mod_log::field_value(content)
+    ));
+    let failed = enforce(
+        ctx,
+        msg,
+        guild_id,
+        action,
+        "Caught by the filter",
+        warning,
+        timeout_for,
+    )
+    .await
+    .err();
+    if let Some(e) = &failed {
+        println!("Failed to enforce filter rules in {guild_id}: {e}");
+    }
+
+    let report_channel = rules
+        .report_channel
+        .or(data.database.get_guild_settings(guild_id).staff_channel);
+    let Some(report_channel) = report_channel else {
+        return Ok(true);
+    };
+
+    let mut action_taken = format!("Message {}", action.taken());
+    if action == RuleAction::Timeout {
+        action_taken = format!("{action_taken} for {}m", timeout_for.as_secs() / 60);
+    }
+    if failed.is_some() {
+        action_taken.push_str(" (failed)");
+    }
+
+    let title = if from_image {
+        "Image filtered"
+    } else {
+        "Message filtered"
+    };
+    let mut embed = CreateEmbed::new()
+        .author(CreateEmbedAuthor::new(msg.author.tag()).icon_url(msg.author.face()))
+        .title(title)
+        .description(mod_log::field_value(content))
+        .field("Reason", reason, true)
+        .field("Action", action_taken, true);
+    if let Some(strikes) = strikes {
+        embed = embed.field("Strikes", strikes.to_string(), true);
+    }
+    let embed = embed
+        .field(
+            "Channel",
+            format!("<#{}> ([Jump]({}))", msg.channel_id, msg.link()),
+            false,
+        )
+        .footer(CreateEmbedFooter::new(format!(
+            "User ID: {}",
+            msg.author.id
+        )))
+        .colour(Colour::RED)
+        .timestamp(msg.timestamp);
+
+    report_channel
+        .send_message(&ctx.http, CreateMessage::new().embed(embed))
+        .await?;
+
+    Ok(true)
+}
+
+/// The harshest action of every rule the verdict triggers, along with what triggered it.
+fn rule_action(rules: &FilterRules, verdict: Verdict) -> Option<(&'static str, RuleAction)> {
+    let triggered = [
+        (
+            "Severe content",
+            rules.severe_action,
+            verdict.severity == Some(Severity::Severe),
+        ),
+        (
+            "Moderate content",
+            rules.moderate_action,
+            verdict.severity.is_some(),
+        ),
+        ("Badlisted word", rules.badlist_action, verdict.badlist),
+    ];
+
+    triggered
+        .into_iter()
+        .filter(|(_, _, hit)| *hit)
+        .filter_map(|(reason, action, _)| Some((reason, action?)))
+        .max_by_key(|(_, action)| *action)
+}
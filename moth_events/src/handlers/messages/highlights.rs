@@ -0,0 +1,209 @@
+use std::{fmt::Write, sync::LazyLock, time::Duration};
+
+use lumi::serenity_prelude::{
+    self as serenity, ChannelType, CreateEmbed, CreateEmbedFooter, CreateMessage, GenericChannelId,
+    GetMessages, GuildId, Message, UserId,
+};
+use mini_moka::sync::Cache;
+use moth_core::data::structs::Data;
+
+use crate::helper::{get_channel_name, get_guild_name, truncate};
+use crate::Error;
+
+/// How long a member is left alone in a channel after being highlighted there.
+const COOLDOWN: Duration = Duration::from_secs(60 * 5);
+/// Members that spoke in a channel within this long are already reading it.
+const ACTIVE_WINDOW: Duration = Duration::from_secs(60 * 5);
+/// Messages before the highlighted one shown for context.
+const CONTEXT_MESSAGES: u8 = 3;
+
+static ACTIVE: LazyLock<Cache<(GenericChannelId, UserId), ()>> =
+    LazyLock::new(|| Cache::builder().time_to_live(ACTIVE_WINDOW).build());
+
+static COOLDOWNS: LazyLock<Cache<(GenericChannelId, UserId), ()>> =
+    LazyLock::new(|| Cache::builder().time_to_live(COOLDOWN).build());
+
+/// Dms every member with a highlight matching `content`, `content` is either the message content
+/// or text read from one of its attachments.
+pub(super) async fn check_highlights(
+    ctx: &serenity::Context,
+    data: &Data,
+    msg: &Message,
+    content: &str,
+) -> Result<(), Error> {
+    let Some(guild_id) = msg.guild_id else {
+        return Ok(());
+    };
+
+    if msg.author.bot() {
+        return Ok(());
+    }
+
+    ACTIVE.insert((msg.channel_id, msg.author.id), ());
+
+    let matches = data.database.matching_highlights(guild_id, content);
+    if matches.is_empty() {
+        return Ok(());
+    }
+
+    let mut context = None;
+    for (user_id, pattern) in matches {
+        if !should_notify(ctx, data, msg, guild_id, user_id).await {
+            continue;
+        }
+
+        // only fetched once something is going to be sent.
+        if context.is_none() {
+            let footer = format!(
+                "{} • #{}",
+                get_guild_name(ctx, Some(guild_id)),
+                get_channel_name(ctx, Some(guild_id), msg.channel_id).await
+            );
+            context = Some((build_context(ctx, msg, content).await, footer));
+        }
+        let Some((description, footer)) = &context else {
+            continue;
+        };
+
+        let embed = CreateEmbed::new()
+            .title(format!("Highlighted \"{pattern}\""))
+            .description(description.as_str())
+            .field(
+                "Source",
+                format!("[Jump to message]({})", msg.link()),
+                false,
+            )
+            .footer(CreateEmbedFooter::new(footer.as_str()))
+            .timestamp(msg.timestamp);
+
+        if user_id
+            .dm(&ctx.http, CreateMessage::new().embed(embed))
+            .await
+            .is_ok()
+        {
+            COOLDOWNS.insert((msg.channel_id, user_id), ());
+        }
+    }
+
+    Ok(())
+}
+
+async fn should_notify(
+    ctx: &serenity::Context,
+    data: &Data,
+    msg: &Message,
+    guild_id: GuildId,
+    user_id: UserId,
+) -> bool {
+    if user_id == msg.author.id {
+        return false;
+    }
+
+    let key = (msg.channel_id, user_id);
+    if ACTIVE.contains_key(&key) || COOLDOWNS.contains_key(&key) {
+        return false;
+    }
+
+    let settings = data.database.get_highlight_settings(guild_id, user_id);
+    if settings.ignored_channels.contains(&msg.channel_id)
+        || settings.ignored_users.contains(&msg.author.id)
+    {
+        return false;
+    }
+
+    can_see(ctx, guild_id, msg.channel_id, user_id).await
+}
+
+/// If the member can view the channel, threads use the permissions of their parent and private
+/// threads are only seen by their members and moderators.
+async fn can_see(
+    ctx: &serenity::Context,
+    guild_id: GuildId,
+    channel_id: GenericChannelId,
+    user_id: UserId,
+) -> bool {
+    let cached = ctx
+        .cache
+        .guild(guild_id)
+        .and_then(|g| g.members.get(&user_id).cloned());
+
+    let member = match cached {
+        Some(member) => member,
+        None => match guild_id.member(&ctx.http, user_id).await {
+            Ok(member) => member,
+            // members that left can't see anything.
+            Err(_) => return false,
+        },
+    };
+
+    // private threads also need the member to be in them, which can only be checked over http.
+    let thread_id = {
+        let Some(guild) = ctx.cache.guild(guild_id) else {
+            return false;
+        };
+
+        if let Some(channel) = guild.channels.get(&channel_id.expect_channel()) {
+            return guild.user_permissions_in(channel, &member).view_channel();
+        }
+
+        let Some(thread) = guild
+            .threads
+            .iter()
+            .find(|t| t.id == channel_id.expect_thread())
+        else {
+            return false;
+        };
+        let Some(parent) = guild.channels.get(&thread.parent_id) else {
+            return false;
+        };
+
+        let permissions = guild.user_permissions_in(parent, &member);
+        if !permissions.view_channel() {
+            return false;
+        }
+        if thread.base.kind != ChannelType::PrivateThread || permissions.manage_threads() {
+            return true;
+        }
+
+        thread.id
+    };
+
+    ctx.http
+        .get_thread_channel_member(thread_id, user_id, false)
+        .await
+        .is_ok()
+}
+
+async fn build_context(ctx: &serenity::Context, msg: &Message, content: &str) -> String {
+    let mut context = String::new();
+
+    let before = msg
+        .channel_id
+        .messages(
+            &ctx.http,
+            GetMessages::new().before(msg.id).limit(CONTEXT_MESSAGES),
+        )
+        .await
+        .unwrap_or_default();
+
+    // messages come back newest first.
+    for message in before.iter().rev() {
+        writeln!(
+            context,
+            "**{}**: {}",
+            message.author.name,
+            truncate(&message.content, 200)
+        )
+        .unwrap();
+    }
+
+    write!(
+        context,
+        "> **{}**: {}",
+        msg.author.name,
+        truncate(content, 1000)
+    )
+    .unwrap();
+
+    context
+}
@@ -0,0 +1,401 @@
+use std::{fmt::Write, sync::LazyLock};
+
+use base64::engine::{general_purpose, Engine as _};
+use lumi::serenity_prelude::Invite;
+use moth_core::data::structs::Data;
+use resvg::{tiny_skia::Pixmap, usvg::Tree};
+
+use crate::Error;
+
+const WIDTH: u32 = 430;
+const HEIGHT: u32 = 110;
+const BANNER_HEIGHT: u32 = 100;
+const ICON_SIZE: u32 = 50;
+const FONT_FAMILY: &str = "'Noto Sans', 'DejaVu Sans', sans-serif";
+/// Icons and banners are requested small, anything past this isn't a real one.
+const MAX_IMAGE_SIZE: usize = 2 * 1024 * 1024;
+
+/// Text is drawn with whatever fonts the system has, loading them is slow so it only happens once.
+static OPTIONS: LazyLock<usvg::Options<'static>> = LazyLock::new(|| {
+    let mut options = usvg::Options::default();
+    options.fontdb_mut().load_system_fonts();
+    options
+});
+
+/// Everything drawn on an invite card, separate from serenity's types so cards can be built
+/// without a real invite.
+pub(super) struct InviteCard {
+    pub code: String,
+    pub guild_name: String,
+    /// Png data of the guild icon.
+    pub icon: Option<Vec<u8>>,
+    /// Png data of the guild banner.
+    pub banner: Option<Vec<u8>>,
+    pub online: Option<u64>,
+    pub members: Option<u64>,
+    pub verified: bool,
+    pub partnered: bool,
+}
+
+impl InviteCard {
+    /// Builds a card from an invite, downloading the icon and banner from discord's cdn.
+    ///
+    /// Returns `None` for invites that aren't to a guild, such as group dm invites.
+    pub async fn from_invite(data: &Data, invite: &Invite) -> Option<Self> {
+        let guild = invite.guild.as_ref()?;
+
+        let icon = match &guild.icon {
+            Some(hash) => {
+                let url = format!(
+                    "https://cdn.discordapp.com/icons/{}/{hash}.png?size=128",
+                    guild.id
+                );
+                download(data, &url).await
+            }
+            None => None,
+        };
+        let banner = match &guild.banner {
+            Some(hash) => {
+                let url = format!(
+                    "https://cdn.discordapp.com/banners/{}/{hash}.png?size=480",
+                    guild.id
+                );
+                download(data, &url).await
+            }
+            None => None,
+        };
+
+        Self::new(invite, icon, banner)
+    }
+
+    /// Builds a card from an invite and its already downloaded images.
+    fn new(invite: &Invite, icon: Option<Vec<u8>>, banner: Option<Vec<u8>>) -> Option<Self> {
+        let guild = invite.guild.as_ref()?;
+        let has_feature = |feature: &str| guild.features.iter().any(|f| f.as_str() == feature);
+
+        Some(InviteCard {
+            code: invite.code.to_string(),
+            guild_name: guild.name.to_string(),
+            icon,
+            banner,
+            online: invite.approximate_presence_count,
+            members: invite.approximate_member_count,
+            verified: has_feature("VERIFIED"),
+            partnered: has_feature("PARTNERED"),
+        })
+    }
+
+    /// Draws the card in the style of discord's own invite embeds.
+    pub fn to_svg(&self) -> String {
+        let top = if self.banner.is_some() {
+            BANNER_HEIGHT
+        } else {
+            0
+        };
+        let height = HEIGHT + top;
+
+        let mut svg = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{WIDTH}\" height=\"{height}\" \
+             viewBox=\"0 0 {WIDTH} {height}\"><defs><clipPath id=\"card\"><rect width=\"{WIDTH}\" \
+             height=\"{height}\" rx=\"8\"/></clipPath><clipPath id=\"icon\"><rect x=\"16\" \
+             y=\"{}\" width=\"{ICON_SIZE}\" height=\"{ICON_SIZE}\" rx=\"15\"/></clipPath></defs>\
+             <g clip-path=\"url(#card)\"><rect width=\"{WIDTH}\" height=\"{height}\" \
+             fill=\"#2b2d31\"/>",
+            top + 44
+        );
+
+        if let Some(banner) = &self.banner {
+            write!(
+                svg,
+                "<image width=\"{WIDTH}\" height=\"{BANNER_HEIGHT}\" \
+                 preserveAspectRatio=\"xMidYMid slice\" href=\"{}\"/>",
+                data_uri(banner)
+            )
+            .unwrap();
+        }
+
+        write!(
+            svg,
+            "<text x=\"16\" y=\"{}\" font-family=\"{FONT_FAMILY}\" font-size=\"12\" \
+             font-weight=\"bold\" fill=\"#b5bac1\">YOU'VE BEEN INVITED TO JOIN A SERVER</text>",
+            top + 28
+        )
+        .unwrap();
+
+        if let Some(icon) = &self.icon {
+            write!(
+                svg,
+                "<image x=\"16\" y=\"{}\" width=\"{ICON_SIZE}\" height=\"{ICON_SIZE}\" \
+                 clip-path=\"url(#icon)\" href=\"{}\"/>",
+                top + 44,
+                data_uri(icon)
+            )
+            .unwrap();
+        } else {
+            // discord shows the initials of the name for guilds without an icon.
+            write!(
+                svg,
+                "<rect x=\"16\" y=\"{}\" width=\"{ICON_SIZE}\" height=\"{ICON_SIZE}\" rx=\"15\" \
+                 fill=\"#313338\"/><text x=\"{}\" y=\"{}\" font-family=\"{FONT_FAMILY}\" \
+                 font-size=\"16\" fill=\"#dbdee1\" text-anchor=\"middle\">{}</text>",
+                top + 44,
+                16 + ICON_SIZE / 2,
+                top + 44 + ICON_SIZE / 2 + 6,
+                escape(&initials(&self.guild_name))
+            )
+            .unwrap();
+        }
+
+        let mut name_x = 80;
+        if self.verified || self.partnered {
+            let colour = if self.verified { "#23a55a" } else { "#5865f2" };
+            write!(
+                svg,
+                "<circle cx=\"{}\" cy=\"{}\" r=\"7\" fill=\"{colour}\"/><path d=\"M{} {}l2 2 4-4\" \
+                 stroke=\"#ffffff\" stroke-width=\"1.5\" fill=\"none\"/>",
+                name_x + 7,
+                top + 59,
+                name_x + 4,
+                top + 59
+            )
+            .unwrap();
+            name_x += 20;
+        }
+
+        write!(
+            svg,
+            "<text x=\"{name_x}\" y=\"{}\" font-family=\"{FONT_FAMILY}\" font-size=\"16\" \
+             font-weight=\"bold\" fill=\"#f2f3f5\">{}</text>",
+            top + 64,
+            escape(&truncate(&self.guild_name, 32))
+        )
+        .unwrap();
+
+        let mut count_x = 80;
+        for (count, label, colour) in [
+            (self.online, "Online", "#23a55a"),
+            (self.members, "Members", "#80848e"),
+        ] {
+            let Some(count) = count else {
+                continue;
+            };
+
+            let text = format!("{} {label}", thousands(count));
+            write!(
+                svg,
+                "<circle cx=\"{}\" cy=\"{}\" r=\"4\" fill=\"{colour}\"/><text x=\"{}\" y=\"{}\" \
+                 font-family=\"{FONT_FAMILY}\" font-size=\"13\" fill=\"#b5bac1\">{text}</text>",
+                count_x + 4,
+                top + 82,
+                count_x + 12,
+                top + 86
+            )
+            .unwrap();
+
+            // there's no text measuring, so this is a rough width for the font size.
+            count_x += 24 + text.chars().count() as u32 * 7;
+        }
+
+        svg.push_str("</g></svg>");
+        svg
+    }
+
+    /// Renders the card into png data.
+    pub fn render(&self) -> Result<Vec<u8>, Error> {
+        convert_svg_to_png(&self.to_svg())
+    }
+}
+
+async fn download(data: &Data, url: &str) -> Option<Vec<u8>> {
+    let mut response = data.reqwest.get(url).send().await.ok()?;
+    if !response.status().is_success()
+        || response
+            .content_length()
+            .is_some_and(|len| len > MAX_IMAGE_SIZE as u64)
+    {
+        return None;
+    }
+
+    let mut bytes = Vec::new();
+    while let Some(chunk) = response.chunk().await.ok()? {
+        if bytes.len() + chunk.len() > MAX_IMAGE_SIZE {
+            return None;
+        }
+        bytes.extend_from_slice(&chunk);
+    }
+
+    Some(bytes)
+}
+
+fn data_uri(png_data: &[u8]) -> String {
+    format!(
+        "data:image/png;base64,{}",
+        general_purpose::STANDARD.encode(png_data)
+    )
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+fn truncate(text: &str, max: usize) -> String {
+    if text.chars().count() <= max {
+        return text.to_owned();
+    }
+
+    let truncated = text.chars().take(max - 1).collect::<String>();
+    format!("{}…", truncated.trim_end())
+}
+
+fn initials(name: &str) -> String {
+    name.split_whitespace()
+        .filter_map(|word| word.chars().next())
+        .take(5)
+        .collect()
+}
+
+fn thousands(count: u64) -> String {
+    let digits = count.to_string();
+    let mut formatted = String::with_capacity(digits.len() + digits.len() / 3);
+
+    for (i, c) in digits.chars().enumerate() {
+        if i != 0 && (digits.len() - i) % 3 == 0 {
+            formatted.push(',');
+        }
+        formatted.push(c);
+    }
+
+    formatted
+}
+
+fn convert_svg_to_png(svg_data: &str) -> Result<Vec<u8>, Error> {
+    let tree = Tree::from_str(svg_data, &OPTIONS)?;
+
+    let size = tree.size().to_int_size();
+    let mut pixmap = Pixmap::new(size.width(), size.height()).expect("Failed to create a pixmap");
+
+    resvg::render(
+        &tree,
+        resvg::tiny_skia::Transform::default(),
+        &mut pixmap.as_mut(),
+    );
+
+    Ok(pixmap.encode_png()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn card(guild_name: &str) -> InviteCard {
+        InviteCard {
+            code: "rust".to_owned(),
+            guild_name: guild_name.to_owned(),
+            icon: None,
+            banner: None,
+            online: Some(1234),
+            members: Some(1_234_567),
+            verified: false,
+            partnered: false,
+        }
+    }
+
+    #[test]
+    fn from_invite_without_icon_or_counts() {
+        // what discord sends for an invite fetched without counts to a guild with no icon.
+        let invite: Invite = serde_json::from_value(serde_json::json!({
+            "type": 0,
+            "code": "rust",
+            "guild": {
+                "id": "273534239310479360",
+                "name": "Rust Programming Language",
+                "splash": null,
+                "banner": null,
+                "description": null,
+                "icon": null,
+                "features": ["COMMUNITY", "VERIFIED"],
+                "verification_level": 2,
+                "vanity_url_code": null,
+                "nsfw_level": 0,
+                "premium_subscription_count": 0
+            },
+            "channel": {
+                "id": "273534239310479361",
+                "type": 0,
+                "name": "general"
+            },
+            "expires_at": null
+        }))
+        .unwrap();
+
+        let card = InviteCard::new(&invite, None, None).unwrap();
+        assert_eq!(card.code, "rust");
+        assert_eq!(card.guild_name, "Rust Programming Language");
+        assert!(card.icon.is_none() && card.banner.is_none());
+        assert_eq!((card.online, card.members), (None, None));
+        assert!(card.verified);
+        assert!(!card.partnered);
+
+        let svg = card.to_svg();
+        assert!(svg.contains(">RPL</text>"));
+        assert!(!svg.contains("Online"));
+        assert!(!svg.contains("Members"));
+    }
+
+    #[test]
+    fn escapes_guild_name() {
+        let svg = card("<Tom & \"Jerry's\">").to_svg();
+
+        assert!(svg.contains("&lt;Tom &amp; &quot;Jerry&apos;s&quot;&gt;"));
+        assert!(!svg.contains("<Tom"));
+    }
+
+    #[test]
+    fn formats_thousands() {
+        assert_eq!(thousands(0), "0");
+        assert_eq!(thousands(999), "999");
+        assert_eq!(thousands(1000), "1,000");
+        assert_eq!(thousands(1_234_567), "1,234,567");
+
+        let svg = card("Rust").to_svg();
+        assert!(svg.contains("1,234 Online"));
+        assert!(svg.contains("1,234,567 Members"));
+    }
+
+    #[test]
+    fn initials_without_icon() {
+        assert_eq!(initials("Rust Programming Language"), "RPL");
+        assert_eq!(initials("  spaced   out  "), "so");
+        assert_eq!(initials("a b c d e f g"), "abcde");
+
+        let svg = card("Rust Programming Language").to_svg();
+        assert!(svg.contains(">RPL</text>"));
+    }
+
+    #[test]
+    fn banner_adds_height() {
+        let mut card = card("Rust");
+        assert!(card.to_svg().contains(&format!("height=\"{HEIGHT}\"")));
+
+        card.banner = Some(vec![0; 4]);
+        let svg = card.to_svg();
+        assert!(svg.contains(&format!("height=\"{}\"", HEIGHT + BANNER_HEIGHT)));
+        assert!(svg.contains("data:image/png;base64,AAAAAA=="));
+    }
+
+    #[test]
+    fn renders_png() {
+        let mut card = card("Rust & Friends");
+        card.verified = true;
+        let png = card.render().unwrap();
+
+        assert!(png.starts_with(b"\x89PNG\r\n\x1a\n"));
+        // the IHDR chunk holds the size right after the signature and chunk header.
+        assert_eq!(png[16..20], WIDTH.to_be_bytes());
+        assert_eq!(png[20..24], HEIGHT.to_be_bytes());
+    }
+}
@@ -1,16 +1,49 @@
+use std::fmt::Write;
 use std::sync::{Arc, LazyLock};
 
-use lumi::serenity_prelude::{self as serenity, CreateMessage, GuildId, Message};
+use lumi::serenity_prelude::{self as serenity, CreateMessage, HttpError, Message};
 use moth_core::data::structs::Data;
 use regex::Regex;
 
-use ::serenity::all::{CreateAllowedMentions, CreateEmbedAuthor, GenericChannelId};
-use resvg::{tiny_skia::Pixmap, usvg::Tree};
+use ::serenity::all::{CreateAllowedMentions, CreateEmbedAuthor};
 
+use super::enforce::{Warning, enforce};
+use super::invite_card::InviteCard;
 use crate::Error;
 
-pub static INVITE: LazyLock<Regex> =
-    LazyLock::new(|| Regex::new(r"discord(?:(?:app)?\.com/invite|\.gg)/([a-zA-Z0-9-]+)").unwrap());
+pub static INVITE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?i)discord(?:app)?\s*(?:\.|\(dot\)|\[dot\]|\[\.\]|\sdot\s)\s*(?:gg(?:\s*/\s*invite)?|com\s*/\s*invite)\s*/\s*([a-z0-9-]+)",
+    )
+    .unwrap()
+});
+
+/// Every invite code in the content, including invites hidden behind markdown, zero width
+/// characters or spaced out links.
+#[must_use]
+pub fn invite_codes(content: &str) -> Vec<String> {
+    // none of these can be in an invite code, so they're only there to break up the link.
+    let normalised = content
+        .chars()
+        .filter(|c| {
+            !matches!(
+                c,
+                '\u{200b}'
+                    ..='\u{200d}' | '\u{2060}' | '\u{feff}' | '*' | '_' | '~' | '|' | '`' | '\\'
+            )
+        })
+        .collect::<String>();
+
+    let mut codes = Vec::new();
+    for captures in INVITE.captures_iter(&normalised) {
+        let code = captures[1].to_owned();
+        if !codes.contains(&code) {
+            codes.push(code);
+        }
+    }
+
+    codes
+}
 
 pub async fn moderate_invites(
     ctx: &serenity::Context,
@@ -21,28 +54,80 @@ pub async fn moderate_invites(
         return Ok(());
     };
 
-    if guild_id != GuildId::new(98226572468690944) {
+    let settings = data.database.get_guild_settings(guild_id);
+    let log_channel = settings
+        .invite_logging()
+        .then_some(settings.invite_log_channel)
+        .flatten();
+
+    let roles = msg
+        .member
+        .as_ref()
+        .map(|m| m.roles.to_vec())
+        .unwrap_or_default();
+    // bots and webhooks can't be warned or timed out, so they're only ever logged.
+    let rules = data.database.get_invite_rules(guild_id).filter(|r| {
+        !msg.author.bot() && msg.webhook_id.is_none() && !r.is_exempt(&roles, msg.channel_id)
+    });
+
+    if log_channel.is_none() && rules.is_none() {
         return Ok(());
     }
 
-    let mut invites = Vec::new();
-    for invite in INVITE.find_iter(&msg.content) {
-        let Ok(details) = ctx
-            .http
-            .get_invite(invite.as_str(), false, false, None)
+    let codes = invite_codes(&msg.content);
+    if codes.is_empty() {
+        return Ok(());
+    }
+
+    let mut invites = Vec::with_capacity(codes.len());
+    for code in codes {
+        match ctx.http.get_invite(&code, true, false, None).await {
+            Ok(invite) => invites.push(Some(invite)),
+            // an expired or made up invite is still posting one, a failed lookup says nothing.
+            Err(e) if is_unknown_invite(&e) => invites.push(None),
+            Err(e) => println!("Failed to resolve invite {code} in {guild_id}: {e}"),
+        }
+    }
+
+    let mut action = None;
+    if let Some(rules) = &rules {
+        let blocked = invites.iter().any(|invite| {
+            let target = invite.as_ref().and_then(|i| i.guild.as_ref()).map(|g| g.id);
+            !rules.is_allowed(guild_id, target)
+        });
+
+        if blocked {
+            let warning =
+                Warning::Channel("invites to other servers aren't allowed here.".to_owned());
+            if let Err(e) = enforce(
+                ctx,
+                msg,
+                guild_id,
+                rules.action,
+                "Posted an invite that isn't allowed",
+                warning,
+                rules.timeout_for,
+            )
             .await
-        else {
-            continue;
-        };
+            {
+                println!("Failed to enforce invite rules in {guild_id}: {e}");
+            }
+            action = Some(rules.action);
+        }
+    }
 
-        let Some(guild) = details.guild else {
-            continue;
-        };
+    let Some(log_channel) = log_channel else {
+        return Ok(());
+    };
 
-        invites.push((details.code, guild.name));
+    let mut cards = Vec::with_capacity(invites.len());
+    for invite in invites.iter().flatten() {
+        if let Some(card) = InviteCard::from_invite(data, invite).await {
+            cards.push(card);
+        }
     }
 
-    let mut embeds = Vec::with_capacity(invites.len());
+    let mut embeds = Vec::with_capacity(cards.len());
     let mut builder = CreateMessage::new().allowed_mentions(
         CreateAllowedMentions::new()
             .all_users(false)
@@ -50,20 +135,8 @@ pub async fn moderate_invites(
             .everyone(false),
     );
     let mut first_name = None;
-    for (index, (code, name)) in invites.iter().enumerate() {
-        let Ok(response) = data
-            .reqwest
-            .get(format!("https://invidget.switchblade.xyz/{code}"))
-            .send()
-            .await
-        else {
-            continue;
-        };
-
-        // https://github.com/SwitchbladeBot/invidget/pull/82
-        let text = response.text().await?.replace("image/jpg", "image/jpeg");
-
-        let Ok(png_data) = convert_svg_to_png(&text) else {
+    for (index, card) in cards.iter().enumerate() {
+        let Ok(png_data) = card.render() else {
             continue;
         };
 
@@ -73,14 +146,14 @@ pub async fn moderate_invites(
 
         let mut embed = serenity::CreateEmbed::new()
             .attachment(attachment_name)
-            .description(format!("https://discord.gg/{code}"));
+            .description(format!("https://discord.gg/{}", card.code));
 
         if index == 0 {
             embed = embed.author(CreateEmbedAuthor::from(&msg.author));
         }
 
         if first_name.is_none() {
-            first_name = Some(name);
+            first_name = Some(&card.guild_name);
         }
 
         if embeds.len() < 10 {
@@ -90,40 +163,40 @@ pub async fn moderate_invites(
 
     builder = builder.embeds(&embeds);
 
-    if !embeds.is_empty() {
-        if embeds.len() == 1 {
-            builder = builder.content(format!(
-                "{} posted an invite to {} in <#{}>",
-                msg.author,
-                first_name.expect("This should always be populated."),
-                msg.channel_id
-            ));
-        } else {
-            builder = builder.content(format!(
-                "{} posted multiple invites in <#{}>",
-                msg.author, msg.channel_id
-            ));
-        }
+    let mut content = match embeds.len() {
+        // only worth logging when something was done about it.
+        0 if action.is_none() => return Ok(()),
+        0 => format!(
+            "{} posted an invite that couldn't be resolved in <#{}>",
+            msg.author, msg.channel_id
+        ),
+        1 => format!(
+            "{} posted an invite to {} in <#{}>",
+            msg.author,
+            first_name.expect("This should always be populated."),
+            msg.channel_id
+        ),
+        _ => format!(
+            "{} posted multiple invites in <#{}>",
+            msg.author, msg.channel_id
+        ),
+    };
 
-        GenericChannelId::new(277163440999628800)
-            .send_message(&ctx.http, builder)
-            .await?;
+    if let Some(action) = action {
+        write!(content, ", it isn't allowed and was {}.", action.taken()).unwrap();
     }
 
+    log_channel
+        .send_message(&ctx.http, builder.content(content))
+        .await?;
+
     Ok(())
 }
 
-fn convert_svg_to_png(svg_data: &str) -> Result<Vec<u8>, Error> {
-    let tree = Tree::from_str(svg_data, &usvg::Options::default())?;
-
-    let size = tree.size().to_int_size();
-    let mut pixmap = Pixmap::new(size.width(), size.height()).expect("Failed to create a pixmap");
-
-    resvg::render(
-        &tree,
-        resvg::tiny_skia::Transform::default(),
-        &mut pixmap.as_mut(),
-    );
-
-    Ok(pixmap.encode_png()?)
+fn is_unknown_invite(error: &serenity::Error) -> bool {
+    matches!(
+        error,
+        serenity::Error::Http(HttpError::UnsuccessfulRequest(response))
+            if response.status_code.as_u16() == 404
+    )
 }
@@ -2,12 +2,20 @@ use std::fmt::Write;
 use std::sync::Arc;
 
 mod anti_delete;
+mod archive;
 mod database;
+mod enforce;
+mod filter;
+mod highlights;
+mod invite_card;
+pub mod modmail;
+mod ocr;
 use ::serenity::all::GenericChannelId;
 pub use database::EMOJI_REGEX;
 use invites::moderate_invites;
 pub mod invites;
 
+use crate::handlers::mod_log;
 use crate::helper::{get_channel_name, get_guild_name, get_guild_name_override};
 use crate::{Data, Error};
 
@@ -15,13 +23,15 @@ use moth_ansi::{CYAN, DIM, HI_BLACK, HI_RED, RESET};
 
 use database::insert_message;
 use lumi::serenity_prelude::{
-    self as serenity, Colour, CreateEmbed, CreateEmbedFooter, CreateMessage, GuildId, Message,
-    MessageId, UserId,
+    self as serenity, Attachment, Colour, CreateAttachment, CreateEmbed, CreateEmbedAuthor,
+    CreateEmbedFooter, CreateMessage, GuildId, Message, MessageId,
 };
+use moth_core::data::database::{archive::StoredMessage, mod_log::ModLogEvent};
 
 pub async fn message(ctx: &serenity::Context, msg: &Message, data: Arc<Data>) -> Result<(), Error> {
     let mut dont_print = false;
-    let (content, patterns) = {
+    let lists = data.database.get_filter_lists(msg.guild_id);
+    let (content, verdict) = {
         let config = &data.config.read().events;
 
         if should_skip_msg(
@@ -32,10 +42,7 @@ pub async fn message(ctx: &serenity::Context, msg: &Message, data: Arc<Data>) ->
             dont_print = true;
         }
 
-        let maybe_flagged =
-            moth_filter::filter_content(&msg.content, &config.badlist, &config.fixlist);
-
-        (maybe_flagged, config.regex.clone())
+        moth_filter::inspect(&msg.content, &lists)
     };
 
     let guild_id = msg.guild_id;
@@ -57,33 +64,44 @@ pub async fn message(ctx: &serenity::Context, msg: &Message, data: Arc<Data>) ->
 
     let guild_name = get_guild_name(ctx, guild_id);
     let _ = tokio::join!(
-        check_event_dm_regex(ctx, msg, &guild_name, patterns.as_deref()),
-        handle_dm(ctx, msg),
+        highlights::check_highlights(ctx, &data, msg, &msg.content),
+        modmail::handle_dm(ctx, &data, msg),
+        modmail::relay_staff_reply(ctx, &data, msg),
         insert_message(&data, msg),
+        archive::archive_message(ctx, &data, msg),
         // TODO: check why this broke
         moderate_invites(ctx, &data, msg),
+        filter::moderate_content(ctx, &data, msg, &msg.content, verdict, false),
         auto_super_poop(ctx, msg),
+        ocr::scan_attachments(ctx, &data, msg, &guild_name, dont_print),
     );
 
     Ok(())
 }
 
 async fn auto_super_poop(ctx: &serenity::Context, msg: &Message) -> Result<(), Error> {
-    let expected_guild = GuildId::new(98226572468690944);
-    let super_poop_role = serenity::RoleId::new(1384235804678684712);
-    let announce_thread = GenericChannelId::new(1390062742274310317);
+    let Some(guild_id) = msg.guild_id else {
+        return Ok(());
+    };
+
+    let data = ctx.data_ref::<Data>();
+    let settings = data.database.get_guild_settings(guild_id);
 
-    // Early return if not the correct guild
-    if msg.guild_id != Some(expected_guild) {
+    // Early return if the guild hasn't opted in.
+    if !settings.auto_super_poop() {
         return Ok(());
     }
 
+    let (Some(super_poop_role), Some(announce_thread)) =
+        (settings.super_poop_role, settings.super_poop_channel)
+    else {
+        return Ok(());
+    };
+
     let send_message = "Seems like you have a horrible avatar decoration or nameplate! As such, \
                         you have been awarded with a role that reflects your choice! Remove it to \
                         remove this role";
 
-    let data = ctx.data_ref::<Data>();
-
     // Check if user is marked as auto_pooped in memory
     let auto_pooped = data.auto_pooped.contains(&msg.author.id);
 
@@ -102,7 +120,7 @@ async fn auto_super_poop(ctx: &serenity::Context, msg: &Message) -> Result<(), E
             // Should have the role but doesn't - add it back
             ctx.http
                 .add_member_role(
-                    expected_guild,
+                    guild_id,
                     msg.author.id,
                     super_poop_role,
                     Some("User contains shitty decor/nameplate but no longer had the role"),
@@ -132,7 +150,7 @@ async fn auto_super_poop(ctx: &serenity::Context, msg: &Message) -> Result<(), E
             // Should NOT have the role but does - remove it and update DB/memory
             ctx.http
                 .remove_member_role(
-                    expected_guild,
+                    guild_id,
                     msg.author.id,
                     super_poop_role,
                     Some("Member no longer has the shitty decor/nameplate"),
@@ -175,7 +193,7 @@ async fn auto_super_poop(ctx: &serenity::Context, msg: &Message) -> Result<(), E
 
             ctx.http
                 .add_member_role(
-                    expected_guild,
+                    guild_id,
                     msg.author.id,
                     super_poop_role,
                     Some("User has shitty decor/nameplate."),
@@ -243,6 +261,31 @@ pub async fn message_edit(
     let guild_name = get_guild_name_override(ctx, &data, guild_id);
     let channel_name = get_channel_name(ctx, guild_id, new_message.channel_id).await;
 
+    if let Err(e) = archive::archive_edit(ctx, &data, new_message).await {
+        println!("Failed to archive edit of {}: {e}", new_message.id);
+    }
+
+    // embeds loading also sends an update, but without an edit timestamp.
+    let content_changed = match old_if_available {
+        Some(old_message) => old_message.content != new_message.content,
+        None => new_message.edited_timestamp.is_some(),
+    };
+    if content_changed {
+        let lists = data.database.get_filter_lists(guild_id);
+        let (_, verdict) = moth_filter::inspect(&new_message.content, &lists);
+        let _ = tokio::join!(
+            moderate_invites(ctx, &data, new_message),
+            filter::moderate_content(
+                ctx,
+                &data,
+                new_message,
+                &new_message.content,
+                verdict,
+                false
+            ),
+        );
+    }
+
     // I can probably just check event instead, it probably has what i need.
     if let Some(old_message) = old_if_available {
         if new_message.author.bot() {
@@ -278,9 +321,134 @@ pub async fn message_edit(
         );
     }
 
+    if let Some(guild_id) = guild_id {
+        log_edit(ctx, &data, guild_id, old_if_available.as_ref(), new_message).await;
+    }
+
     Ok(())
 }
 
+async fn log_edit(
+    ctx: &serenity::Context,
+    data: &Data,
+    guild_id: GuildId,
+    old_message: Option<&Message>,
+    new_message: &Message,
+) {
+    if new_message.author.bot()
+        || !mod_log::enabled(data, guild_id, ModLogEvent::MessageEdit)
+        || mod_log::is_ignored(
+            data,
+            Some(new_message.author.id),
+            Some(new_message.channel_id),
+        )
+    {
+        return;
+    }
+
+    let before = match old_message {
+        Some(old) if old.content == new_message.content => return,
+        Some(old) => mod_log::field_value(&old.content),
+        // embeds loading also sends an update, but without an edit timestamp.
+        None if new_message.edited_timestamp.is_none() => return,
+        None => "*Not cached*".to_owned(),
+    };
+
+    let mut embed = CreateEmbed::new()
+        .author(
+            CreateEmbedAuthor::new(new_message.author.tag()).icon_url(new_message.author.face()),
+        )
+        .title("Message edited")
+        .description(format!(
+            "<#{}> [Jump to message]({})",
+            new_message.channel_id,
+            new_message.link()
+        ))
+        .field("Before", before, false)
+        .field("After", mod_log::field_value(&new_message.content), false)
+        .footer(CreateEmbedFooter::new(format!(
+            "User ID: {} | Message ID: {}",
+            new_message.author.id, new_message.id
+        )))
+        .colour(Colour::ORANGE);
+
+    if let Some(attachments) = attachment_links(&new_message.attachments) {
+        embed = embed.field("Attachments", attachments, false);
+    }
+
+    mod_log::send(ctx, data, guild_id, ModLogEvent::MessageEdit, embed).await;
+}
+
+async fn log_delete(
+    ctx: &serenity::Context,
+    data: &Data,
+    guild_id: GuildId,
+    channel_id: GenericChannelId,
+    message_id: MessageId,
+    message: Option<&Message>,
+) {
+    if !mod_log::enabled(data, guild_id, ModLogEvent::MessageDelete)
+        || mod_log::is_ignored(data, message.map(|m| m.author.id), Some(channel_id))
+    {
+        return;
+    }
+
+    let mut embed = CreateEmbed::new()
+        .title("Message deleted")
+        .description(format!("<#{channel_id}>"))
+        .colour(Colour::RED);
+
+    if let Some(message) = message {
+        embed = embed
+            .author(CreateEmbedAuthor::new(message.author.tag()).icon_url(message.author.face()))
+            .field("Content", mod_log::field_value(&message.content), false)
+            .footer(CreateEmbedFooter::new(format!(
+                "User ID: {} | Message ID: {message_id}",
+                message.author.id
+            )));
+
+        if let Some(attachments) = attachment_links(&message.attachments) {
+            embed = embed.field("Attachments", attachments, false);
+        }
+    } else if let Ok(Some(history)) = data.database.get_message_history(message_id).await
+        && let Some(revision) = history.revisions.last()
+    {
+        // not cached, but the channel is archived.
+        embed = embed
+            .field(
+                "Content (archived)",
+                mod_log::field_value(&revision.content),
+                false,
+            )
+            .field("Author", format!("<@{}>", history.user_id), false)
+            .footer(CreateEmbedFooter::new(format!(
+                "User ID: {} | Message ID: {message_id}",
+                history.user_id
+            )));
+    } else {
+        embed = embed
+            .field("Content", "*Not cached*", false)
+            .footer(CreateEmbedFooter::new(format!("Message ID: {message_id}")));
+    }
+
+    mod_log::send(ctx, data, guild_id, ModLogEvent::MessageDelete, embed).await;
+}
+
+/// Links to each attachment, the links stop working once the message is deleted.
+fn attachment_links(attachments: &[Attachment]) -> Option<String> {
+    if attachments.is_empty() {
+        return None;
+    }
+
+    let links = attachments
+        .iter()
+        .map(|a| format!("[{}]({})", a.filename, a.url))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    Some(mod_log::field_value(&links))
+}
+
 pub async fn message_delete(
     ctx: &serenity::Context,
     channel_id: GenericChannelId,
@@ -292,28 +460,32 @@ pub async fn message_delete(
 
     let channel_name = get_channel_name(ctx, guild_id, channel_id).await;
 
+    if let Err(e) =
+        archive::archive_delete(ctx, &data, guild_id, channel_id, deleted_message_id).await
+    {
+        println!("Failed to archive delete of {deleted_message_id}: {e}");
+    }
+
     // This works but might not be optimal.
     let message = ctx
         .cache
         .message(channel_id, deleted_message_id)
         .map(|message_ref| message_ref.clone());
 
-    if let Some(message) = message {
-        let user_name = message.author.tag();
-        let content = message.content.clone();
-
-        let (attachments_fmt, embeds_fmt) = attachments_embed_fmt(&message);
+    if let Some(guild_id) = guild_id {
+        log_delete(
+            ctx,
+            &data,
+            guild_id,
+            channel_id,
+            deleted_message_id,
+            message.as_ref(),
+        )
+        .await;
+    }
 
-        println!(
-            "{HI_RED}{DIM}[{}] [#{}] A message from {RESET}{}{HI_RED}{DIM} was deleted: \
-             {}{}{}{RESET}",
-            guild_name,
-            channel_name,
-            user_name,
-            content,
-            attachments_fmt.as_deref().unwrap_or(""),
-            embeds_fmt.as_deref().unwrap_or("")
-        );
+    if let Some(message) = message {
+        print_deleted(&guild_name, &channel_name, &message);
     } else {
         println!(
             "{HI_RED}{DIM}A message (ID:{deleted_message_id}) was deleted but was not in \
@@ -324,134 +496,261 @@ pub async fn message_delete(
     if let Some(guild_id) = guild_id
         && let Some(user) =
             anti_delete::anti_delete(ctx, &data, channel_id, guild_id, deleted_message_id).await
-        && guild_id.get() == 98226572468690944
+        && let Some(alert_channel) = Some(data.database.get_guild_settings(guild_id))
+            .filter(|s| s.mass_delete_alerts())
+            .and_then(|s| s.mass_delete_channel)
+        && anti_delete::should_find_deleter(guild_id, channel_id)
     {
-        let embed = CreateEmbed::new()
-            .title("Possible mass deletion?")
-            .description(format!("Triggered on <@{user}>"))
-            .footer(CreateEmbedFooter::new(
-                "This doesn't check my own database or oinks database.",
-            ));
-        let builder = CreateMessage::new().embed(embed);
-        let _ = GenericChannelId::new(1284217769423798282)
-            .send_message(&ctx.http, builder)
-            .await;
+        // finding the deleter waits on the audit log, which shouldn't hold up the event.
+        let ctx = ctx.clone();
+        tokio::spawn(async move {
+            let description = match anti_delete::find_deleter(&ctx, guild_id, channel_id, user)
+                .await
+            {
+                Some(deleter) if deleter != user => {
+                    format!("<@{deleter}> is deleting messages from <@{user}> in <#{channel_id}>")
+                }
+                _ => format!("<@{user}> is deleting their own messages in <#{channel_id}>"),
+            };
+            let embed = CreateEmbed::new()
+                .title("Possible mass deletion?")
+                .description(description)
+                .footer(CreateEmbedFooter::new(format!(
+                    "User ID: {user} | Message ID: {deleted_message_id}"
+                )));
+            let builder = CreateMessage::new().embed(embed);
+            let _ = alert_channel.send_message(&ctx.http, builder).await;
+        });
     }
     Ok(())
 }
 
-fn should_skip_msg(
-    no_log_users: Option<&Vec<u64>>,
-    no_log_channels: Option<&Vec<u64>>,
-    message: &Message,
-) -> bool {
-    let user_condition = no_log_users
-        .as_ref()
-        .is_some_and(|vec| vec.contains(&message.author.id.get()));
-
-    let channel_condition = no_log_channels
-        .as_ref()
-        .is_some_and(|vec| vec.contains(&message.channel_id.get()));
-
-    // ignore commands in mudae channel.
-    let mudae_cmd = message.content.starts_with('$') && message.channel_id == 850342078034870302;
+fn print_deleted(guild_name: &str, channel_name: &str, message: &Message) {
+    let (attachments_fmt, embeds_fmt) = attachments_embed_fmt(message);
+
+    println!(
+        "{HI_RED}{DIM}[{}] [#{}] A message from {RESET}{}{HI_RED}{DIM} was deleted: \
+         {}{}{}{RESET}",
+        guild_name,
+        channel_name,
+        message.author.tag(),
+        message.content,
+        attachments_fmt.as_deref().unwrap_or(""),
+        embeds_fmt.as_deref().unwrap_or("")
+    );
+}
 
-    user_condition || channel_condition || mudae_cmd
+fn print_deleted_stored(guild_name: &str, channel_name: &str, message: &StoredMessage) {
+    println!(
+        "{HI_RED}{DIM}[{guild_name}] [#{channel_name}] A message from {RESET}{}{HI_RED}{DIM} was \
+         deleted (not cached): {}{RESET}",
+        message.user_id,
+        message.content.as_deref().unwrap_or("<content unknown>")
+    );
 }
 
-async fn check_event_dm_regex(
+pub async fn message_delete_bulk(
     ctx: &serenity::Context,
-    msg: &Message,
-    guild_name: &str,
-    patterns: Option<&[regex::Regex]>,
-) {
-    let Some(patterns) = patterns else {
-        return;
+    channel_id: GenericChannelId,
+    deleted_message_ids: &[MessageId],
+    guild_id: Option<GuildId>,
+    data: Arc<Data>,
+) -> Result<(), Error> {
+    let guild_name = get_guild_name_override(ctx, &data, guild_id);
+    let channel_name = get_channel_name(ctx, guild_id, channel_id).await;
+
+    let mut message_ids = deleted_message_ids.to_vec();
+    message_ids.sort_unstable();
+
+    let messages = message_ids
+        .iter()
+        .filter_map(|id| ctx.cache.message(channel_id, *id).map(|m| m.clone()))
+        .collect::<Vec<_>>();
+
+    let (no_log_channel, no_log_users) = {
+        let config = &data.config.read().events;
+        let no_log_channel = config
+            .no_log_channels
+            .as_ref()
+            .is_some_and(|c| c.contains(&channel_id.get()));
+        (
+            no_log_channel,
+            config.no_log_users.clone().unwrap_or_default(),
+        )
+    };
+
+    if !no_log_channel {
+        println!(
+            "{HI_RED}{DIM}[{guild_name}] [#{channel_name}] {} messages were bulk deleted, {} \
+             were not in cache{RESET}",
+            message_ids.len(),
+            message_ids.len() - messages.len()
+        );
+        for message in &messages {
+            if !no_log_users.contains(&message.author.id.get()) {
+                print_deleted(&guild_name, &channel_name, message);
+            }
+        }
+    }
+
+    let uncached = message_ids
+        .iter()
+        .filter(|id| !messages.iter().any(|m| m.id == **id))
+        .copied()
+        .collect::<Vec<_>>();
+    let stored = if uncached.is_empty() {
+        Vec::new()
+    } else {
+        data.database
+            .get_stored_messages(&uncached)
+            .await
+            .unwrap_or_else(|e| {
+                println!("Failed to get stored messages for bulk delete in {channel_id}: {e}");
+                Vec::new()
+            })
     };
 
-    if patterns.iter().any(|pattern| {
-        pattern.is_match(&msg.content) && msg.author.id != 158567567487795200 && !msg.author.bot()
-    }) {
-        if matches!(msg.author.id.get(), 441785661503176724 | 840780008623570954) {
-            return;
+    // the console gets the stored messages too, so it has the whole transcript even without a
+    // mod log.
+    if !no_log_channel {
+        for message in &stored {
+            if !no_log_users.contains(&message.user_id.get()) {
+                print_deleted_stored(&guild_name, &channel_name, message);
+            }
         }
+    }
 
-        let _ = pattern_matched(ctx, msg, guild_name).await;
+    if let Err(e) =
+        archive::archive_bulk_delete(ctx, &data, guild_id, channel_id, &message_ids).await
+    {
+        println!("Failed to archive bulk delete in {channel_id}: {e}");
     }
+
+    let Some(guild_id) = guild_id else {
+        return Ok(());
+    };
+
+    anti_delete::anti_delete_bulk(&data, guild_id, &message_ids);
+
+    log_bulk_delete(
+        ctx,
+        &data,
+        guild_id,
+        channel_id,
+        &message_ids,
+        &messages,
+        &stored,
+    )
+    .await;
+
+    Ok(())
 }
 
-async fn pattern_matched(ctx: &serenity::Context, msg: &Message, guild: &str) -> Result<(), Error> {
-    let embed = serenity::CreateEmbed::default()
-        .title("A pattern was matched!")
+async fn log_bulk_delete(
+    ctx: &serenity::Context,
+    data: &Data,
+    guild_id: GuildId,
+    channel_id: GenericChannelId,
+    message_ids: &[MessageId],
+    messages: &[Message],
+    stored: &[StoredMessage],
+) {
+    if !mod_log::enabled(data, guild_id, ModLogEvent::MessageDelete)
+        || mod_log::is_ignored(data, None, Some(channel_id))
+    {
+        return;
+    }
+
+    let transcript = bulk_delete_transcript(data, message_ids, messages, stored);
+
+    let embed = CreateEmbed::new()
+        .title("Messages bulk deleted")
         .description(format!(
-            "<#{}> by **{}** {}\n\n [Jump to message!]({})",
-            msg.channel_id,
-            msg.author.tag(),
-            msg.content,
-            msg.link()
+            "{} messages were deleted in <#{channel_id}>",
+            message_ids.len()
         ))
-        .color(Colour::from_rgb(0, 255, 0));
-
-    let msg = serenity::CreateMessage::default()
-        .content(format!(
-            "In {} <#{}> you were mentioned by {} (ID:{})",
-            guild,
-            msg.channel_id,
-            msg.author.tag(),
-            msg.author.id
-        ))
-        .embed(embed);
-
-    // TODO: use fw owner's or make configurable.
-    UserId::from(158567567487795200).dm(&ctx.http, msg).await?;
+        .field("Cached", messages.len().to_string(), true)
+        .field("Stored", stored.len().to_string(), true)
+        .field(
+            "Unknown",
+            (message_ids.len() - messages.len() - stored.len()).to_string(),
+            true,
+        )
+        .colour(Colour::RED);
+
+    let file = CreateAttachment::bytes(
+        transcript.into_bytes(),
+        format!("bulk-delete-{channel_id}.txt"),
+    );
 
-    Ok(())
+    mod_log::send_with_file(ctx, data, guild_id, ModLogEvent::MessageDelete, embed, file).await;
 }
 
-async fn handle_dm(ctx: &serenity::Context, msg: &Message) -> Result<(), Error> {
-    let (user, is_interaction) = if let Some(metadata) = &msg.interaction_metadata.as_deref() {
-        let data = match *metadata {
-            serenity::MessageInteractionMetadata::Command(data) => &data.user,
-            serenity::MessageInteractionMetadata::Component(data) => &data.user,
-            serenity::MessageInteractionMetadata::ModalSubmit(data) => &data.user,
-            _ => &msg.author,
-        };
+/// Every deleted message oldest first, messages that were never stored only have their id.
+fn bulk_delete_transcript(
+    data: &Data,
+    message_ids: &[MessageId],
+    messages: &[Message],
+    stored: &[StoredMessage],
+) -> String {
+    let mut transcript = String::new();
 
-        (data, true)
-    } else {
-        (&msg.author, false)
-    };
+    for id in message_ids {
+        let sent_at = id.created_at();
 
-    // TODO: use fw owner's or make configurable.
-    if msg.guild_id.is_some()
-        || [158567567487795200, ctx.cache.current_user().id.get()].contains(&user.id.get())
-    {
-        return Ok(());
-    }
+        if let Some(message) = messages.iter().find(|m| m.id == *id) {
+            if mod_log::is_ignored(data, Some(message.author.id), None) {
+                continue;
+            }
 
-    if is_interaction {
-        return Ok(());
+            writeln!(
+                transcript,
+                "[{sent_at}] {} ({}): {}",
+                message.author.tag(),
+                message.author.id,
+                message.content
+            )
+            .unwrap();
+            for attachment in &message.attachments {
+                writeln!(transcript, "    Attachment: {}", attachment.url).unwrap();
+            }
+        } else if let Some(message) = stored.iter().find(|m| m.message_id == *id) {
+            if mod_log::is_ignored(data, Some(message.user_id), None) {
+                continue;
+            }
+
+            writeln!(
+                transcript,
+                "[{sent_at}] {} (not cached): {}",
+                message.user_id,
+                message.content.as_deref().unwrap_or("<content unknown>")
+            )
+            .unwrap();
+        } else {
+            writeln!(transcript, "[{sent_at}] Unknown message {id}").unwrap();
+        }
     }
 
-    let description = format!("**{}**: {}", msg.author.tag(), msg.content);
+    transcript
+}
+
+fn should_skip_msg(
+    no_log_users: Option<&Vec<u64>>,
+    no_log_channels: Option<&Vec<u64>>,
+    message: &Message,
+) -> bool {
+    let user_condition = no_log_users
+        .as_ref()
+        .is_some_and(|vec| vec.contains(&message.author.id.get()));
 
-    let embed = serenity::CreateEmbed::default()
-        .title("I was messaged!")
-        .description(description)
-        .color(Colour::from_rgb(0, 255, 0))
-        .footer(CreateEmbedFooter::new(format!("{}", msg.channel_id)));
+    let channel_condition = no_log_channels
+        .as_ref()
+        .is_some_and(|vec| vec.contains(&message.channel_id.get()));
 
-    let msg = serenity::CreateMessage::default()
-        .content(format!(
-            "{} (ID:{}) messaged me",
-            msg.author.tag(),
-            msg.author.id
-        ))
-        .embed(embed);
+    // ignore commands in mudae channel.
+    let mudae_cmd = message.content.starts_with('$') && message.channel_id == 850342078034870302;
 
-    // dm me about the mention of me.
-    UserId::from(158567567487795200).dm(&ctx.http, msg).await?;
-    Ok(())
+    user_condition || channel_condition || mudae_cmd
 }
 
 #[must_use]
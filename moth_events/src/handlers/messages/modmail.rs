@@ -0,0 +1,549 @@
+use std::{
+    borrow::Cow,
+    fmt::Write,
+    sync::{Arc, LazyLock},
+    time::Duration,
+};
+
+use dashmap::DashMap;
+use lumi::serenity_prelude::{
+    self as serenity, Attachment, ChannelId, Colour, ComponentInteractionCollector,
+    CreateActionRow, CreateAttachment, CreateButton, CreateComponent, CreateEmbed,
+    CreateEmbedAuthor, CreateEmbedFooter, CreateForumPost, CreateInteractionResponse,
+    CreateInteractionResponseMessage, CreateMessage, EditThread, GenericChannelId, GetMessages,
+    GuildId, Message, MessageId, UserId,
+};
+use mini_moka::sync::Cache;
+use moth_core::data::{database::modmail::ModmailThread, structs::Data};
+use tokio::sync::Mutex;
+
+use crate::Error;
+use crate::helper::truncate;
+
+/// Staff messages starting with this stay in the thread as notes.
+const NOTE_PREFIX: &str = "//";
+
+/// Files are uploaded up to this size in total, anything past it is sent as a link instead.
+const MAX_UPLOAD_SIZE: u32 = 8 * 1024 * 1024;
+
+/// Held while a user's dm is handled, so a second dm can't prompt them twice or open a second
+/// thread while the first is being opened.
+static HANDLING: LazyLock<DashMap<UserId, Arc<Mutex<()>>>> = LazyLock::new(DashMap::new);
+
+/// The modmail guilds a user is in, so every dm doesn't look them up in each guild again.
+static MODMAIL_GUILDS: LazyLock<Cache<UserId, Vec<(GuildId, ChannelId)>>> = LazyLock::new(|| {
+    Cache::builder()
+        .time_to_live(Duration::from_secs(60 * 5))
+        .build()
+});
+
+/// Relays a dm into the user's modmail thread, opening one if they don't have one yet.
+pub(super) async fn handle_dm(
+    ctx: &serenity::Context,
+    data: &Data,
+    msg: &Message,
+) -> Result<(), Error> {
+    if msg.guild_id.is_some() || msg.interaction_metadata.is_some() || msg.author.bot() {
+        return Ok(());
+    }
+
+    let lock = HANDLING.entry(msg.author.id).or_default().clone();
+    let result = {
+        let _guard = lock.lock().await;
+        handle_dm_locked(ctx, data, msg).await
+    };
+
+    drop(lock);
+    // only the map holds it once nobody else is waiting.
+    HANDLING.remove_if(&msg.author.id, |_, lock| Arc::strong_count(lock) == 1);
+
+    result
+}
+
+async fn handle_dm_locked(
+    ctx: &serenity::Context,
+    data: &Data,
+    msg: &Message,
+) -> Result<(), Error> {
+    // a dm handled while this one waited could've opened it.
+    if let Some(thread) = data.database.get_user_modmail_thread(msg.author.id) {
+        return relay_to_thread(ctx, msg, thread.thread_id).await;
+    }
+
+    let guilds = modmail_guilds(ctx, data, msg.author.id).await;
+
+    let (guild_id, forum) = match guilds.as_slice() {
+        [] => return forward_to_owner(ctx, msg).await,
+        [guild] => *guild,
+        _ => {
+            let Some(guild) = prompt_guild(ctx, msg, &guilds).await? else {
+                return Ok(());
+            };
+            guild
+        }
+    };
+
+    open_thread(ctx, data, msg, guild_id, forum).await
+}
+
+/// The guilds with modmail set up that the user is in, along with their forum.
+async fn modmail_guilds(
+    ctx: &serenity::Context,
+    data: &Data,
+    user_id: UserId,
+) -> Vec<(GuildId, ChannelId)> {
+    if let Some(guilds) = MODMAIL_GUILDS.get(&user_id) {
+        return guilds;
+    }
+
+    let forums = data
+        .database
+        .filter_guild_settings(|s| s.modmail() && s.modmail_channel.is_some());
+
+    let mut guilds = Vec::new();
+    for (guild_id, settings) in forums {
+        let Some(forum) = settings.modmail_channel else {
+            continue;
+        };
+
+        // the member list isn't always fully cached, so the api is the fallback.
+        let cached = ctx
+            .cache
+            .guild(guild_id)
+            .is_some_and(|g| g.members.contains_key(&user_id));
+        if cached || guild_id.member(&ctx.http, user_id).await.is_ok() {
+            guilds.push((guild_id, forum));
+        }
+    }
+
+    MODMAIL_GUILDS.insert(user_id, guilds.clone());
+    guilds
+}
+
+/// Relays a staff reply in a modmail thread back to the user.
+pub(super) async fn relay_staff_reply(
+    ctx: &serenity::Context,
+    data: &Data,
+    msg: &Message,
+) -> Result<(), Error> {
+    if msg.guild_id.is_none() || msg.author.bot() {
+        return Ok(());
+    }
+
+    let Some(thread) = data.database.get_modmail_thread(msg.channel_id) else {
+        return Ok(());
+    };
+
+    if !is_relayed(msg) {
+        return Ok(());
+    }
+
+    let guild_name = ctx
+        .cache
+        .guild(thread.guild_id)
+        .map_or_else(|| "Staff".to_owned(), |g| g.name.to_string());
+
+    let mut embed = CreateEmbed::new()
+        .author(CreateEmbedAuthor::new(format!(
+            "{} ({guild_name})",
+            msg.author.name
+        )))
+        .colour(Colour::from_rgb(88, 101, 242))
+        .timestamp(msg.timestamp);
+
+    let (files, links) = download(&msg.attachments).await;
+    if !msg.content.is_empty() || !links.is_empty() {
+        embed = embed.description(with_links(&msg.content, &links));
+    }
+
+    let builder = CreateMessage::new().embed(embed).add_files(files);
+
+    if let Err(e) = thread.user_id.dm(&ctx.http, builder).await {
+        msg.channel_id
+            .send_message(
+                &ctx.http,
+                CreateMessage::new().content(format!("Couldn't send that to the user: {e}")),
+            )
+            .await?;
+        return Ok(());
+    }
+
+    let _ = msg.react(&ctx.http, '✅').await;
+
+    Ok(())
+}
+
+/// Closes a modmail thread, storing the transcript and letting the user know.
+///
+/// Returns `None` if the channel isn't an open modmail thread.
+pub async fn close_thread(
+    ctx: &serenity::Context,
+    data: &Data,
+    thread_id: GenericChannelId,
+    closed_by: UserId,
+) -> Result<Option<ModmailThread>, Error> {
+    let Some(thread) = data.database.get_modmail_thread(thread_id) else {
+        return Ok(None);
+    };
+
+    let transcript = transcript(ctx, &thread).await?;
+    let Some(thread) = data
+        .database
+        .close_modmail_thread(thread_id, Some(closed_by), Some(&transcript))
+        .await?
+    else {
+        return Ok(None);
+    };
+
+    let _ = thread
+        .user_id
+        .dm(
+            &ctx.http,
+            CreateMessage::new()
+                .content("Your thread has been closed, messaging me again will open a new one."),
+        )
+        .await;
+
+    let builder = CreateMessage::new()
+        .content(format!("Closed by <@{closed_by}>."))
+        .add_file(CreateAttachment::bytes(
+            transcript.into_bytes(),
+            format!("modmail-{}.txt", thread.user_id),
+        ));
+    thread_id.send_message(&ctx.http, builder).await?;
+
+    thread_id
+        .expect_thread()
+        .edit(&ctx.http, EditThread::new().archived(true).locked(true))
+        .await?;
+
+    Ok(Some(thread))
+}
+
+/// Closes the modmail thread of a forum post that was deleted, there's nothing left to make a
+/// transcript from.
+pub async fn thread_deleted(
+    ctx: &serenity::Context,
+    data: &Data,
+    thread_id: GenericChannelId,
+) -> Result<(), Error> {
+    let Some(thread) = data
+        .database
+        .close_modmail_thread(thread_id, None, None)
+        .await?
+    else {
+        return Ok(());
+    };
+
+    let _ = thread
+        .user_id
+        .dm(
+            &ctx.http,
+            CreateMessage::new()
+                .content("Your thread has been closed, messaging me again will open a new one."),
+        )
+        .await;
+
+    Ok(())
+}
+
+async fn open_thread(
+    ctx: &serenity::Context,
+    data: &Data,
+    msg: &Message,
+    guild_id: GuildId,
+    forum: ChannelId,
+) -> Result<(), Error> {
+    let user = &msg.author;
+
+    let mut description = format!(
+        "**User:** {user} ({})\n**Created:** <t:{}:R>",
+        user.id,
+        user.id.created_at().unix_timestamp()
+    );
+    let cached = ctx
+        .cache
+        .guild(guild_id)
+        .and_then(|g| g.members.get(&user.id).map(|m| m.joined_at));
+    let joined_at = match cached {
+        Some(joined_at) => joined_at,
+        None => guild_id
+            .member(&ctx.http, user.id)
+            .await
+            .ok()
+            .and_then(|m| m.joined_at),
+    };
+    if let Some(joined_at) = joined_at {
+        write!(
+            description,
+            "\n**Joined:** <t:{}:R>",
+            joined_at.unix_timestamp()
+        )
+        .unwrap();
+    }
+
+    let header = CreateEmbed::new()
+        .title(format!("Modmail from {}", user.tag()))
+        .thumbnail(user.face())
+        .description(description)
+        .footer(CreateEmbedFooter::new(format!(
+            "Replies are sent to the user, unless they start with {NOTE_PREFIX}. Close the \
+             thread with modmail close."
+        )));
+
+    let post = CreateForumPost::new(user.tag(), CreateMessage::new().embed(header));
+    let thread = forum.create_forum_post(&ctx.http, post).await?;
+    let thread_id = thread.id.widen();
+
+    if let Err(e) = data
+        .database
+        .open_modmail_thread(guild_id, user.id, thread_id)
+        .await
+    {
+        // the post is useless without the thread behind it.
+        let _ = ctx
+            .http
+            .delete_channel(thread_id, Some("Modmail thread couldn't be opened"))
+            .await;
+        return Err(e);
+    }
+
+    relay_to_thread(ctx, msg, thread_id).await?;
+
+    let guild_name = ctx
+        .cache
+        .guild(guild_id)
+        .map_or_else(|| guild_id.to_string(), |g| g.name.to_string());
+    msg.channel_id
+        .send_message(
+            &ctx.http,
+            CreateMessage::new().content(format!(
+                "Your message was sent to the staff of **{guild_name}**, their replies will show \
+                 up here."
+            )),
+        )
+        .await?;
+
+    Ok(())
+}
+
+async fn relay_to_thread(
+    ctx: &serenity::Context,
+    msg: &Message,
+    thread_id: GenericChannelId,
+) -> Result<(), Error> {
+    // the author is what marks this as the user's message in the transcript.
+    let mut embed = CreateEmbed::new()
+        .author(CreateEmbedAuthor::from(&msg.author))
+        .colour(Colour::from_rgb(0, 255, 0))
+        .timestamp(msg.timestamp);
+
+    let (files, links) = download(&msg.attachments).await;
+    if !msg.content.is_empty() || !links.is_empty() {
+        embed = embed.description(with_links(&msg.content, &links));
+    }
+
+    let builder = CreateMessage::new().embed(embed).add_files(files);
+    thread_id.send_message(&ctx.http, builder).await?;
+
+    let _ = msg.react(&ctx.http, '✅').await;
+
+    Ok(())
+}
+
+/// Asks a user that shares multiple modmail guilds with the bot which one to contact.
+async fn prompt_guild(
+    ctx: &serenity::Context,
+    msg: &Message,
+    guilds: &[(GuildId, ChannelId)],
+) -> Result<Option<(GuildId, ChannelId)>, Error> {
+    let buttons = guilds
+        .iter()
+        .take(5)
+        .map(|(guild_id, _)| {
+            let name = ctx
+                .cache
+                .guild(*guild_id)
+                .map_or_else(|| guild_id.to_string(), |g| g.name.to_string());
+            // guild names can be longer than a button label.
+            CreateButton::new(format!("{}-{guild_id}", msg.id)).label(truncate(&name, 80))
+        })
+        .collect::<Vec<_>>();
+
+    let prompt = msg
+        .channel_id
+        .send_message(
+            &ctx.http,
+            CreateMessage::new()
+                .content("Which server's staff do you want to message?")
+                .components(vec![CreateComponent::ActionRow(CreateActionRow::Buttons(
+                    Cow::Owned(buttons),
+                ))]),
+        )
+        .await?;
+
+    let msg_id = msg.id.to_string();
+    let press = ComponentInteractionCollector::new(ctx)
+        .filter(move |press| press.data.custom_id.starts_with(&msg_id))
+        .timeout(Duration::from_secs(120))
+        .await;
+
+    let Some(press) = press else {
+        let _ = prompt.delete(&ctx.http, None).await;
+        return Ok(None);
+    };
+
+    let picked = press
+        .data
+        .custom_id
+        .rsplit('-')
+        .next()
+        .and_then(|id| id.parse::<u64>().ok())
+        .and_then(|id| guilds.iter().find(|(g, _)| g.get() == id))
+        .copied();
+
+    press
+        .create_response(
+            &ctx.http,
+            CreateInteractionResponse::UpdateMessage(
+                CreateInteractionResponseMessage::new()
+                    .content("Sending your message...")
+                    .components(vec![]),
+            ),
+        )
+        .await?;
+
+    Ok(picked)
+}
+
+/// Where dms went before modmail, used when the user doesn't share a guild with modmail set up.
+async fn forward_to_owner(ctx: &serenity::Context, msg: &Message) -> Result<(), Error> {
+    // TODO: use fw owner's or make configurable.
+    if [158567567487795200, ctx.cache.current_user().id.get()].contains(&msg.author.id.get()) {
+        return Ok(());
+    }
+
+    let description = format!("**{}**: {}", msg.author.tag(), msg.content);
+
+    let embed = serenity::CreateEmbed::default()
+        .title("I was messaged!")
+        .description(description)
+        .color(Colour::from_rgb(0, 255, 0))
+        .footer(CreateEmbedFooter::new(format!("{}", msg.channel_id)));
+
+    let msg = serenity::CreateMessage::default()
+        .content(format!(
+            "{} (ID:{}) messaged me",
+            msg.author.tag(),
+            msg.author.id
+        ))
+        .embed(embed);
+
+    // dm me about the mention of me.
+    UserId::from(158567567487795200).dm(&ctx.http, msg).await?;
+    Ok(())
+}
+
+/// Notes and commands stay in the thread, everything else is sent to the user.
+fn is_relayed(msg: &Message) -> bool {
+    if msg.content.is_empty() && msg.attachments.is_empty() {
+        return false;
+    }
+
+    !msg.content.starts_with(NOTE_PREFIX) && !moth_core::prefix::invokes_command(&msg.content)
+}
+
+/// Downloads attachments so they can be uploaded on the other side, along with links to the
+/// ones that are too large or couldn't be downloaded.
+async fn download(attachments: &[Attachment]) -> (Vec<CreateAttachment<'static>>, Vec<String>) {
+    let mut files = Vec::with_capacity(attachments.len());
+    let mut links = Vec::new();
+    let mut total = 0;
+    for attachment in attachments {
+        if total + attachment.size <= MAX_UPLOAD_SIZE
+            && let Ok(bytes) = attachment.download().await
+        {
+            total += attachment.size;
+            files.push(CreateAttachment::bytes(
+                bytes,
+                attachment.filename.to_string(),
+            ));
+        } else {
+            links.push(attachment.url.to_string());
+        }
+    }
+
+    (files, links)
+}
+
+fn with_links(content: &str, links: &[String]) -> String {
+    let mut description = content.to_owned();
+    for link in links {
+        if !description.is_empty() {
+            description.push('\n');
+        }
+        description.push_str(link);
+    }
+
+    description
+}
+
+async fn transcript(ctx: &serenity::Context, thread: &ModmailThread) -> Result<String, Error> {
+    let bot_id = ctx.cache.current_user().id;
+
+    // the starter message shares its id with the thread and only holds the header.
+    let mut after = MessageId::new(thread.thread_id.get());
+    let mut messages = Vec::new();
+    loop {
+        let batch = thread
+            .thread_id
+            .messages(&ctx.http, GetMessages::new().after(after).limit(100))
+            .await?;
+
+        let Some(newest) = batch.iter().map(|m| m.id).max() else {
+            break;
+        };
+        let done = batch.len() < 100;
+
+        after = newest;
+        messages.extend(batch);
+
+        if done {
+            break;
+        }
+    }
+    messages.sort_by_key(|m| m.id);
+
+    let mut transcript = format!(
+        "Modmail thread with {} opened at {}\n\n",
+        thread.user_id, thread.opened_at
+    );
+
+    for msg in &messages {
+        let (name, content, note) = if msg.author.id == bot_id {
+            // only relayed user messages are kept from the bot.
+            let Some(embed) = msg.embeds.first() else {
+                continue;
+            };
+            let Some(author) = &embed.author else {
+                continue;
+            };
+            let content = embed.description.as_deref().unwrap_or_default();
+            (author.name.to_string(), content, false)
+        } else {
+            (
+                msg.author.tag().to_string(),
+                msg.content.as_str(),
+                !is_relayed(msg),
+            )
+        };
+
+        let note = if note { " (note)" } else { "" };
+        writeln!(transcript, "[{}] {name}{note}: {content}", msg.timestamp).unwrap();
+        for attachment in &msg.attachments {
+            writeln!(transcript, "    Attachment: {}", attachment.url).unwrap();
+        }
+    }
+
+    Ok(transcript)
+}
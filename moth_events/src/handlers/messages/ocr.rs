@@ -0,0 +1,84 @@
+use std::sync::Arc;
+
+use lumi::serenity_prelude::{self as serenity, Attachment, Message};
+use moth_ansi::{HI_BLACK, RESET};
+use moth_core::ocr::MAX_IMAGE_SIZE;
+use moth_filter::Severity;
+
+use super::filter::moderate_content;
+use super::highlights::check_highlights;
+use crate::{Data, Error};
+
+fn is_scannable(attachment: &Attachment) -> bool {
+    attachment.size <= MAX_IMAGE_SIZE
+        && attachment
+            .content_type
+            .as_deref()
+            .is_some_and(|c| c.starts_with("image/"))
+}
+
+/// Runs image attachments through OCR, the text is then filtered, moderated and checked for
+/// highlights like the content of a normal message.
+pub(super) async fn scan_attachments(
+    ctx: &serenity::Context,
+    data: &Arc<Data>,
+    msg: &Message,
+    guild_name: &str,
+    dont_print: bool,
+) -> Result<(), Error> {
+    let Some(guild_id) = msg.guild_id else {
+        return Ok(());
+    };
+
+    if msg.author.bot() || !data.database.get_guild_settings(guild_id).ocr_scanning() {
+        return Ok(());
+    }
+
+    let mut moderated = false;
+    for attachment in msg.attachments.iter().filter(|a| is_scannable(a)) {
+        let Ok(image_data) = attachment.download().await else {
+            continue;
+        };
+
+        let text = match data.ocr_engine.process(image_data).await {
+            Ok(text) => text,
+            Err(e) => {
+                println!("Failed to OCR {}: {e}", attachment.url);
+                continue;
+            }
+        };
+
+        let text = text.trim();
+        if text.is_empty() {
+            continue;
+        }
+
+        let lists = data.database.get_filter_lists(msg.guild_id);
+        let (filtered, verdict) = moth_filter::inspect(text, &lists);
+
+        if !dont_print {
+            let severity = match verdict.severity {
+                Some(Severity::Severe) => " (severe)",
+                Some(Severity::Moderate) => " (moderate)",
+                None => "",
+            };
+            println!(
+                "{HI_BLACK}[{guild_name}] OCR of {} by {}{severity}:{RESET} {filtered}{RESET}",
+                attachment.filename,
+                msg.author.tag()
+            );
+        }
+
+        // one image is enough to act on the message, the rest would only warn the author again.
+        if !moderated {
+            match moderate_content(ctx, data, msg, text, verdict, true).await {
+                Ok(acted) => moderated = acted,
+                Err(e) => println!("Failed to moderate OCR of {}: {e}", attachment.filename),
+            }
+        }
+
+        let _ = check_highlights(ctx, data, msg, text).await;
+    }
+
+    Ok(())
+}
@@ -1,5 +1,6 @@
 use crate::{Data, Error};
-use lumi::serenity_prelude::{self as serenity, Ready};
+use lumi::serenity_prelude::{self as serenity, Ready, UserId};
+use moth_core::data::database::highlights::Highlight;
 
 use std::sync::Arc;
 use std::sync::atomic::Ordering;
@@ -27,13 +28,39 @@ pub async fn ready(ctx: &serenity::Context, ready: &Ready, data: Arc<Data>) -> R
 
 async fn finalize_start(ctx: &serenity::Context) {
     let data = ctx.data::<Data>();
+
+    if let Err(e) = data
+        .database
+        .end_stale_voice_sessions(&ctx.cache.guilds())
+        .await
+    {
+        println!("Failed to end voice sessions of guilds that were left: {e}");
+    }
+
+    import_event_regex(ctx, &data).await;
     let data_clone = data.clone();
 
     tokio::spawn(async move {
         let mut interval: tokio::time::Interval = tokio::time::interval(Duration::from_secs(2));
         loop {
             interval.tick().await;
-            data_clone.anti_delete_cache.decay_proc();
+            data_clone
+                .anti_delete_cache
+                .decay_proc(&data_clone.database);
+        }
+    });
+
+    let data_clone = data.clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(60 * 5));
+        loop {
+            interval.tick().await;
+            if let Err(e) = data_clone.database.touch_voice_sessions().await {
+                println!("Failed to update voice sessions: {e}");
+            }
+            if let Err(e) = data_clone.database.touch_presence_activities().await {
+                println!("Failed to update presence activities: {e}");
+            }
         }
     });
 
@@ -45,6 +72,17 @@ async fn finalize_start(ctx: &serenity::Context) {
         .start_background_task(Arc::clone(&data.database))
         .await;
 
+    if std::env::var("RETENTION_ACTIVE")
+        .map(|v| v.parse::<bool>().unwrap_or(false))
+        .unwrap_or(false)
+    {
+        data.retention
+            .start_background_task(Arc::clone(&data.database));
+    } else {
+        data.retention
+            .start_presence_task(Arc::clone(&data.database));
+    }
+
     if std::env::var("PRIVATE_VC_ACTIVE")
         .map(|v| v.parse::<bool>().unwrap_or(false))
         .unwrap_or(false)
@@ -52,3 +90,29 @@ async fn finalize_start(ctx: &serenity::Context) {
         data.private_vc.start_background_task(ctx.clone()).await;
     }
 }
+
+/// The old `events.regex` patterns DMed the owner for matches in any guild, so they become the
+/// owner's regex highlights in every guild. The config only forgets them once all are stored.
+async fn import_event_regex(ctx: &serenity::Context, data: &Data) {
+    let Some(patterns) = data.config.read().events.regex.clone() else {
+        return;
+    };
+
+    let owner = UserId::new(158567567487795200);
+    for guild_id in ctx.cache.guilds() {
+        for pattern in &patterns {
+            let Ok(highlight) = Highlight::new(owner, pattern.as_str().to_owned(), true) else {
+                continue;
+            };
+
+            if let Err(e) = data.database.add_highlight(guild_id, highlight).await {
+                println!("Failed to import event regex {pattern}: {e}");
+                return;
+            }
+        }
+    }
+
+    let mut config = data.config.write();
+    config.events.regex = None;
+    config.write_config();
+}
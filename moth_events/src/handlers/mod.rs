@@ -2,6 +2,8 @@ pub mod channels;
 pub mod guilds;
 pub mod messages;
 pub mod misc;
+pub mod mod_log;
+pub mod presences;
 pub mod reactions;
 pub mod users;
 pub mod voice;
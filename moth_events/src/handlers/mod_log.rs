@@ -0,0 +1,162 @@
+use std::sync::LazyLock;
+
+use dashmap::DashMap;
+use lumi::serenity_prelude::{
+    self as serenity, CreateAllowedMentions, CreateAttachment, CreateEmbed, CreateMessage,
+    ExecuteWebhook, GenericChannelId, GuildId, Timestamp, UserId, Webhook,
+};
+use moth_core::data::database::mod_log::{ModLogEvent, ModLogTarget};
+
+use crate::Data;
+
+/// Fetched webhooks by url, so only the first event sent through a webhook needs a lookup.
+static WEBHOOKS: LazyLock<DashMap<String, Webhook>> = LazyLock::new(DashMap::new);
+
+/// Embed field values are capped at 1024 characters.
+const MAX_FIELD_LENGTH: usize = 1000;
+
+/// If anything has been routed for this event, used to skip building embeds for nothing.
+pub(crate) fn enabled(data: &Data, guild_id: GuildId, event: ModLogEvent) -> bool {
+    data.database.get_mod_log_target(guild_id, event).is_some()
+}
+
+/// If the user or channel is in `no_log_users` or `no_log_channels`.
+pub(crate) fn is_ignored(
+    data: &Data,
+    user_id: Option<UserId>,
+    channel_id: Option<GenericChannelId>,
+) -> bool {
+    let config = &data.config.read().events;
+
+    let user_ignored = user_id.is_some_and(|u| {
+        config
+            .no_log_users
+            .as_ref()
+            .is_some_and(|users| users.contains(&u.get()))
+    });
+    let channel_ignored = channel_id.is_some_and(|c| {
+        config
+            .no_log_channels
+            .as_ref()
+            .is_some_and(|channels| channels.contains(&c.get()))
+    });
+
+    user_ignored || channel_ignored
+}
+
+/// Sends the embed to wherever the event is routed to in the guild, does nothing if the event
+/// isn't routed anywhere.
+pub(crate) async fn send(
+    ctx: &serenity::Context,
+    data: &Data,
+    guild_id: GuildId,
+    event: ModLogEvent,
+    embed: CreateEmbed<'_>,
+) {
+    send_inner(ctx, data, guild_id, event, embed, None).await;
+}
+
+/// Same as [`send`], but with a file attached to the message.
+pub(crate) async fn send_with_file<'a>(
+    ctx: &serenity::Context,
+    data: &Data,
+    guild_id: GuildId,
+    event: ModLogEvent,
+    embed: CreateEmbed<'a>,
+    file: CreateAttachment<'a>,
+) {
+    send_inner(ctx, data, guild_id, event, embed, Some(file)).await;
+}
+
+async fn send_inner<'a>(
+    ctx: &serenity::Context,
+    data: &Data,
+    guild_id: GuildId,
+    event: ModLogEvent,
+    embed: CreateEmbed<'a>,
+    file: Option<CreateAttachment<'a>>,
+) {
+    let Some(target) = data.database.get_mod_log_target(guild_id, event) else {
+        return;
+    };
+
+    let embed = embed.timestamp(Timestamp::now());
+
+    let result = match target {
+        ModLogTarget::Channel(channel_id) => {
+            let mut builder = CreateMessage::new()
+                .embed(embed)
+                .allowed_mentions(CreateAllowedMentions::new());
+            if let Some(file) = file {
+                builder = builder.add_file(file);
+            }
+
+            channel_id
+                .send_message(&ctx.http, builder)
+                .await
+                .map(|_| ())
+        }
+        ModLogTarget::Webhook(url) => execute_webhook(ctx, &url, embed, file).await,
+    };
+
+    if let Err(e) = result {
+        println!("Failed to send {event:?} to the mod log of {guild_id}: {e}");
+    }
+}
+
+async fn execute_webhook<'a>(
+    ctx: &serenity::Context,
+    url: &str,
+    embed: CreateEmbed<'a>,
+    file: Option<CreateAttachment<'a>>,
+) -> Result<(), serenity::Error> {
+    let webhook = match WEBHOOKS.get(url) {
+        Some(webhook) => webhook.clone(),
+        None => {
+            let webhook = Webhook::from_url(&ctx.http, url).await?;
+            WEBHOOKS.insert(url.to_owned(), webhook.clone());
+            webhook
+        }
+    };
+
+    let mut builder = ExecuteWebhook::new()
+        .embed(embed)
+        .allowed_mentions(CreateAllowedMentions::new());
+    if let Some(file) = file {
+        builder = builder.add_file(file);
+    }
+
+    if let Err(e) = webhook.execute(&ctx.http, false, builder).await {
+        // the webhook could've been deleted or had its token reset.
+        WEBHOOKS.remove(url);
+        return Err(e);
+    }
+
+    Ok(())
+}
+
+/// Truncates text to fit in an embed field, empty text is shown as such.
+pub(crate) fn field_value(text: &str) -> String {
+    if text.is_empty() {
+        return "*Empty*".to_owned();
+    }
+
+    if text.chars().count() > MAX_FIELD_LENGTH {
+        let truncated = text.chars().take(MAX_FIELD_LENGTH).collect::<String>();
+        return format!("{truncated}...");
+    }
+
+    text.to_owned()
+}
+
+/// Wraps a diff that uses `moth_ansi` colours in a codeblock discord can render.
+pub(crate) fn ansi_block(diff: &str) -> String {
+    let diff = diff.replace("```", "`\u{200b}``");
+    let diff = if diff.chars().count() > 4000 {
+        diff.chars().take(4000).collect::<String>()
+    } else {
+        diff
+    };
+
+    format!("```ansi\n{diff}\n```")
+}
@@ -0,0 +1,48 @@
+use std::sync::Arc;
+
+use lumi::serenity_prelude::{self as serenity, ActivityType, Presence};
+
+use crate::{Data, Error};
+
+/// Records what a member is playing and their status, only for guilds that opted in.
+pub async fn presence_update(
+    ctx: &serenity::Context,
+    presence: &Presence,
+    data: Arc<Data>,
+) -> Result<(), Error> {
+    let Some(guild_id) = presence.guild_id else {
+        return Ok(());
+    };
+
+    if !data
+        .database
+        .get_guild_settings(guild_id)
+        .presence_history()
+    {
+        return Ok(());
+    }
+
+    let is_bot = ctx
+        .cache
+        .guild(guild_id)
+        .and_then(|g| g.members.get(&presence.user.id).map(|m| m.user.bot()))
+        .unwrap_or(false);
+    if is_bot {
+        return Ok(());
+    }
+
+    let mut games = presence
+        .activities
+        .iter()
+        .filter(|a| a.kind == ActivityType::Playing)
+        .map(|a| a.name.as_str())
+        .collect::<Vec<_>>();
+    games.sort_unstable();
+    games.dedup();
+
+    data.database
+        .update_presence(guild_id, presence.user.id, presence.status.name(), &games)
+        .await?;
+
+    Ok(())
+}
@@ -2,15 +2,16 @@ use std::sync::Arc;
 
 use chrono::Utc;
 use lumi::serenity_prelude::{
-    self as serenity, CreateEmbed, CreateEmbedAuthor, CreateEmbedFooter, GuildMemberUpdateEvent,
-    Member,
+    self as serenity, Colour, CreateEmbed, CreateEmbedAuthor, CreateEmbedFooter, GuildId,
+    GuildMemberUpdateEvent, Member,
 };
 use moth_ansi::{HI_GREEN, RESET};
+use moth_core::data::database::{mod_log::ModLogEvent, names::NameChangeKind};
 
 use ::serenity::all::GenericChannelId;
 use small_fixed_array::FixedString;
 
-use crate::{helper::get_guild_name_override, Data, Error};
+use crate::{Data, Error, handlers::mod_log, helper::get_guild_name_override};
 
 pub async fn guild_member_update(
     ctx: &serenity::Context,
@@ -22,6 +23,24 @@ pub async fn guild_member_update(
     let guild_id = event.guild_id;
     let guild_name = get_guild_name_override(ctx, &data, Some(guild_id));
 
+    // the cache won't have these roles when the member leaves, so keep them for the snapshot.
+    if old_if_available.is_none()
+        && data
+            .database
+            .get_guild_settings(guild_id)
+            .role_persistence()
+    {
+        data.database
+            .cache_member_roles(guild_id, event.user.id, event.roles.to_vec());
+    }
+
+    if let Some(new_member) = new
+        && let Err(e) =
+            record_name_changes(&data, guild_id, old_if_available.as_ref(), new_member).await
+    {
+        println!("Failed to record name changes: {e}");
+    }
+
     if let Some(old_member) = old_if_available {
         if let Some(new_member) = new {
             let old_nickname = old_member.nick.as_deref().unwrap_or("None");
@@ -63,14 +82,21 @@ pub async fn guild_member_update(
                     new_member.user.id
                 );
             }
+
+            log_member_update(ctx, &data, guild_id, old_member, new_member).await;
         }
 
         if let Some(timestamp) = event.unusual_dm_activity_until {
             let timestamp = timestamp.timestamp();
-            if guild_id != 98226572468690944 {
+            let settings = data.database.get_guild_settings(guild_id);
+            if !settings.dm_activity_alerts() {
                 return Ok(());
             }
 
+            let Some(alert_channel) = settings.staff_channel else {
+                return Ok(());
+            };
+
             let now_utc = Utc::now().timestamp();
 
             // If this is in the past, it doesn't need to continue.
@@ -83,7 +109,7 @@ pub async fn guild_member_update(
             let old_stamp = data.get_activity_check(event.user.id).await;
 
             let Some(old_stamp) = old_stamp else {
-                dm_activity_new(ctx, event, 0).await?;
+                dm_activity_new(ctx, event, alert_channel, 0).await?;
                 data.new_or_announced(event.user.id, now_utc, timestamp, Some(1))
                     .await;
                 return Ok(());
@@ -93,7 +119,7 @@ pub async fn guild_member_update(
             if let Some(until) = old_stamp.until {
                 // Display a message if its over an hour since the last one.
                 if timestamp - until >= 3600 {
-                    dm_activity_updated(ctx, event, old_stamp.count).await?;
+                    dm_activity_updated(ctx, event, alert_channel, old_stamp.count).await?;
                     data.new_or_announced(
                         event.user.id,
                         now_utc,
@@ -115,7 +141,7 @@ pub async fn guild_member_update(
                     .await;
                 }
             } else {
-                dm_activity_new(ctx, event, old_stamp.count).await?;
+                dm_activity_new(ctx, event, alert_channel, old_stamp.count).await?;
                 data.new_or_announced(event.user.id, now_utc, timestamp, Some(old_stamp.count + 1))
                     .await;
             }
@@ -125,9 +151,118 @@ pub async fn guild_member_update(
     Ok(())
 }
 
+/// Records what changed, when the old member wasn't cached every value is passed on as is and
+/// only stored if it differs from the last stored one.
+async fn record_name_changes(
+    data: &Data,
+    guild_id: GuildId,
+    old: Option<&Member>,
+    new: &Member,
+) -> Result<(), Error> {
+    let values = |member: &Member| {
+        [
+            (NameChangeKind::Username, Some(member.user.tag())),
+            (
+                NameChangeKind::DisplayName,
+                member.user.global_name.as_deref().map(str::to_owned),
+            ),
+            (
+                NameChangeKind::Nickname,
+                member.nick.as_deref().map(str::to_owned),
+            ),
+            (
+                NameChangeKind::Avatar,
+                member.user.avatar.map(|h| h.to_string()),
+            ),
+            (
+                NameChangeKind::GuildAvatar,
+                member.avatar.map(|h| h.to_string()),
+            ),
+        ]
+    };
+
+    let before = old.map(values);
+    for (i, (kind, after)) in values(new).into_iter().enumerate() {
+        let changed = match &before {
+            Some(before) => before[i].1 != after,
+            // nothing to compare to, so the stored history decides. unset values are skipped as
+            // they would be stored for everyone that never set one.
+            None => after.is_some(),
+        };
+
+        if changed {
+            let before = before.as_ref().and_then(|b| b[i].1.clone());
+            data.database
+                .insert_name_change(
+                    new.user.id,
+                    guild_id,
+                    kind,
+                    before.as_deref(),
+                    after.as_deref(),
+                )
+                .await?;
+        }
+    }
+
+    Ok(())
+}
+
+async fn log_member_update(
+    ctx: &serenity::Context,
+    data: &Data,
+    guild_id: GuildId,
+    old: &Member,
+    new: &Member,
+) {
+    if !mod_log::enabled(data, guild_id, ModLogEvent::MemberUpdate)
+        || mod_log::is_ignored(data, Some(new.user.id), None)
+    {
+        return;
+    }
+
+    let name = |n: Option<&str>| n.unwrap_or("None").to_owned();
+    let changes = [
+        (
+            "Nickname",
+            name(old.nick.as_deref()),
+            name(new.nick.as_deref()),
+        ),
+        ("Username", old.user.tag(), new.user.tag()),
+        (
+            "Display name",
+            name(old.user.global_name.as_deref()),
+            name(new.user.global_name.as_deref()),
+        ),
+    ];
+
+    let mut embed = CreateEmbed::new()
+        .author(CreateEmbedAuthor::new(new.user.tag()).icon_url(new.user.face()))
+        .title("Member updated")
+        .description(format!("<@{}>", new.user.id))
+        .footer(CreateEmbedFooter::new(format!("User ID: {}", new.user.id)))
+        .colour(Colour::BLUE);
+
+    let mut changed = false;
+    for (field, before, after) in changes {
+        if before != after {
+            changed = true;
+            embed = embed.field(
+                field,
+                mod_log::field_value(&format!("{before} -> {after}")),
+                false,
+            );
+        }
+    }
+
+    if changed {
+        mod_log::send(ctx, data, guild_id, ModLogEvent::MemberUpdate, embed).await;
+    }
+}
+
 async fn dm_activity_new(
     ctx: &serenity::Context,
     event: &GuildMemberUpdateEvent,
+    alert_channel: GenericChannelId,
     count: i16,
 ) -> Result<(), Error> {
     let user_ping = format!("<@{}>", event.user.id);
@@ -183,7 +318,7 @@ async fn dm_activity_new(
         embed = embed.description(format!("**Online on**:\n{stats}"));
     }
 
-    GenericChannelId::new(158484765136125952)
+    alert_channel
         .send_message(&ctx.http, serenity::CreateMessage::default().embed(embed))
         .await?;
 
@@ -193,6 +328,7 @@ async fn dm_activity_new(
 async fn dm_activity_updated(
     ctx: &serenity::Context,
     event: &GuildMemberUpdateEvent,
+    alert_channel: GenericChannelId,
     count: i16,
 ) -> Result<(), Error> {
     let user_ping = format!("<@{}>", event.user.id);
@@ -246,7 +382,7 @@ async fn dm_activity_updated(
         embed = embed.description(format!("**Online on**:\n{stats}"));
     }
 
-    GenericChannelId::new(158484765136125952)
+    alert_channel
         .send_message(&ctx.http, serenity::CreateMessage::default().embed(embed))
         .await?;
 
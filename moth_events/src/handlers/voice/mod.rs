@@ -7,12 +7,17 @@ use crate::{
 use lumi::serenity_prelude::{self as serenity, VoiceState};
 use moth_ansi::{GREEN, RESET};
 pub mod private;
+pub mod sessions;
 
 pub async fn voice_state_update(
     ctx: &serenity::Context,
     old: &Option<VoiceState>,
     new: &VoiceState,
 ) -> Result<(), Error> {
+    if let Err(e) = sessions::track(ctx, new).await {
+        println!("Failed to track voice session: {e}");
+    }
+
     if let Some(old) = old {
         if old.channel_id != new.channel_id && new.channel_id.is_some() {
             handle_switch(ctx, old, new).await?;
@@ -20,7 +25,7 @@ pub async fn voice_state_update(
             handle_leave(ctx, old, new).await?;
         }
 
-        // third case where mutes and other changes happen.
+        // third case where mutes and other changes happen, only sessions care about these.
     } else {
         handle_joins(ctx, new).await?;
     }
@@ -1,7 +1,4 @@
-use moth_core::data::{
-    database::private_vcs::task::{GUILD, VC_CHANNEL},
-    structs::Data,
-};
+use moth_core::data::structs::Data;
 use serenity::all::{Context, User, VoiceState};
 
 /// Check if a user is in the channel creation channel, and moves them if they are.
@@ -11,10 +8,21 @@ pub async fn check_channel(
     new_state: &VoiceState,
     user: Option<&User>,
 ) {
-    if new_state.guild_id != Some(GUILD) {
+    let Some(guild_id) = new_state.guild_id else {
+        return;
+    };
+
+    let data = ctx.data_ref::<Data>();
+    let settings = data.database.get_guild_settings(guild_id);
+
+    if !settings.private_vcs() {
         return;
     }
 
+    let Some(vc_channel) = settings.private_vc_channel else {
+        return;
+    };
+
     let (joined_channel_id, left_channel_id) = match old_state {
         Some(old) => {
             let joined = match (old.channel_id, new_state.channel_id) {
@@ -34,22 +42,21 @@ pub async fn check_channel(
         None => (new_state.channel_id, None),
     };
 
-    let data = ctx.data_ref::<Data>();
-
     // Handle joins
     if let Some(joined) = joined_channel_id
         && (data
             .database
-            .get_private_vc(joined, Some(GUILD))
+            .get_private_vc(joined, Some(guild_id))
             .await
             .is_some()
-            || joined == VC_CHANNEL)
+            || joined == vc_channel)
     {
         let Some(user) = user else { return };
 
         data.private_vc
             .sender
             .join(
+                guild_id,
                 joined,
                 new_state.user_id,
                 small_fixed_array::FixedString::from_str_trunc(user.display_name()),
@@ -61,11 +68,14 @@ pub async fn check_channel(
     if let Some(left) = left_channel_id
         && (data
             .database
-            .get_private_vc(left, Some(GUILD))
+            .get_private_vc(left, Some(guild_id))
             .await
             .is_some()
-            || left == VC_CHANNEL)
+            || left == vc_channel)
     {
-        data.private_vc.sender.leave(left, new_state.user_id).await;
+        data.private_vc
+            .sender
+            .leave(guild_id, left, new_state.user_id)
+            .await;
     }
 }
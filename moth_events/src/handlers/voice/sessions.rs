@@ -0,0 +1,76 @@
+use lumi::serenity_prelude::{self as serenity, Guild, VoiceState};
+use moth_core::data::database::voice_sessions::VoiceSnapshot;
+
+use crate::{Data, Error};
+
+fn is_muted(state: &VoiceState) -> bool {
+    state.mute() || state.self_mute()
+}
+
+fn is_deafened(state: &VoiceState) -> bool {
+    state.deaf() || state.self_deaf()
+}
+
+/// Starts, ends or updates the voice session of the member the voice state belongs to.
+///
+/// The open session is used instead of the old voice state, which is missing when it wasn't
+/// cached.
+pub(super) async fn track(ctx: &serenity::Context, new: &VoiceState) -> Result<(), Error> {
+    let Some(guild_id) = new.guild_id else {
+        return Ok(());
+    };
+
+    if new.member.as_ref().is_some_and(|m| m.user.bot()) {
+        return Ok(());
+    }
+
+    let data = ctx.data_ref::<Data>();
+    let database = &data.database;
+
+    let open = database.get_voice_session_channel(guild_id, new.user_id);
+    match (open, new.channel_id) {
+        (Some(open), Some(channel_id)) if open == channel_id => {
+            database.update_voice_session(guild_id, new.user_id, is_muted(new), is_deafened(new));
+        }
+        (_, Some(channel_id)) => {
+            database
+                .start_voice_session(
+                    guild_id,
+                    new.user_id,
+                    channel_id,
+                    is_muted(new),
+                    is_deafened(new),
+                )
+                .await?;
+        }
+        (Some(_), None) => database.end_voice_session(guild_id, new.user_id).await?,
+        (None, None) => {}
+    }
+
+    Ok(())
+}
+
+/// Brings the sessions of a guild in line with the cached voice states, sessions can't be
+/// tracked while the bot is offline or the guild is unavailable.
+pub async fn recover(ctx: &serenity::Context, guild: &Guild) -> Result<(), Error> {
+    let states = guild
+        .voice_states
+        .iter()
+        .filter(|v| !guild.members.get(&v.user_id).is_some_and(|m| m.user.bot()))
+        .filter_map(|v| {
+            Some(VoiceSnapshot {
+                user_id: v.user_id,
+                channel_id: v.channel_id?,
+                muted: is_muted(v),
+                deafened: is_deafened(v),
+            })
+        })
+        .collect::<Vec<_>>();
+
+    ctx.data_ref::<Data>()
+        .database
+        .recover_voice_sessions(guild.id, &states)
+        .await?;
+
+    Ok(())
+}
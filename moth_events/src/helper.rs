@@ -114,6 +114,17 @@ fn retrieve_cached_name(
     }
 }
 
+/// Cuts `content` down to `max` characters, ending it with an ellipsis if anything was cut.
+#[must_use]
+pub fn truncate(content: &str, max: usize) -> String {
+    if content.chars().count() <= max {
+        return content.to_owned();
+    }
+
+    let truncated = content.chars().take(max - 1).collect::<String>();
+    format!("{truncated}…")
+}
+
 #[must_use]
 pub fn channel_type_to_string(channel_type: ChannelType) -> String {
     match channel_type {
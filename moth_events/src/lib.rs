@@ -56,6 +56,21 @@ pub async fn event_handler(
             messages::message_delete(ctx, *channel_id, *deleted_message_id, *guild_id, data)
                 .await?;
         }
+        FullEvent::MessageDeleteBulk {
+            channel_id,
+            multiple_deleted_messages_ids,
+            guild_id,
+            ..
+        } => {
+            messages::message_delete_bulk(
+                ctx,
+                *channel_id,
+                multiple_deleted_messages_ids,
+                *guild_id,
+                data,
+            )
+            .await?;
+        }
         FullEvent::ReactionAdd { add_reaction, .. } => {
             reactions::reaction_add(ctx, add_reaction, data).await?;
         }
@@ -67,11 +82,26 @@ pub async fn event_handler(
         FullEvent::GuildCreate { guild, is_new, .. } => {
             guilds::guild_create(ctx, guild, is_new).await?;
         }
+        FullEvent::GuildDelete { incomplete, .. } => {
+            guilds::guild_delete(data, incomplete).await?;
+        }
         FullEvent::GuildMemberAddition { new_member, .. } => {
             guilds::guild_member_addition(ctx, data, new_member).await?;
         }
-        FullEvent::GuildMemberRemoval { guild_id, user, .. } => {
-            guilds::guild_member_removal(ctx, guild_id, user, data).await?;
+        FullEvent::GuildMemberRemoval {
+            guild_id,
+            user,
+            member_data_if_available,
+            ..
+        } => {
+            guilds::guild_member_removal(
+                ctx,
+                guild_id,
+                user,
+                member_data_if_available.as_ref(),
+                data,
+            )
+            .await?;
         }
         FullEvent::GuildAuditLogEntryCreate {
             entry, guild_id, ..
@@ -153,6 +183,9 @@ pub async fn event_handler(
         } => {
             users::guild_member_update(ctx, old_if_available, new, event, data).await?;
         }
+        FullEvent::PresenceUpdate { new_data, .. } => {
+            presences::presence_update(ctx, new_data, data).await?;
+        }
         FullEvent::Ready { data_about_bot, .. } => {
             misc::ready(ctx, data_about_bot, data).await?;
         }
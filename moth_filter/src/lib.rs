@@ -1,12 +1,16 @@
 use std::fmt::Write;
-use std::sync::{LazyLock, OnceLock};
+use std::sync::LazyLock;
 use std::{borrow::Cow, collections::HashSet};
 
 use regex::Regex;
-use rustrict::{Censor, Trie, Type};
+pub use rustrict::Type;
+use rustrict::{Censor, Trie};
 
 use moth_ansi::{BOLD, RED, RESET};
 
+mod normalise;
+pub use normalise::normalise;
+
 pub static WHITESPACE: std::sync::LazyLock<regex::Regex> =
     std::sync::LazyLock::new(|| regex::Regex::new(r"(\s*)(\S+)").unwrap());
 
@@ -23,20 +27,42 @@ fn get_threshold() -> Type {
         & (Type::MODERATE | Type::SEVERE)
 }
 
-fn get_trie() -> &'static Trie {
-    static TRIE: OnceLock<Trie> = OnceLock::new();
-    TRIE.get_or_init(|| {
+/// The words content is checked against, usually a guild's lists on top of the global ones.
+pub struct WordLists {
+    pub badlist: HashSet<String>,
+    /// Words that contain something on the badlist but are fine.
+    pub fixlist: HashSet<String>,
+    /// rustrict's own list, with any overrides applied.
+    trie: Trie,
+}
+
+impl WordLists {
+    pub fn new(
+        badlist: HashSet<String>,
+        fixlist: HashSet<String>,
+        overrides: impl IntoIterator<Item = (String, Type)>,
+    ) -> Self {
         let mut trie = Trie::default();
-        // patch fix
-        trie.set("fcing", Type::SAFE);
-        trie.set("pp", Type::SAFE);
-        trie.set("ppcat", Type::SAFE);
+        for (word, kind) in overrides {
+            trie.set(&word, kind);
+        }
 
-        trie
-    })
+        Self {
+            badlist,
+            fixlist,
+            trie,
+        }
+    }
+}
+
+impl Default for WordLists {
+    fn default() -> Self {
+        Self::new(HashSet::new(), HashSet::new(), [])
+    }
 }
 
-/// A function that cleans stuff up that rustrict isn't good with.
+/// A function that cleans stuff up that rustrict isn't good with, the result is normalised with
+/// [`normalise`] so lookalikes and leet are seen for what they are.
 pub fn preprocess(content: &str) -> Cow<'_, str> {
     let processed = EMOJI_REGEX.replace_all(content, |caps: &regex::Captures| {
         let matched = caps.get(2).unwrap();
@@ -51,6 +77,8 @@ pub fn preprocess(content: &str) -> Cow<'_, str> {
     let processed = NUMBERS.replace_all(&processed, |_: &regex::Captures| "");
     // links can falsely trigger stuff, so they are ommitted.
     let processed = LINKS.replace_all(&processed, |_: &regex::Captures| "");
+    // done last, folding digits and symbols would stop the patterns above from matching.
+    let processed = normalise(&processed);
 
     if processed == content {
         Cow::Borrowed(content)
@@ -59,37 +87,98 @@ pub fn preprocess(content: &str) -> Cow<'_, str> {
     }
 }
 
-pub fn analyze(content: &str) -> Type {
+pub fn analyze(content: &str, lists: &WordLists) -> Type {
     let processed = preprocess(content);
     let mut censor = Censor::from_str(&processed);
     let censor = censor
-        .with_trie(get_trie())
+        .with_trie(&lists.trie)
         .with_censor_threshold(get_threshold());
 
     censor.analyze()
 }
 
-pub fn filter_content<'a>(
-    content: &'a str,
-    badlist: &HashSet<String>,
-    fixlist: &HashSet<String>,
-) -> Cow<'a, str> {
-    let mut changed_words = Vec::new();
+/// How bad content is according to rustrict, ordered from least to most severe.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    Moderate,
+    Severe,
+}
+
+/// What the filter thinks of some content.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Verdict {
+    /// `None` when the content is below the threshold.
+    pub severity: Option<Severity>,
+    /// If a word contains something on the badlist.
+    pub badlist: bool,
+}
+
+impl Verdict {
+    #[must_use]
+    pub fn is_clean(&self) -> bool {
+        self.severity.is_none() && !self.badlist
+    }
+}
+
+/// Judges content the same way [`filter_content`] highlights it.
+#[must_use]
+pub fn judge(content: &str, lists: &WordLists) -> Verdict {
+    Verdict {
+        severity: severity(analyze(content, lists)),
+        badlist: !badlist_words(content, lists).is_empty(),
+    }
+}
+
+fn severity(analysis: Type) -> Option<Severity> {
+    let categories = Type::PROFANE | Type::OFFENSIVE;
+
+    if analysis.is(categories & Type::SEVERE) {
+        Some(Severity::Severe)
+    } else if analysis.is(categories & Type::MODERATE) {
+        Some(Severity::Moderate)
+    } else {
+        None
+    }
+}
+
+/// Words containing something on the badlist, unless they also contain something on the fixlist.
+/// Words are normalised first, but the original words are returned.
+fn badlist_words<'a>(content: &'a str, lists: &WordLists) -> Vec<&'a str> {
+    content
+        .split_whitespace()
+        .filter(|word| {
+            normalise::word_variants(word).iter().any(|word| {
+                lists
+                    .badlist
+                    .iter()
+                    .any(|badword| word.contains(badword.as_str()))
+                    && !lists
+                        .fixlist
+                        .iter()
+                        .any(|fixword| word.contains(fixword.as_str()))
+            })
+        })
+        .collect()
+}
+
+pub fn filter_content<'a>(content: &'a str, lists: &WordLists) -> Cow<'a, str> {
+    inspect(content, lists).0
+}
+
+/// Both [`filter_content`] and [`judge`] while only analysing the content once.
+#[must_use]
+pub fn inspect<'a>(content: &'a str, lists: &WordLists) -> (Cow<'a, str>, Verdict) {
+    let mut changed_words = badlist_words(content, lists);
+    let badlist = !changed_words.is_empty();
+
     let processed = preprocess(content);
     let mut censor = Censor::from_str(&processed);
     let censor = censor
-        .with_trie(get_trie())
+        .with_trie(&lists.trie)
         .with_censor_threshold(get_threshold());
 
-    content.split_whitespace().for_each(|word| {
-        let is_blacklisted = badlist.iter().any(|badword| word.contains(badword))
-            && !fixlist.iter().any(|fixword| word.contains(fixword));
-        if is_blacklisted {
-            changed_words.push(word);
-        }
-    });
-
-    if censor.analyze() != Type::NONE {
+    let analysis = censor.analyze();
+    if analysis != Type::NONE {
         censor.reset(processed.chars());
         let censored = censor.censor();
 
@@ -110,10 +199,15 @@ pub fn filter_content<'a>(
         }
     }
 
+    let verdict = Verdict {
+        severity: severity(analysis),
+        badlist,
+    };
+
     if changed_words.is_empty() {
-        Cow::Borrowed(content)
+        (Cow::Borrowed(content), verdict)
     } else {
-        Cow::Owned(colour_string(content, &changed_words))
+        (Cow::Owned(colour_string(content, &changed_words)), verdict)
     }
 }
 
@@ -0,0 +1,317 @@
+//! Folds text people use to sneak words past the filter back into plain lowercase ascii.
+//!
+//! Everything works a word at a time and whitespace is left alone, so the words of normalised
+//! text always line up with the words of the original.
+
+use std::borrow::Cow;
+
+use crate::WHITESPACE;
+
+/// Runs of the same character at least this long are treated as stretching.
+const STRETCH_RUN: usize = 3;
+
+/// Normalises every word in the content, keeping the whitespace between them as is.
+pub fn normalise(content: &str) -> Cow<'_, str> {
+    let mut result = String::with_capacity(content.len());
+    for cap in WHITESPACE.captures_iter(content) {
+        result.push_str(&cap[1]);
+        result.push_str(&normalise_word(&cap[2]));
+    }
+
+    // trailing whitespace isn't captured.
+    let trimmed = content.trim_end();
+    result.push_str(&content[trimmed.len()..]);
+
+    if result == content {
+        Cow::Borrowed(content)
+    } else {
+        Cow::Owned(result)
+    }
+}
+
+/// Normalises a word, stretched letters are kept as two so doubled letters still match.
+#[must_use]
+pub fn normalise_word(word: &str) -> String {
+    let folded = fold(word);
+    if folded.is_empty() {
+        // a word made of invisible characters, keeping it means words still line up.
+        return word.to_owned();
+    }
+
+    collapse(&folded, 2)
+}
+
+/// Every form of a word worth matching against a word list, stretched letters are tried as both
+/// one and two letters. The word as written is included, as list entries can contain symbols
+/// that would otherwise be folded away.
+#[must_use]
+pub fn word_variants(word: &str) -> Vec<String> {
+    let mut variants = vec![word.to_lowercase()];
+
+    let folded = fold(word);
+    if folded.is_empty() {
+        return variants;
+    }
+
+    for keep in [2, 1] {
+        let variant = collapse(&folded, keep);
+        if !variants.contains(&variant) {
+            variants.push(variant);
+        }
+    }
+
+    variants
+}
+
+/// Removes invisible characters, folds lookalikes and undoes leet substitutions.
+fn fold(word: &str) -> String {
+    let mut folded = String::with_capacity(word.len());
+    for c in word.chars() {
+        if let Some(c) = fold_char(c) {
+            folded.extend(c.to_lowercase());
+        }
+    }
+
+    // digits and symbols are only letters when they're mixed in with letters.
+    if !folded.chars().any(char::is_alphabetic) {
+        return folded;
+    }
+
+    let chars = folded.chars().collect::<Vec<_>>();
+    let mut result = String::with_capacity(folded.len());
+    for (i, &c) in chars.iter().enumerate() {
+        let followed = chars.get(i + 1).is_some_and(|n| n.is_alphanumeric());
+        result.push(unleet(c, followed).unwrap_or(c));
+    }
+
+    result
+}
+
+/// Shortens runs of the same character that are at least [`STRETCH_RUN`] long to `keep`.
+fn collapse(word: &str, keep: usize) -> String {
+    let mut result = String::with_capacity(word.len());
+    let mut chars = word.chars().peekable();
+    while let Some(c) = chars.next() {
+        let mut run = 1;
+        while chars.next_if_eq(&c).is_some() {
+            run += 1;
+        }
+
+        let len = if run >= STRETCH_RUN { keep } else { run };
+        result.extend(std::iter::repeat_n(c, len));
+    }
+
+    result
+}
+
+/// The letter a leet character stands for, symbols only count when they aren't trailing
+/// punctuation.
+fn unleet(c: char, followed: bool) -> Option<char> {
+    let letter = match c {
+        '0' => 'o',
+        '1' => 'i',
+        '3' => 'e',
+        '4' => 'a',
+        '5' => 's',
+        '7' => 't',
+        '8' => 'b',
+        '9' => 'g',
+        '@' if followed => 'a',
+        '$' if followed => 's',
+        '!' if followed => 'i',
+        '|' if followed => 'l',
+        '+' if followed => 't',
+        '€' if followed => 'e',
+        _ => return None,
+    };
+
+    Some(letter)
+}
+
+/// Folds a character into the ascii character it looks like, `None` for characters that should
+/// be dropped entirely.
+fn fold_char(c: char) -> Option<char> {
+    let cp = c as u32;
+
+    let offset = |base: u32, start: char| char::from_u32(start as u32 + (cp - base));
+
+    match cp {
+        // zero width and other invisible characters.
+        0x00AD | 0x034F | 0x180E | 0x200B..=0x200F | 0x2060..=0x2064 | 0xFEFF => None,
+        // combining marks, used for zalgo text.
+        0x0300..=0x036F | 0x1AB0..=0x1AFF | 0x1DC0..=0x1DFF | 0x20D0..=0x20FF => None,
+        // fullwidth ascii.
+        0xFF01..=0xFF5E => char::from_u32(cp - 0xFEE0),
+        // mathematical alphanumerics, 52 letters for each style.
+        0x1D400..=0x1D6A3 => {
+            let index = (cp - 0x1D400) % 52;
+            if index < 26 {
+                char::from_u32('A' as u32 + index)
+            } else {
+                char::from_u32('a' as u32 + index - 26)
+            }
+        }
+        0x1D7CE..=0x1D7FF => char::from_u32('0' as u32 + (cp - 0x1D7CE) % 10),
+        // enclosed letters.
+        0x249C..=0x24B5 => offset(0x249C, 'a'),
+        0x24B6..=0x24CF => offset(0x24B6, 'A'),
+        0x24D0..=0x24E9 => offset(0x24D0, 'a'),
+        0x1F130..=0x1F149 => offset(0x1F130, 'A'),
+        0x1F150..=0x1F169 => offset(0x1F150, 'A'),
+        0x1F170..=0x1F189 => offset(0x1F170, 'A'),
+        0x1F1E6..=0x1F1FF => offset(0x1F1E6, 'a'),
+        _ => Some(fold_confusable(c)),
+    }
+}
+
+/// Lookalikes from other scripts that are commonly swapped in for latin letters.
+fn fold_confusable(c: char) -> char {
+    match c {
+        // cyrillic
+        'а' | 'А' => 'a',
+        'В' | 'в' => 'b',
+        'с' | 'С' => 'c',
+        'ԁ' => 'd',
+        'е' | 'Е' | 'ё' | 'Ё' => 'e',
+        'һ' | 'Н' | 'н' => 'h',
+        'і' | 'І' | 'ї' | 'Ї' => 'i',
+        'ј' | 'Ј' => 'j',
+        'К' | 'к' => 'k',
+        'М' | 'м' => 'm',
+        'о' | 'О' => 'o',
+        'р' | 'Р' => 'p',
+        'ѕ' | 'Ѕ' => 's',
+        'Т' | 'т' => 't',
+        'у' | 'У' => 'y',
+        'х' | 'Х' => 'x',
+        'ԛ' => 'q',
+        'ԝ' => 'w',
+        // greek
+        'α' | 'Α' => 'a',
+        'β' | 'Β' => 'b',
+        'ε' | 'Ε' => 'e',
+        'Η' => 'h',
+        'ι' | 'Ι' => 'i',
+        'κ' | 'Κ' => 'k',
+        'Μ' => 'm',
+        'ν' | 'Ν' => 'n',
+        'ο' | 'Ο' => 'o',
+        'ρ' | 'Ρ' => 'p',
+        'τ' | 'Τ' => 't',
+        'υ' | 'Υ' => 'y',
+        'χ' | 'Χ' => 'x',
+        'Ζ' => 'z',
+        // letterlike symbols
+        'ℎ' => 'h',
+        'ℯ' | 'ℰ' => 'e',
+        'ℊ' => 'g',
+        'ℓ' | 'ℒ' => 'l',
+        'ℴ' => 'o',
+        'ℂ' => 'c',
+        'ℍ' | 'ℋ' | 'ℌ' => 'h',
+        'ℕ' => 'n',
+        'ℙ' => 'p',
+        'ℚ' => 'q',
+        'ℝ' | 'ℛ' | 'ℜ' => 'r',
+        'ℤ' => 'z',
+        'ℐ' | 'ℑ' => 'i',
+        'ℬ' => 'b',
+        'ℱ' => 'f',
+        'ℳ' => 'm',
+        // small capitals and other latin lookalikes
+        'ᴀ' => 'a',
+        'ʙ' => 'b',
+        'ᴄ' => 'c',
+        'ᴅ' => 'd',
+        'ᴇ' => 'e',
+        'ꜰ' => 'f',
+        'ɢ' | 'ɡ' => 'g',
+        'ʜ' => 'h',
+        'ɪ' | 'ı' => 'i',
+        'ᴊ' => 'j',
+        'ᴋ' => 'k',
+        'ʟ' => 'l',
+        'ᴍ' => 'm',
+        'ɴ' => 'n',
+        'ᴏ' => 'o',
+        'ᴘ' => 'p',
+        'ǫ' => 'q',
+        'ʀ' => 'r',
+        'ꜱ' => 's',
+        'ᴛ' => 't',
+        'ᴜ' => 'u',
+        'ᴠ' => 'v',
+        'ᴡ' => 'w',
+        'ʏ' => 'y',
+        'ᴢ' => 'z',
+        _ => c,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fold_drops_invisible_characters() {
+        assert_eq!(fold("h\u{200b}e\u{0301}l\u{feff}lo"), "hello");
+        assert_eq!(fold("\u{200b}\u{200d}"), "");
+    }
+
+    #[test]
+    fn fold_lookalikes() {
+        assert_eq!(fold("ＨＥＬＬＯ"), "hello");
+        assert_eq!(fold("𝐡𝐞𝐥𝐥𝐨"), "hello");
+        assert_eq!(fold("ⓗⓔⓛⓛⓞ"), "hello");
+        assert_eq!(fold("🇭🇮"), "hi");
+        // cyrillic а, е and о.
+        assert_eq!(fold("bаdwоrdе"), "badworde");
+        assert_eq!(fold("ʜᴇʟʟᴏ"), "hello");
+    }
+
+    #[test]
+    fn fold_undoes_leet() {
+        assert_eq!(fold("h3ll0"), "hello");
+        assert_eq!(fold("@ss"), "ass");
+        assert_eq!(fold("$hit"), "shit");
+        assert_eq!(fold("sh!t"), "shit");
+        // symbols at the end are punctuation, not letters.
+        assert_eq!(fold("wow!"), "wow!");
+        // numbers on their own are left alone.
+        assert_eq!(fold("1337"), "1337");
+    }
+
+    #[test]
+    fn collapse_stretched_letters() {
+        assert_eq!(collapse("heeeello", 2), "heello");
+        assert_eq!(collapse("heeeello", 1), "hello");
+        assert_eq!(collapse("aaa", 1), "a");
+        // doubled letters aren't stretching.
+        assert_eq!(collapse("hello", 1), "hello");
+        assert_eq!(collapse("", 2), "");
+    }
+
+    #[test]
+    fn unleet_symbols_only_when_followed() {
+        assert_eq!(unleet('4', false), Some('a'));
+        assert_eq!(unleet('0', true), Some('o'));
+        assert_eq!(unleet('@', true), Some('a'));
+        assert_eq!(unleet('@', false), None);
+        assert_eq!(unleet('!', false), None);
+        assert_eq!(unleet('x', true), None);
+    }
+
+    #[test]
+    fn words_line_up() {
+        assert_eq!(normalise("h3llo  w0rld "), "hello  world ");
+        assert!(matches!(normalise("hello world"), Cow::Borrowed(_)));
+        // a word of invisible characters is kept so the words still line up.
+        assert_eq!(normalise_word("\u{200b}"), "\u{200b}");
+    }
+
+    #[test]
+    fn variants_try_both_lengths() {
+        assert_eq!(word_variants("HEEEY"), ["heeey", "heey", "hey"]);
+        assert_eq!(word_variants("hey"), ["hey"]);
+    }
+}
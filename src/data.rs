@@ -1,13 +1,18 @@
 use dashmap::DashMap;
-use moth_core::data::{
-    database::reactions::EmoteProcessor,
-    structs::{Data, StarboardConfig, WebServer},
+use moth_core::{
+    data::{
+        database::{
+            reactions::EmoteProcessor,
+            retention::{RetentionConfig, RetentionHandler, RetentionPolicy, RetentionTable},
+        },
+        structs::{Data, StarboardConfig, WebServer},
+    },
+    ocr::OcrConfig,
 };
-use parking_lot::lock_api::Mutex;
 use serenity::all::{GenericChannelId, GuildId, RoleId};
 use std::{
-    collections::VecDeque,
     sync::{Arc, atomic::AtomicBool},
+    time::Duration,
 };
 
 pub async fn setup() -> Arc<Data> {
@@ -35,13 +40,14 @@ pub async fn setup() -> Arc<Data> {
         config: parking_lot::RwLock::new(config),
         anti_delete_cache: moth_core::data::structs::AntiDeleteCache::default(),
         starboard_config,
-        ocr_engine: moth_core::ocr::OcrEngine::new(),
+        ocr_engine: moth_core::ocr::OcrEngine::new(ocr_config())
+            .expect("Failed to start OCR, check OCR_LANGUAGES"),
         new_join_vc: DashMap::default(),
-        osu_game_joins: Mutex::new(VecDeque::new()),
         web: WebServer::new().await,
         auto_pooped,
         emote_processor: EmoteProcessor::default(),
         private_vc: moth_core::data::database::private_vcs::PrivateVcHandler::default(),
+        retention: RetentionHandler::new(retention_config()),
     })
 }
 
@@ -79,3 +85,74 @@ fn starboard_config() -> StarboardConfig {
             .unwrap_or(5),
     }
 }
+
+fn ocr_config() -> OcrConfig {
+    let default = OcrConfig::default();
+
+    OcrConfig {
+        workers: std::env::var("OCR_WORKERS")
+            .ok()
+            .and_then(|val| val.parse::<usize>().ok())
+            .unwrap_or(default.workers),
+        timeout: std::env::var("OCR_TIMEOUT_SECS")
+            .ok()
+            .and_then(|val| val.parse::<u64>().ok())
+            .map_or(default.timeout, Duration::from_secs),
+        languages: std::env::var("OCR_LANGUAGES").unwrap_or(default.languages),
+    }
+}
+
+/// Pruning only runs with `RETENTION_ACTIVE=true` apart from presence history, which is always
+/// pruned. The rest fall back to the defaults:
+/// - `RETENTION_INTERVAL_HOURS`: hours between runs, at least 1.
+/// - `RETENTION_BATCH_SIZE`: rows deleted per statement, at least 1.
+/// - `RETENTION_<TABLE>_DAYS`: days rows are kept for, 0 disables pruning that table.
+/// - `RETENTION_<TABLE>_ROLLUP`: if pruned rows are added to the daily aggregate first.
+fn retention_config() -> RetentionConfig {
+    let default = RetentionConfig::default();
+
+    let policies = RetentionTable::ALL
+        .into_iter()
+        .filter_map(|table| {
+            let key = table.name().to_uppercase();
+            let default_policy = default.policies.iter().find(|p| p.table == table);
+
+            let days = std::env::var(format!("RETENTION_{key}_DAYS"))
+                .ok()
+                .and_then(|val| val.parse::<i64>().ok())
+                .or_else(|| default_policy.map(|p| p.keep_for.num_days()))?;
+
+            if days <= 0 {
+                return None;
+            }
+
+            let rollup = std::env::var(format!("RETENTION_{key}_ROLLUP"))
+                .ok()
+                .and_then(|val| val.parse::<bool>().ok())
+                .unwrap_or_else(|| default_policy.is_none_or(|p| p.rollup));
+
+            Some(RetentionPolicy {
+                table,
+                keep_for: chrono::TimeDelta::days(days),
+                rollup,
+            })
+        })
+        .collect();
+
+    RetentionConfig {
+        interval: std::env::var("RETENTION_INTERVAL_HOURS")
+            .ok()
+            .and_then(|val| val.parse::<u64>().ok())
+            .map_or(default.interval, |hours| {
+                // a zero interval would make the timer panic.
+                Duration::from_secs(hours.max(1) * 60 * 60)
+            }),
+        batch_size: std::env::var("RETENTION_BATCH_SIZE")
+            .ok()
+            .and_then(|val| val.parse::<i64>().ok())
+            // a batch that can't delete anything would never finish.
+            .map_or(default.batch_size, |size| size.max(1)),
+        batch_delay: default.batch_delay,
+        policies,
+    }
+}
@@ -1,8 +1,18 @@
 use ::serenity::{all::CreateAllowedMentions, small_fixed_array::FixedString};
 use lumi::{CreateReply, serenity_prelude as serenity};
-use moth_commands::utils::{handle_cooldown, prefix_bot_perms};
+use moth_commands::{
+    log_invocation,
+    utils::{handle_cooldown, prefix_bot_perms},
+};
 use moth_core::data::structs::{Context, Data, Error, InvocationData};
 
+/// If the command errored because a cooldown set by the command itself is still active.
+async fn on_cooldown(ctx: Context<'_>) -> bool {
+    ctx.invocation_data::<InvocationData>()
+        .await
+        .is_some_and(|data| data.cooldown_remaining.is_some())
+}
+
 async fn handle_command_error(ctx: Context<'_>, error: Error) {
     if let Some(invocation_data) = ctx.invocation_data::<InvocationData>().await
         && let Some(duration) = invocation_data.cooldown_remaining
@@ -99,6 +109,22 @@ async fn handle_argument_parse_error(ctx: Context<'_>, input: Option<String>, er
 }
 
 pub async fn handler(error: lumi::FrameworkError<'_, Data, Error>) {
+    if let Some(ctx) = error.ctx() {
+        // cooldowns and failed checks never ran the command, so they aren't failures of it.
+        let failure = match &error {
+            lumi::FrameworkError::CooldownHit { .. }
+            | lumi::FrameworkError::CommandCheckFailed { .. } => None,
+            lumi::FrameworkError::Command { error, .. } => {
+                (!on_cooldown(ctx).await).then(|| error.to_string())
+            }
+            error => Some(error.to_string()),
+        };
+
+        if let Some(failure) = failure {
+            log_invocation(ctx, Some(&failure)).await;
+        }
+    }
+
     match error {
         lumi::FrameworkError::Command { error, ctx, .. } => handle_command_error(ctx, error).await,
         lumi::FrameworkError::NotAnOwner { .. } | lumi::FrameworkError::UnknownCommand { .. } => {}
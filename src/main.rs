@@ -27,10 +27,17 @@ async fn main() {
 
         command_check: Some(|ctx| Box::pin(moth_commands::command_check(ctx))),
 
+        post_command: |ctx| Box::pin(moth_commands::post_command(ctx)),
+
         skip_checks_for_owners: false,
         ..Default::default()
     };
 
+    // modmail needs to tell commands apart from replies.
+    moth_core::prefix::set_command_names(options.commands.iter().flat_map(|c| {
+        std::iter::once(c.name.to_string()).chain(c.aliases.iter().map(ToString::to_string))
+    }));
+
     let framework = lumi::Framework::new(options);
 
     let token = serenity::Token::from_env("MOTH_TOKEN")
@@ -45,20 +52,6 @@ async fn main() {
 
     let data = data::setup().await;
 
-    /*     {
-        let mut joins = data.osu_game_joins.lock();
-        joins.push_back(UserId::new(111111111111111111));
-        joins.push_back(UserId::new(111111111111111112));
-        joins.push_back(UserId::new(111111111111111113));
-        joins.push_back(UserId::new(111111111111111114));
-        joins.push_back(UserId::new(111111111111111115));
-        joins.push_back(UserId::new(222222222222222221));
-        joins.push_back(UserId::new(222222222222222222));
-        joins.push_back(UserId::new(222222222222222223));
-        joins.push_back(UserId::new(222222222222222224));
-        joins.push_back(UserId::new(222222222222222225));
-    } */
-
     let mut client = serenity::Client::builder(token, intents)
         .framework(framework)
         .data(data.clone())
@@ -102,8 +95,7 @@ async fn try_strip_prefix(msg: &serenity::Message) -> Result<Option<(&str, &str)
         return Ok(Some(msg.content.split_at(accela_prefix.len())));
     }
 
-    let normal_prefixes = ["-", "m!", "m"];
-    for prefix in normal_prefixes {
+    for prefix in moth_core::prefix::PREFIXES {
         if let Some(stripped) = msg.content.strip_prefix(prefix)
             && let Some(first_word) = stripped.split_whitespace().next()
         {